@@ -196,6 +196,7 @@ impl TryFrom<GameServerAllocation> for Target {
             address,
             priority: 0,
             meta,
+            claims: HashMap::new(),
         })
     }
 }
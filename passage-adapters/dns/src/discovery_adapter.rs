@@ -143,6 +143,7 @@ impl DnsDiscoveryAdapter {
                         ("weight".to_string(), srv.weight.to_string()),
                         ("domain".to_string(), domain.to_string()),
                     ]),
+                    claims: HashMap::new(),
                 });
             }
         }
@@ -172,6 +173,7 @@ impl DnsDiscoveryAdapter {
                 address,
                 priority: 0,
                 meta: HashMap::from([("domain".to_string(), domain.to_string())]),
+                claims: HashMap::new(),
             });
         }
 
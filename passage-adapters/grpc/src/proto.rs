@@ -1,6 +1,7 @@
 use crate::error::MissingFieldError;
+use passage_adapters::status::favicon::FaviconCache;
 use passage_adapters::{
-    Client, Error, Player, ServerPlayer, ServerPlayers, ServerStatus, ServerVersion,
+    Client, Error, Player, ServerPlayer, ServerPlayers, ServerStatus, ServerVersion, Session,
 };
 use serde_json::value::RawValue;
 use std::net::{IpAddr, SocketAddr};
@@ -25,6 +26,7 @@ impl From<&passage_adapters::Target> for Target {
                 })
                 .collect(),
             priority: value.priority as u32,
+            claims: value.claims.clone(),
         }
     }
 }
@@ -54,6 +56,7 @@ impl TryFrom<Target> for passage_adapters::Target {
                 .into_iter()
                 .map(|entry| (entry.key, entry.value))
                 .collect(),
+            claims: value.claims,
         })
     }
 }
@@ -75,6 +78,7 @@ impl TryFrom<Profile> for passage_adapters::authentication::Profile {
             name: value.name,
             properties: value.properties.into_iter().map(Into::into).collect(),
             profile_actions: value.profile_actions,
+            claims: value.claims,
         })
     }
 }
@@ -89,11 +93,10 @@ impl From<ProfileProperty> for passage_adapters::authentication::ProfileProperty
     }
 }
 
-impl TryFrom<StatusData> for ServerStatus {
-    type Error = Error;
-
-    fn try_from(value: StatusData) -> Result<Self, Self::Error> {
-        let description = value
+impl StatusData {
+    /// Converts the status data into a status, encoding the favicon with the cache.
+    pub fn into_status(self, favicons: &FaviconCache) -> Result<ServerStatus, Error> {
+        let description = self
             .description
             .map(RawValue::from_string)
             .transpose()
@@ -102,26 +105,28 @@ impl TryFrom<StatusData> for ServerStatus {
                 cause: err.into(),
             })?;
 
-        let favicon = value
+        // the favicon is either a raw image or a data uri
+        let favicon = self
             .favicon
-            .map(String::from_utf8)
-            .transpose()
-            .map_err(|err| Error::FailedParse {
-                adapter_type: "grpc",
-                cause: err.into(),
-            })?;
+            .map(|favicon| {
+                favicons.encode(&favicon).map_err(|err| Error::FailedParse {
+                    adapter_type: "grpc",
+                    cause: err.into(),
+                })
+            })
+            .transpose()?;
 
-        Ok(Self {
-            version: value.version.map(Into::into).ok_or(Error::FailedParse {
+        Ok(ServerStatus {
+            version: self.version.map(Into::into).ok_or(Error::FailedParse {
                 adapter_type: "grpc",
                 cause: Box::new(MissingFieldError {
                     field: "status.version",
                 }),
             })?,
-            players: value.players.map(Into::into),
+            players: self.players.map(Into::into),
             description,
             favicon,
-            enforces_secure_chat: value.enforces_secure_chat,
+            enforces_secure_chat: self.enforces_secure_chat,
         })
     }
 }
@@ -198,6 +203,25 @@ impl From<Player> for PlayerInfo {
         Self {
             name: value.name,
             id: value.id.to_string(),
+            claims: value.claims,
+            cookies: value
+                .cookies
+                .into_iter()
+                .map(|(key, value)| (key, value.to_string()))
+                .collect(),
+            session: value.session.map(Into::into),
+        }
+    }
+}
+
+impl From<Session> for SessionInfo {
+    fn from(value: Session) -> Self {
+        Self {
+            id: value.id.to_string(),
+            first_seen: value.first_seen,
+            hops: value.hops,
+            targets: value.targets,
+            instance: value.instance,
         }
     }
 }
@@ -1,5 +1,6 @@
 use crate::proto::status_client::StatusClient;
 use crate::proto::{Address, StatusRequest};
+use passage_adapters::status::favicon::FaviconCache;
 use passage_adapters::{Client, Error, Result, ServerStatus, metrics, status::StatusAdapter};
 use std::fmt::{Debug, Formatter};
 use tokio::time::Instant;
@@ -13,6 +14,9 @@ const ADAPTER_TYPE: &str = "grpc_status_adapter";
 pub struct GrpcStatusAdapter {
     /// The client by which requests are made.
     client: StatusClient<Channel>,
+
+    /// The cache of the encoded favicons of the responses.
+    favicons: FaviconCache,
 }
 
 impl Debug for GrpcStatusAdapter {
@@ -35,6 +39,7 @@ impl GrpcStatusAdapter {
                     cause: err.into(),
                 }
             })?,
+            favicons: FaviconCache::new(),
         })
     }
 
@@ -62,7 +67,7 @@ impl GrpcStatusAdapter {
             })?
             .into_inner()
             .status
-            .map(TryInto::try_into)
+            .map(|status| status.into_status(&self.favicons))
             .transpose()
     }
 }
@@ -0,0 +1,128 @@
+use crate::HTTP_CLIENT;
+use passage_adapters::Error;
+use passage_adapters::status::favicon::{FaviconSource, encode};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::select;
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, instrument, warn};
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "http_favicon_source";
+
+/// HTTP-based favicon source that fetches the favicon image from a URL. On creation, the source will
+/// start a background task that periodically refreshes the favicon. The task is automatically stopped
+/// once the source is dropped. The favicon is only updated if the image could be fetched and encoded.
+pub struct HttpFaviconSource {
+    /// The encoded favicon. This thread-safe container is shared between the instance and its refresh
+    /// task.
+    inner: Arc<RwLock<Option<String>>>,
+
+    /// The cancellation token used to stop the background refresh task.
+    token: CancellationToken,
+}
+
+impl Debug for HttpFaviconSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HttpFaviconSource")
+    }
+}
+
+impl HttpFaviconSource {
+    /// Creates a new `HttpFaviconSource` that fetches `address` every `cache_duration` seconds.
+    ///
+    /// The background refresh task starts immediately and is cancelled when the source is dropped.
+    /// The `cache_duration` must be greater than zero.
+    pub fn new(address: String, cache_duration: u64) -> Result<Self, Error> {
+        if cache_duration == 0 {
+            return Err(Error::FailedInitialization {
+                adapter_type: ADAPTER_TYPE,
+                cause: "cache duration must be greater than zero".into(),
+            });
+        }
+        let refresh_interval = Duration::from_secs(cache_duration);
+        let inner = Arc::new(RwLock::new(None));
+        let token = CancellationToken::new();
+
+        let _inner = inner.clone();
+        let _token = token.clone();
+        let mut interval = tokio::time::interval(refresh_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        tokio::spawn(async move {
+            info!("starting HTTP favicon background task");
+            loop {
+                select! {
+                    biased;
+                    _ = _token.cancelled() => break,
+                    _ = interval.tick() => {
+                        debug!("refreshing favicon from HTTP");
+                        match Self::fetch(&address).await {
+                            Ok(next) => *_inner.write().await = Some(next),
+                            Err(err) => warn!(err = %err, "Failed favicon refresh")
+                        };
+                    },
+                }
+            }
+            info!("stopped HTTP favicon background task");
+        });
+
+        Ok(Self { inner, token })
+    }
+
+    /// Fetches the favicon image from HTTP and encodes it.
+    #[instrument(skip_all)]
+    async fn fetch(url: &str) -> Result<String, Error> {
+        let image = HTTP_CLIENT
+            // send fetch request
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| Error::FailedFetch {
+                adapter_type: ADAPTER_TYPE,
+                cause: err.into(),
+            })?
+            // handle status codes
+            .error_for_status()
+            .map_err(|err| Error::FailedFetch {
+                adapter_type: ADAPTER_TYPE,
+                cause: err.into(),
+            })?
+            // read the image
+            .bytes()
+            .await
+            .map_err(|err| Error::FailedFetch {
+                adapter_type: ADAPTER_TYPE,
+                cause: err.into(),
+            })?;
+
+        encode(&image).map_err(|err| Error::FailedParse {
+            adapter_type: ADAPTER_TYPE,
+            cause: err.into(),
+        })
+    }
+}
+
+impl Drop for HttpFaviconSource {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+impl FaviconSource for HttpFaviconSource {
+    async fn favicon(&self) -> Option<String> {
+        self.inner.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reject_zero_cache_duration() {
+        let result = HttpFaviconSource::new("http://localhost/favicon.png".to_string(), 0);
+        assert!(matches!(result, Err(Error::FailedInitialization { .. })));
+    }
+}
@@ -1,15 +1,21 @@
 //! This crate provides HTTP-based adapters.
 //!
 //! It contains the [`MojangAdapter`] for official Minecraft authentication and the
-//! [`HttpStatusAdapter`] for polling a remote HTTP endpoint for server status.
+//! [`HttpStatusAdapter`] for polling a remote HTTP endpoint for server status. The [`ProfileCache`]
+//! optionally caches the profiles verified by the [`MojangAdapter`]. The [`HttpFaviconSource`]
+//! fetches the status favicon from a URL.
 
 use std::sync::LazyLock;
 
+pub mod favicon_source;
 pub mod mojang_adapter;
+pub mod profile_cache;
 pub mod status_adapter;
 
 // reexport adapters
+pub use favicon_source::HttpFaviconSource;
 pub use mojang_adapter::MojangAdapter;
+pub use profile_cache::ProfileCache;
 pub use status_adapter::HttpStatusAdapter;
 
 /// The shared HTTP client for all adapters.
@@ -1,23 +1,66 @@
 use crate::HTTP_CLIENT;
-use passage_adapters::authentication::{AuthenticationAdapter, Profile, minecraft_hash};
-use passage_adapters::{Client, Player, metrics, reject};
+use crate::profile_cache::ProfileCache;
+use passage_adapters::authentication::{
+    AuthenticationAdapter, Profile, UNAUTHENTICATED_KEY, minecraft_hash,
+};
+use passage_adapters::backoff::ExponentialBackoff;
+use passage_adapters::{Client, Player, metrics, reject, reject_reason};
+use reqwest::{StatusCode, Url};
 use std::fmt::{Debug, Formatter};
+use std::time::Duration;
 use tokio::time::Instant;
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// The name of the adapter. It is primarily used for logging and metrics.
 const ADAPTER_TYPE: &str = "mojang_authentication_adapter";
 
+/// The default base URL of the session server.
+pub const DEFAULT_BASE_URL: &str = "https://sessionserver.mojang.com";
+
 /// Authentication adapter that validates players against Mojang's session server.
 ///
 /// During login, the client performs an encryption handshake that includes sending its profile to
-/// the Mojang session server. This adapter verifies that handshake by contacting
-/// `sessionserver.mojang.com` and returns the profile if the player has authenticated.
-#[derive(Default)]
+/// the Mojang session server. This adapter verifies that handshake by contacting the session server
+/// (`sessionserver.mojang.com` by default) and returns the profile if the player has authenticated.
+/// Any Yggdrasil-compatible session server (e.g. authlib-injector) can be used by changing the base
+/// URL.
+///
+/// Failed requests (connection errors and server errors) are retried using the configured backoff.
+/// By default, requests are not retried. Optionally, verified profiles are cached in a
+/// [`ProfileCache`], which can also serve as a degraded-mode fallback and limit repeated failures.
 pub struct MojangAdapter {
     /// The ID of this server, it will be included in the Mojang session verification request. Can
     /// be left empty for most use-cases.
     server_id: String,
+
+    /// The base URL of the session server, without the `/session/minecraft/hasJoined` path.
+    base_url: String,
+
+    /// Whether the client ip is sent to the session server. The session server then rejects players
+    /// that authenticated from a different ip (e.g. through a proxy).
+    prevent_proxy_connections: bool,
+
+    /// The timeout of a single session server request (disabled if empty).
+    timeout: Option<Duration>,
+
+    /// The backoff used to retry failed session server requests.
+    backoff: ExponentialBackoff,
+
+    /// The cache of verified profiles (disabled if empty).
+    cache: Option<ProfileCache>,
+}
+
+impl Default for MojangAdapter {
+    fn default() -> Self {
+        Self {
+            server_id: String::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            prevent_proxy_connections: false,
+            timeout: None,
+            backoff: ExponentialBackoff::once(),
+            cache: None,
+        }
+    }
 }
 
 impl MojangAdapter {
@@ -30,8 +73,88 @@ impl MojangAdapter {
         self
     }
 
+    /// Sets the base URL of the session server (builder style). The `/session/minecraft/hasJoined`
+    /// path is appended to it.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sets whether the client ip is sent to the session server (builder style).
+    pub fn with_prevent_proxy_connections(mut self, prevent_proxy_connections: bool) -> Self {
+        self.prevent_proxy_connections = prevent_proxy_connections;
+        self
+    }
+
+    /// Sets the timeout of a single session server request (builder style).
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the backoff used to retry failed session server requests (builder style).
+    pub fn with_backoff(mut self, backoff: ExponentialBackoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the cache of verified profiles (builder style).
+    pub fn with_cache(mut self, cache: Option<ProfileCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
     async fn authenticate(
         &self,
+        client: &Client,
+        player: &Player,
+        shared_secret: &[u8],
+        encoded_public: &[u8],
+    ) -> passage_adapters::Result<Profile> {
+        let Some(cache) = &self.cache else {
+            return self
+                .request(client, player, shared_secret, encoded_public)
+                .await;
+        };
+        let ip = client.address.ip();
+
+        // Use recently verified profiles
+        if let Some(profile) = cache.fresh(&player.name, ip) {
+            debug!("using cached profile");
+            return Ok(profile);
+        }
+
+        // Shield the session server from repeated failures of the same username and ip
+        if cache.is_limited(&player.name, ip) {
+            debug!("username reached failure limit, rejecting");
+            return Err(reject(ADAPTER_TYPE));
+        }
+
+        match self
+            .request(client, player, shared_secret, encoded_public)
+            .await
+        {
+            Ok(profile) => {
+                cache.insert(&player.name, ip, profile.clone());
+                Ok(profile)
+            }
+            Err(err) => {
+                // Use stale profiles while the session server is unavailable
+                if is_unavailable(&err)
+                    && let Some(profile) = cache.stale(&player.name, ip)
+                {
+                    warn!(err = %err, "session server unavailable, using stale cached profile");
+                    return Ok(profile);
+                }
+                cache.record_failure(&player.name, ip);
+                Err(err)
+            }
+        }
+    }
+
+    async fn request(
+        &self,
+        client: &Client,
         player: &Player,
         shared_secret: &[u8],
         encoded_public: &[u8],
@@ -39,29 +162,62 @@ impl MojangAdapter {
         // Calculate the minecraft hash for this secret, key and username
         let hash = minecraft_hash(&self.server_id, shared_secret, encoded_public);
 
-        // Issue a request to the Mojang authentication endpoint
-        let username = &player.name;
-        let url = format!(
-            "https://sessionserver.mojang.com/session/minecraft/hasJoined?username={username}&serverId={hash}"
-        );
-        let response = HTTP_CLIENT
-            .get(&url)
-            .send()
-            .await
-            .map_err(|err| passage_adapters::Error::FailedFetch {
-                adapter_type: ADAPTER_TYPE,
-                cause: Box::new(err),
-            })?
-            .error_for_status()
-            .map_err(|err| passage_adapters::Error::FailedFetch {
-                adapter_type: ADAPTER_TYPE,
-                cause: Box::new(err),
-            })?;
+        // Build the url of the session server endpoint
+        let mut params = vec![("username", player.name.clone()), ("serverId", hash)];
+        if self.prevent_proxy_connections {
+            params.push(("ip", client.address.ip().to_string()));
+        }
+        let url = Url::parse_with_params(
+            &format!("{}/session/minecraft/hasJoined", self.base_url),
+            &params,
+        )
+        .map_err(|err| passage_adapters::Error::FailedFetch {
+            adapter_type: ADAPTER_TYPE,
+            cause: Box::new(err),
+        })?;
+
+        // Issue requests to the session server endpoint with up to max attempts
+        let mut attempt = 1;
+        let response = loop {
+            let mut request = HTTP_CLIENT.get(url.clone());
+            if let Some(timeout) = self.timeout {
+                request = request.timeout(timeout);
+            }
+            let result = request.send().await.and_then(|res| {
+                // retry on server errors only
+                if res.status().is_server_error() {
+                    return res.error_for_status();
+                }
+                Ok(res)
+            });
+
+            match result {
+                Ok(response) => break response,
+                Err(err) => {
+                    let Some(wait_secs) = self.backoff.secs_after(attempt).await else {
+                        return Err(passage_adapters::Error::FailedFetch {
+                            adapter_type: ADAPTER_TYPE,
+                            cause: Box::new(err),
+                        });
+                    };
+                    warn!(err = %err, attempt, "session server request failed, retrying");
+                    tokio::time::sleep(Duration::from_secs(wait_secs)).await;
+                    attempt += 1;
+                }
+            }
+        };
+        let response =
+            response
+                .error_for_status()
+                .map_err(|err| passage_adapters::Error::FailedFetch {
+                    adapter_type: ADAPTER_TYPE,
+                    cause: Box::new(err),
+                })?;
 
         // If the response is empty, then the client did not make an auth request
-        if response.status() == 204 {
+        if response.status() == StatusCode::NO_CONTENT {
             debug!("client did not make an authentication request");
-            return Err(reject(ADAPTER_TYPE));
+            return Err(reject_reason(ADAPTER_TYPE, UNAUTHENTICATED_KEY));
         }
 
         // Parse the response profile
@@ -77,6 +233,19 @@ impl MojangAdapter {
     }
 }
 
+/// Checks whether the error was caused by the session server being unavailable (i.e., connection
+/// errors, timeouts and server errors).
+fn is_unavailable(err: &passage_adapters::Error) -> bool {
+    let passage_adapters::Error::FailedFetch { cause, .. } = err else {
+        return false;
+    };
+    cause.downcast_ref::<reqwest::Error>().is_some_and(|err| {
+        err.is_timeout()
+            || err.is_connect()
+            || err.status().is_some_and(|status| status.is_server_error())
+    })
+}
+
 impl Debug for MojangAdapter {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "MojangAdapter")
@@ -86,14 +255,14 @@ impl Debug for MojangAdapter {
 impl AuthenticationAdapter for MojangAdapter {
     async fn authenticate(
         &self,
-        _client: &Client,
+        client: &Client,
         player: &Player,
         shared_secret: &[u8],
         encoded_public: &[u8],
     ) -> passage_adapters::Result<Profile> {
         let start = Instant::now();
         let profile = self
-            .authenticate(player, shared_secret, encoded_public)
+            .authenticate(client, player, shared_secret, encoded_public)
             .await;
         metrics::adapter_duration::record(ADAPTER_TYPE, start);
         profile
@@ -0,0 +1,185 @@
+use passage_adapters::authentication::Profile;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Cache of verified profiles used by the [`MojangAdapter`](crate::MojangAdapter).
+///
+/// Profiles are keyed by username and client ip. Entries younger than the `ttl` are used instead
+/// of asking the session server (disabled if zero). If a `max_staleness` is configured, older
+/// entries are used as a degraded-mode fallback while the session server is unavailable. Cached
+/// profiles are not proven by the client, so any client behind the same ip can use them.
+///
+/// Independently, the cache counts failed authentications per username and client ip. Once the
+/// `failure_limit` is reached within the `failure_window`, the username is rejected for that ip
+/// without asking the session server until the window has passed.
+#[derive(Debug)]
+pub struct ProfileCache {
+    /// The duration for which a cached profile is used instead of asking the session server
+    /// (disabled if zero).
+    ttl: Duration,
+
+    /// The maximum age of a cached profile that is used while the session server is unavailable
+    /// (disabled if empty).
+    max_staleness: Option<Duration>,
+
+    /// The maximum number of failures per username and ip within the failure window (disabled if
+    /// empty).
+    failure_limit: Option<u32>,
+
+    /// The window in which failures are counted.
+    failure_window: Duration,
+
+    /// The cached profiles with the time at which they were verified.
+    profiles: Mutex<HashMap<(String, IpAddr), (Instant, Profile)>>,
+
+    /// The failure counts per username and ip with the time at which their window started.
+    failures: Mutex<HashMap<(String, IpAddr), (Instant, u32)>>,
+}
+
+impl ProfileCache {
+    /// Creates a new `ProfileCache` with the given ttl, without degraded-mode fallback and without
+    /// failure limit.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_staleness: None,
+            failure_limit: None,
+            failure_window: Duration::ZERO,
+            profiles: Mutex::new(HashMap::new()),
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the maximum age of cached profiles used while the session server is unavailable
+    /// (builder style).
+    pub fn with_max_staleness(mut self, max_staleness: Option<Duration>) -> Self {
+        self.max_staleness = max_staleness;
+        self
+    }
+
+    /// Sets the maximum number of failures per username and ip within the window (builder style).
+    pub fn with_failure_limit(mut self, failure_limit: Option<u32>, window: Duration) -> Self {
+        self.failure_limit = failure_limit;
+        self.failure_window = window;
+        self
+    }
+
+    /// Gets the cached profile if it is younger than the ttl.
+    pub fn fresh(&self, name: &str, ip: IpAddr) -> Option<Profile> {
+        self.get(name, ip, self.ttl)
+    }
+
+    /// Gets the cached profile if the degraded-mode fallback is enabled and the profile is younger
+    /// than the max staleness.
+    pub fn stale(&self, name: &str, ip: IpAddr) -> Option<Profile> {
+        self.get(name, ip, self.max_staleness?)
+    }
+
+    fn get(&self, name: &str, ip: IpAddr, max_age: Duration) -> Option<Profile> {
+        let profiles = self.profiles.lock().expect("profile cache poisoned");
+        let (verified, profile) = profiles.get(&(name.to_string(), ip))?;
+        (verified.elapsed() < max_age).then(|| profile.clone())
+    }
+
+    /// Stores the verified profile and resets the failures of the username and ip.
+    pub fn insert(&self, name: &str, ip: IpAddr, profile: Profile) {
+        let now = Instant::now();
+        let retention = self.ttl.max(self.max_staleness.unwrap_or_default());
+        let mut profiles = self.profiles.lock().expect("profile cache poisoned");
+        profiles.retain(|_, (verified, _)| now.saturating_duration_since(*verified) < retention);
+        profiles.insert((name.to_string(), ip), (now, profile));
+        drop(profiles);
+
+        self.failures
+            .lock()
+            .expect("failure cache poisoned")
+            .remove(&(name.to_string(), ip));
+    }
+
+    /// Checks whether the username and ip reached the failure limit in the current window.
+    pub fn is_limited(&self, name: &str, ip: IpAddr) -> bool {
+        let Some(limit) = self.failure_limit else {
+            return false;
+        };
+        let failures = self.failures.lock().expect("failure cache poisoned");
+        failures
+            .get(&(name.to_string(), ip))
+            .is_some_and(|(start, count)| start.elapsed() < self.failure_window && *count >= limit)
+    }
+
+    /// Records a failed authentication of the username and ip.
+    pub fn record_failure(&self, name: &str, ip: IpAddr) {
+        if self.failure_limit.is_none() {
+            return;
+        }
+        let now = Instant::now();
+        let mut failures = self.failures.lock().expect("failure cache poisoned");
+        failures
+            .retain(|_, (start, _)| now.saturating_duration_since(*start) < self.failure_window);
+        let (_, count) = failures.entry((name.to_string(), ip)).or_insert((now, 0));
+        *count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IP: IpAddr = IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+
+    #[tokio::test(start_paused = true)]
+    async fn expires_fresh_profiles() {
+        let cache = ProfileCache::new(Duration::from_secs(10))
+            .with_max_staleness(Some(Duration::from_secs(60)));
+        cache.insert("Hydrofin", IP, Profile::default());
+        assert!(cache.fresh("Hydrofin", IP).is_some());
+        assert!(cache.fresh("Scrayos", IP).is_none());
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(cache.fresh("Hydrofin", IP).is_none());
+        assert!(cache.stale("Hydrofin", IP).is_some());
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(cache.stale("Hydrofin", IP).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limits_failures() {
+        let cache = ProfileCache::new(Duration::from_secs(10))
+            .with_failure_limit(Some(2), Duration::from_secs(60));
+        cache.record_failure("Hydrofin", IP);
+        assert!(!cache.is_limited("Hydrofin", IP));
+        cache.record_failure("Hydrofin", IP);
+        assert!(cache.is_limited("Hydrofin", IP));
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(!cache.is_limited("Hydrofin", IP));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limits_failures_per_ip() {
+        let other = IpAddr::V4(std::net::Ipv4Addr::new(203, 0, 113, 1));
+        let cache = ProfileCache::new(Duration::from_secs(10))
+            .with_failure_limit(Some(1), Duration::from_secs(60));
+        cache.record_failure("Hydrofin", other);
+        assert!(cache.is_limited("Hydrofin", other));
+        assert!(!cache.is_limited("Hydrofin", IP));
+
+        cache.record_failure("Hydrofin", IP);
+        cache.insert("Hydrofin", IP, Profile::default());
+        assert!(!cache.is_limited("Hydrofin", IP));
+        assert!(cache.is_limited("Hydrofin", other));
+    }
+
+    #[test]
+    fn zero_ttl_disables_fresh_profiles() {
+        let cache =
+            ProfileCache::new(Duration::ZERO).with_max_staleness(Some(Duration::from_secs(60)));
+        cache.insert("Hydrofin", IP, Profile::default());
+        assert!(cache.fresh("Hydrofin", IP).is_none());
+        assert!(cache.stale("Hydrofin", IP).is_some());
+    }
+}
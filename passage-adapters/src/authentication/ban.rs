@@ -0,0 +1,302 @@
+use crate::authentication::{AuthenticationAdapter, AuthenticationMode, Profile};
+use crate::{Client, Player, error::Result, metrics, reject_reason_with};
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use tracing::{debug, trace, warn};
+use uuid::Uuid;
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "ban_authentication_adapter";
+
+/// The localizable message key used for bans with an expiry time.
+pub const DEFAULT_BAN_KEY: &str = "disconnect_banned";
+
+/// The localizable message key used for bans without an expiry time.
+pub const DEFAULT_PERMANENT_BAN_KEY: &str = "disconnect_banned_permanent";
+
+/// A single ban entry. A ban applies to a player if at least one of the configured criteria
+/// matches (OR logic). A ban without any criteria never applies.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+pub struct Ban {
+    /// The banned player id.
+    pub id: Option<Uuid>,
+
+    /// The banned player name (case-insensitive).
+    pub name: Option<String>,
+
+    /// The banned client ip address.
+    pub ip: Option<IpAddr>,
+
+    /// The reason of the ban. It is passed to the message as the `{reason}` parameter.
+    #[serde(default)]
+    pub reason: String,
+
+    /// The localizable message key that is used instead of the default ban message keys.
+    pub key: Option<String>,
+
+    /// The unix timestamp (in seconds) at which the ban expires. The ban is permanent if empty.
+    #[serde(alias = "expiresat")]
+    pub expires_at: Option<u64>,
+}
+
+impl Ban {
+    /// Checks whether the ban applies to the given client address, player id and player name.
+    fn matches(&self, ip: IpAddr, id: Uuid, name: &str) -> bool {
+        self.ip.is_some_and(|item| item == ip)
+            || self.id.is_some_and(|item| item == id)
+            || self
+                .name
+                .as_ref()
+                .is_some_and(|item| item.eq_ignore_ascii_case(name))
+    }
+
+    /// Checks whether the ban has expired at the given unix timestamp (in seconds).
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// The state of the ban file. The bans are re-read whenever the modification time changes.
+#[derive(Debug)]
+struct BanFile {
+    path: PathBuf,
+    state: RwLock<(Option<SystemTime>, Vec<Ban>)>,
+}
+
+/// Authentication adapter that rejects banned players before and after delegating to another
+/// authentication adapter.
+///
+/// Bans can match by player id, player name or client ip address. They are read from the
+/// configuration and, optionally, from a JSON file containing a list of bans. The file is re-read
+/// whenever it changes. The claimed identity of the login request is checked before the inner
+/// adapter is called, so banned players are rejected without a round-trip to the authentication
+/// authority. The authenticated profile is checked again afterward.
+///
+/// Rejections use the ban's key or, by default, [`DEFAULT_BAN_KEY`] (temporary bans) and
+/// [`DEFAULT_PERMANENT_BAN_KEY`] (permanent bans). The message gets the parameters `{reason}`
+/// and `{remaining}` (e.g. `1d 2h 3m`).
+#[derive(Debug)]
+pub struct BanAuthenticationAdapter<T> {
+    adapter: T,
+    bans: Vec<Ban>,
+    file: Option<BanFile>,
+}
+
+impl<T> BanAuthenticationAdapter<T> {
+    /// Creates a new `BanAuthenticationAdapter` wrapping the given adapter. The ban file is loaded
+    /// lazily on the first login request.
+    pub fn new(adapter: T, bans: Vec<Ban>, file: Option<PathBuf>) -> Self {
+        Self {
+            adapter,
+            bans,
+            file: file.map(|path| BanFile {
+                path,
+                state: RwLock::new((None, Vec::new())),
+            }),
+        }
+    }
+
+    /// Re-reads the ban file if it was modified since it was last read. Failures are logged and
+    /// the previously read bans are kept.
+    async fn refresh(&self, file: &BanFile) {
+        let modified = match tokio::fs::metadata(&file.path).await {
+            Ok(metadata) => metadata.modified().ok(),
+            Err(err) => {
+                warn!(err = %err, path = ?file.path, "failed to read ban file metadata");
+                return;
+            }
+        };
+        if file.state.read().await.0 == modified && modified.is_some() {
+            return;
+        }
+
+        debug!(path = ?file.path, "reading ban file");
+        let bans = match tokio::fs::read(&file.path).await {
+            Ok(content) => serde_json::from_slice::<Vec<Ban>>(&content),
+            Err(err) => {
+                warn!(err = %err, path = ?file.path, "failed to read ban file");
+                return;
+            }
+        };
+        match bans {
+            Ok(bans) => *file.state.write().await = (modified, bans),
+            Err(err) => warn!(err = %err, path = ?file.path, "failed to parse ban file"),
+        }
+    }
+
+    /// Rejects the player if any active ban applies to them.
+    async fn check(&self, client: &Client, id: Uuid, name: &str) -> Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time error")
+            .as_secs();
+        let ip = client.address.ip();
+        let active = |ban: &&Ban| !ban.is_expired(now) && ban.matches(ip, id, name);
+
+        if let Some(ban) = self.bans.iter().find(active) {
+            return Err(rejection(ban, now));
+        }
+
+        if let Some(file) = &self.file {
+            self.refresh(file).await;
+            let state = file.state.read().await;
+            if let Some(ban) = state.1.iter().find(active) {
+                return Err(rejection(ban, now));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: AuthenticationAdapter> AuthenticationAdapter for BanAuthenticationAdapter<T> {
+    #[tracing::instrument(skip_all)]
+    async fn authenticate(
+        &self,
+        client: &Client,
+        player: &Player,
+        shared_secret: &[u8],
+        encoded_public: &[u8],
+    ) -> Result<Profile> {
+        trace!("checking bans of claimed player");
+        let start = Instant::now();
+        let checked = self.check(client, player.id, &player.name).await;
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        checked?;
+
+        let profile = self
+            .adapter
+            .authenticate(client, player, shared_secret, encoded_public)
+            .await?;
+
+        trace!("checking bans of authenticated player");
+        let start = Instant::now();
+        let checked = self.check(client, profile.id, &profile.name).await;
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        checked.map(|_| profile)
+    }
+
+    fn mode(&self) -> AuthenticationMode {
+        self.adapter.mode()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn authorize(&self, client: &Client, player: &Player) -> Result<()> {
+        trace!("checking bans of player");
+        let start = Instant::now();
+        let checked = self.check(client, player.id, &player.name).await;
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        checked?;
+        self.adapter.authorize(client, player).await
+    }
+}
+
+/// Builds the rejection for the given ban with the `{reason}` and `{remaining}` parameters.
+fn rejection(ban: &Ban, now: u64) -> crate::Error {
+    debug!(ban = ?ban, "rejecting banned player");
+    let (key, remaining) = match ban.expires_at {
+        Some(expires_at) => (DEFAULT_BAN_KEY, format_remaining(expires_at - now)),
+        None => (DEFAULT_PERMANENT_BAN_KEY, String::new()),
+    };
+    reject_reason_with(
+        ADAPTER_TYPE,
+        ban.key.as_deref().unwrap_or(key),
+        vec![
+            ("{reason}", escape(&ban.reason)),
+            ("{remaining}", remaining),
+        ],
+    )
+}
+
+/// Formats the remaining seconds into a short human-readable duration (e.g. `1d 2h 3m`).
+fn format_remaining(seconds: u64) -> String {
+    let units = [(86_400, "d"), (3_600, "h"), (60, "m")];
+    let mut parts = vec![];
+    let mut rest = seconds;
+    for (unit, suffix) in units {
+        if rest >= unit {
+            parts.push(format!("{}{suffix}", rest / unit));
+            rest %= unit;
+        }
+    }
+    if parts.is_empty() {
+        parts.push(format!("{rest}s"));
+    }
+    parts.join(" ")
+}
+
+/// Escapes the value, so it can be safely substituted into JSON text components.
+fn escape(value: &str) -> String {
+    let quoted = serde_json::to_string(value).expect("strings are always serializable");
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DisabledAuthenticationAdapter, Error};
+
+    fn player(name: &str) -> Player {
+        Player {
+            name: name.to_string(),
+            id: Uuid::new_v4(),
+            claims: Default::default(),
+            cookies: Default::default(),
+            session: None,
+        }
+    }
+
+    #[test]
+    fn format_remaining_units() {
+        assert_eq!(format_remaining(0), "0s");
+        assert_eq!(format_remaining(59), "59s");
+        assert_eq!(format_remaining(60), "1m");
+        assert_eq!(format_remaining(93_780), "1d 2h 3m");
+    }
+
+    #[tokio::test]
+    async fn rejects_banned_name() {
+        let adapter = BanAuthenticationAdapter::new(
+            DisabledAuthenticationAdapter::new(),
+            vec![Ban {
+                name: Some("Hydrofin".to_string()),
+                reason: "cheating".to_string(),
+                expires_at: Some(u64::MAX),
+                ..Default::default()
+            }],
+            None,
+        );
+
+        let result = adapter
+            .authorize(&Client::default(), &player("hydrofin"))
+            .await;
+        let Err(Error::Rejected { reason, params, .. }) = result else {
+            panic!("expected rejection");
+        };
+        assert_eq!(reason.as_deref(), Some(DEFAULT_BAN_KEY));
+        assert_eq!(params[0], ("{reason}", "cheating".to_string()));
+    }
+
+    #[tokio::test]
+    async fn ignores_expired_ban() {
+        let adapter = BanAuthenticationAdapter::new(
+            DisabledAuthenticationAdapter::new(),
+            vec![Ban {
+                ip: Some(Client::default().address.ip()),
+                expires_at: Some(1),
+                ..Default::default()
+            }],
+            None,
+        );
+
+        let result = adapter
+            .authorize(&Client::default(), &player("Scrayos"))
+            .await;
+        assert!(result.is_ok());
+    }
+}
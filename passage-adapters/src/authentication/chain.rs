@@ -0,0 +1,216 @@
+use crate::authentication::{AuthenticationAdapter, AuthenticationMode, Profile};
+use crate::{Client, Error, Player, error::Result, metrics, reject};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, trace};
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "chain_authentication_adapter";
+
+/// A single link of a [`ChainAuthenticationAdapter`].
+#[derive(Debug)]
+pub struct ChainLink<T> {
+    /// The name of the link, used for logging and metrics.
+    name: String,
+
+    /// The adapter that authenticates players for this link.
+    adapter: T,
+
+    /// Whether the next link is tried if this link rejects the player.
+    fallback_on_rejected: bool,
+
+    /// Whether the next link is tried if this link fails (e.g. fetch failure or timeout).
+    fallback_on_failure: bool,
+
+    /// The maximum time the link may take to authenticate a player (disabled if empty).
+    timeout: Option<Duration>,
+}
+
+impl<T> ChainLink<T> {
+    /// Creates a new `ChainLink` that falls back to the next link on failures but not on
+    /// rejections and has no timeout.
+    pub fn new(name: impl Into<String>, adapter: T) -> Self {
+        Self {
+            name: name.into(),
+            adapter,
+            fallback_on_rejected: false,
+            fallback_on_failure: true,
+            timeout: None,
+        }
+    }
+
+    /// Sets whether the next link is tried if this link rejects the player.
+    #[must_use]
+    pub fn with_fallback_on_rejected(mut self, fallback: bool) -> Self {
+        self.fallback_on_rejected = fallback;
+        self
+    }
+
+    /// Sets whether the next link is tried if this link fails.
+    #[must_use]
+    pub fn with_fallback_on_failure(mut self, fallback: bool) -> Self {
+        self.fallback_on_failure = fallback;
+        self
+    }
+
+    /// Sets the maximum time the link may take to authenticate a player.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Authentication adapter that tries multiple authentication adapters in order.
+///
+/// Each link decides whether the next link is tried if it rejects the player or if it fails
+/// (including timeouts). The first profile that is returned is used. If no link authenticates the
+/// player, the error of the last tried link is returned.
+///
+/// As the encryption handshake happens before any link is called, the [`AuthenticationMode`] of
+/// the chain is the mode of its first link. Players restored from an auth cookie have to be
+/// authorized by all links.
+#[derive(Debug)]
+pub struct ChainAuthenticationAdapter<T> {
+    links: Vec<ChainLink<T>>,
+}
+
+impl<T> ChainAuthenticationAdapter<T> {
+    /// Creates a new `ChainAuthenticationAdapter` trying the links in the given order.
+    pub fn new(links: Vec<ChainLink<T>>) -> Self {
+        Self { links }
+    }
+}
+
+impl<T: AuthenticationAdapter> AuthenticationAdapter for ChainAuthenticationAdapter<T> {
+    #[tracing::instrument(skip_all)]
+    async fn authenticate(
+        &self,
+        client: &Client,
+        player: &Player,
+        shared_secret: &[u8],
+        encoded_public: &[u8],
+    ) -> Result<Profile> {
+        trace!(links = self.links.len(), "authenticating chain");
+        let start = Instant::now();
+
+        let mut last_err = reject(ADAPTER_TYPE);
+        for link in &self.links {
+            let future = link
+                .adapter
+                .authenticate(client, player, shared_secret, encoded_public);
+            let result = match link.timeout {
+                Some(timeout) => tokio::time::timeout(timeout, future)
+                    .await
+                    .map_err(|err| {
+                        let cause = Box::new(err);
+                        let err = Error::FailedFetch {
+                            adapter_type: ADAPTER_TYPE,
+                            cause,
+                        };
+                        (err, "timeout")
+                    })
+                    .and_then(|result| result.map_err(|err| (err, "failed"))),
+                None => future.await.map_err(|err| (err, "failed")),
+            };
+
+            let (err, outcome) = match result {
+                Ok(profile) => {
+                    debug!(link = link.name, "chain link authenticated player");
+                    metrics::authentication_chain_links::inc(link.name.clone(), "authenticated");
+                    metrics::adapter_duration::record(ADAPTER_TYPE, start);
+                    return Ok(profile);
+                }
+                Err((err @ Error::Rejected { .. }, _)) => (err, "rejected"),
+                Err((err, outcome)) => (err, outcome),
+            };
+
+            debug!(link = link.name, outcome, err = %err, "chain link did not authenticate player");
+            metrics::authentication_chain_links::inc(link.name.clone(), outcome);
+            let fallback = match err {
+                Error::Rejected { .. } => link.fallback_on_rejected,
+                _ => link.fallback_on_failure,
+            };
+            last_err = err;
+            if !fallback {
+                break;
+            }
+        }
+
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Err(last_err)
+    }
+
+    fn mode(&self) -> AuthenticationMode {
+        self.links
+            .first()
+            .map(|link| link.adapter.mode())
+            .unwrap_or_default()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn authorize(&self, client: &Client, player: &Player) -> Result<()> {
+        for link in &self.links {
+            link.adapter.authorize(client, player).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FixedAuthenticationAdapter;
+
+    fn profile(name: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_rejected() {
+        let chain = ChainAuthenticationAdapter::new(vec![
+            ChainLink::new("first", FixedAuthenticationAdapter::new(None))
+                .with_fallback_on_rejected(true),
+            ChainLink::new(
+                "second",
+                FixedAuthenticationAdapter::new(Some(profile("b"))),
+            ),
+        ]);
+        let player = Player {
+            name: "a".to_string(),
+            id: Default::default(),
+            claims: Default::default(),
+            cookies: Default::default(),
+            session: None,
+        };
+        let result = chain
+            .authenticate(&Client::default(), &player, &[], &[])
+            .await;
+        assert_eq!(result.expect("authenticated").name, "b");
+    }
+
+    #[tokio::test]
+    async fn stops_on_rejected() {
+        let chain = ChainAuthenticationAdapter::new(vec![
+            ChainLink::new("first", FixedAuthenticationAdapter::new(None)),
+            ChainLink::new(
+                "second",
+                FixedAuthenticationAdapter::new(Some(profile("b"))),
+            ),
+        ]);
+        let player = Player {
+            name: "a".to_string(),
+            id: Default::default(),
+            claims: Default::default(),
+            cookies: Default::default(),
+            session: None,
+        };
+        let result = chain
+            .authenticate(&Client::default(), &player, &[], &[])
+            .await;
+        assert!(matches!(result, Err(Error::Rejected { .. })));
+    }
+}
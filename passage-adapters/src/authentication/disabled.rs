@@ -1,5 +1,5 @@
 use crate::authentication::{AuthenticationAdapter, Profile};
-use crate::{Client, Player, error::Result, metrics};
+use crate::{Claims, Client, Player, error::Result, metrics};
 use tokio::time::Instant;
 use tracing::trace;
 
@@ -38,6 +38,7 @@ impl AuthenticationAdapter for DisabledAuthenticationAdapter {
             name: player.name.clone(),
             properties: vec![],
             profile_actions: vec![],
+            claims: Claims::new(),
         })
     }
 }
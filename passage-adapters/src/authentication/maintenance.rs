@@ -0,0 +1,88 @@
+use crate::authentication::{AuthenticationAdapter, AuthenticationMode, Profile};
+use crate::{Client, Player, error::Result, metrics, reject_reason};
+use tokio::time::Instant;
+use tracing::trace;
+use uuid::Uuid;
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "maintenance_authentication_adapter";
+
+/// The localizable message key used to reject players during maintenance.
+pub const MAINTENANCE_KEY: &str = "disconnect_maintenance";
+
+/// Authentication adapter that rejects all players except an allow-list while delegating the
+/// authentication of allowed players to another authentication adapter.
+///
+/// A player is allowed when they match at least one configured username or id. The claimed identity
+/// of the login request is checked before the inner adapter is called and the authenticated profile
+/// is checked again afterward. Rejected players receive the [`MAINTENANCE_KEY`] message.
+#[derive(Debug)]
+pub struct MaintenanceAuthenticationAdapter<T> {
+    adapter: T,
+
+    /// List of player usernames to allow.
+    usernames: Vec<String>,
+
+    /// List of player IDs to allow.
+    ids: Vec<Uuid>,
+}
+
+impl<T> MaintenanceAuthenticationAdapter<T> {
+    /// Creates a new `MaintenanceAuthenticationAdapter` wrapping the given adapter.
+    pub fn new(adapter: T, usernames: Vec<String>, ids: Vec<Uuid>) -> Self {
+        Self {
+            adapter,
+            usernames,
+            ids,
+        }
+    }
+
+    /// Rejects the player if they are not on the allow-list.
+    fn check(&self, id: Uuid, name: &str) -> Result<()> {
+        let allowed = self.ids.contains(&id)
+            || self
+                .usernames
+                .iter()
+                .any(|item| item.eq_ignore_ascii_case(name));
+        if !allowed {
+            trace!("rejecting player during maintenance");
+            return Err(reject_reason(ADAPTER_TYPE, MAINTENANCE_KEY));
+        }
+        Ok(())
+    }
+}
+
+impl<T: AuthenticationAdapter> AuthenticationAdapter for MaintenanceAuthenticationAdapter<T> {
+    #[tracing::instrument(skip_all)]
+    async fn authenticate(
+        &self,
+        client: &Client,
+        player: &Player,
+        shared_secret: &[u8],
+        encoded_public: &[u8],
+    ) -> Result<Profile> {
+        trace!("checking maintenance allow-list of claimed player");
+        metrics::adapter_duration::record(ADAPTER_TYPE, Instant::now());
+        self.check(player.id, &player.name)?;
+
+        let profile = self
+            .adapter
+            .authenticate(client, player, shared_secret, encoded_public)
+            .await?;
+
+        trace!("checking maintenance allow-list of authenticated player");
+        self.check(profile.id, &profile.name).map(|_| profile)
+    }
+
+    fn mode(&self) -> AuthenticationMode {
+        self.adapter.mode()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn authorize(&self, client: &Client, player: &Player) -> Result<()> {
+        trace!("checking maintenance allow-list of player");
+        metrics::adapter_duration::record(ADAPTER_TYPE, Instant::now());
+        self.check(player.id, &player.name)?;
+        self.adapter.authorize(client, player).await
+    }
+}
@@ -1,13 +1,22 @@
+pub mod ban;
+pub mod chain;
 pub mod disabled;
 pub mod fixed;
+pub mod maintenance;
+pub mod offline;
 
-use crate::{Client, Player, error::Result};
+use crate::{Claims, Client, Player, error::Result};
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
+use std::collections::HashMap;
 use std::fmt::Debug;
 use uuid::Uuid;
 
+/// The localizable message key used to reject players that did not authenticate with the session
+/// server although requested. The encryption handshake treats only this rejection as a failure.
+pub const UNAUTHENTICATED_KEY: &str = "disconnect_unauthenticated";
+
 /// The [`AuthenticationAdapter`] is used to provide custom logic for validating a connecting player
 /// against an authentication authority. The default configuration intents using the HTTP adapter
 /// `MojangAdapter,` which implements the official Minecraft authentication protocol.
@@ -27,6 +36,61 @@ pub trait AuthenticationAdapter: Debug + Send + Sync {
         shared_secret: &[u8],
         encoded_public: &[u8],
     ) -> impl Future<Output = Result<Profile>> + Send;
+
+    /// Returns the mode in which the login encryption handshake is performed. The default
+    /// implementation uses [`AuthenticationMode::Online`].
+    fn mode(&self) -> AuthenticationMode {
+        AuthenticationMode::Online
+    }
+
+    /// Authorizes an already identified player before the target is selected.
+    ///
+    /// In contrast to [`authenticate`](Self::authenticate), this is also called for players whose
+    /// identity was restored from a verified auth cookie. The default implementation accepts all
+    /// players.
+    fn authorize(
+        &self,
+        _client: &Client,
+        _player: &Player,
+    ) -> impl Future<Output = Result<()>> + Send {
+        async { Ok(()) }
+    }
+}
+
+impl<T: AuthenticationAdapter> AuthenticationAdapter for Box<T> {
+    fn authenticate(
+        &self,
+        client: &Client,
+        player: &Player,
+        shared_secret: &[u8],
+        encoded_public: &[u8],
+    ) -> impl Future<Output = Result<Profile>> + Send {
+        (**self).authenticate(client, player, shared_secret, encoded_public)
+    }
+
+    fn authorize(
+        &self,
+        client: &Client,
+        player: &Player,
+    ) -> impl Future<Output = Result<()>> + Send {
+        (**self).authorize(client, player)
+    }
+
+    fn mode(&self) -> AuthenticationMode {
+        (**self).mode()
+    }
+}
+
+/// The mode in which the login encryption handshake is performed for an [`AuthenticationAdapter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AuthenticationMode {
+    /// The connection is encrypted and the client authenticates against the session server.
+    #[default]
+    Online,
+    /// The connection is encrypted, but the client does not authenticate against the session server.
+    Offline,
+    /// The encryption handshake is skipped entirely. The shared secret passed to the adapter is empty.
+    Unencrypted,
 }
 
 /// Represents a single Minecraft user profile with all current properties.
@@ -52,6 +116,9 @@ pub struct Profile {
     /// The pending imposed moderative actions of the Minecraft user profile.
     #[serde(default)]
     pub profile_actions: Vec<String>,
+    /// The claims that are signed into the auth cookie. They are not part of the Minecraft profile.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub claims: Claims,
 }
 
 /// Represents a single property of a Minecraft user profile.
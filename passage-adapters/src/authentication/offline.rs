@@ -0,0 +1,106 @@
+use crate::authentication::{AuthenticationAdapter, AuthenticationMode, Profile};
+use crate::{Claims, Client, Player, error::Result, metrics, reject_reason};
+use md5::{Digest, Md5};
+use tokio::time::Instant;
+use tracing::trace;
+use uuid::{Builder, Uuid};
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "offline_authentication_adapter";
+
+/// The localizable message key used to reject players with invalid usernames.
+pub const INVALID_USERNAME_KEY: &str = "disconnect_invalid_username";
+
+/// Authentication adapter that implements the offline-mode semantics of the vanilla server.
+///
+/// Players are not authenticated against the session server. Instead, their profile uses the
+/// claimed username and the deterministic offline UUID (a v3 UUID of `OfflinePlayer:<name>`).
+/// Usernames must consist of 3 to 16 characters of `a-z`, `A-Z`, `0-9` and `_`, otherwise the
+/// player is rejected with the [`INVALID_USERNAME_KEY`] message.
+///
+/// If `encryption` is disabled, the encryption handshake is skipped entirely (like the vanilla
+/// server does in offline-mode).
+#[derive(Debug, Default)]
+pub struct OfflineAuthenticationAdapter {
+    encryption: bool,
+}
+
+impl OfflineAuthenticationAdapter {
+    /// Creates a new `OfflineAuthenticationAdapter`.
+    pub fn new(encryption: bool) -> Self {
+        Self { encryption }
+    }
+}
+
+impl AuthenticationAdapter for OfflineAuthenticationAdapter {
+    #[tracing::instrument(skip_all)]
+    async fn authenticate(
+        &self,
+        _client: &Client,
+        player: &Player,
+        _shared_secret: &[u8],
+        _encoded_public: &[u8],
+    ) -> Result<Profile> {
+        trace!("authenticating offline profile");
+        metrics::adapter_duration::record(ADAPTER_TYPE, Instant::now());
+        if !is_valid_username(&player.name) {
+            return Err(reject_reason(ADAPTER_TYPE, INVALID_USERNAME_KEY));
+        }
+        Ok(Profile {
+            id: offline_uuid(&player.name),
+            name: player.name.clone(),
+            properties: vec![],
+            profile_actions: vec![],
+            claims: Claims::new(),
+        })
+    }
+
+    fn mode(&self) -> AuthenticationMode {
+        if self.encryption {
+            AuthenticationMode::Offline
+        } else {
+            AuthenticationMode::Unencrypted
+        }
+    }
+}
+
+/// Checks whether the username is a valid Minecraft username (3 to 16 characters of `a-z`, `A-Z`,
+/// `0-9` and `_`).
+pub fn is_valid_username(name: &str) -> bool {
+    (3..=16).contains(&name.len())
+        && name
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+/// Computes the offline UUID of a player, as used by the vanilla server in offline-mode. It is
+/// the v3 (name-based, MD5) UUID of `OfflinePlayer:<name>` without a namespace.
+pub fn offline_uuid(name: &str) -> Uuid {
+    let mut hasher = Md5::new();
+    hasher.update("OfflinePlayer:");
+    hasher.update(name);
+    Builder::from_md5_bytes(hasher.finalize().into()).into_uuid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_offline_uuid() {
+        assert_eq!(
+            offline_uuid("Notch"),
+            Uuid::parse_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap()
+        );
+    }
+
+    #[test]
+    fn validates_username() {
+        assert!(is_valid_username("Scrayos"));
+        assert!(is_valid_username("a_b"));
+        assert!(!is_valid_username("ab"));
+        assert!(!is_valid_username("seventeen_chars_x"));
+        assert!(!is_valid_username("in valid"));
+        assert!(!is_valid_username("Ünicode"));
+    }
+}
@@ -7,7 +7,8 @@ use std::fmt::Debug;
 /// The discovery and discovery actions are applied as a chain. Each modifying the result of the previous
 /// link. Beware; discovery actions may alter the list of targets in any way, even replacing it completely.
 ///
-/// After passing the discovery and discovery actions, the first target in the list is selected. The
+/// After passing the discovery and discovery actions, a target with the lowest priority is selected
+/// (the first one, unless the route uses another tie-breaker). The
 /// target priority is used to pass partial ordering information between the adapters. Adapters should
 /// order the targets based on their priority, with lower values indicating higher priority. The priority
 /// value itself may be updated by any adapter in any way (ephemeral).
@@ -0,0 +1,93 @@
+use crate::discovery_action::reprioritize;
+use crate::{Client, DiscoveryActionAdapter, Player, Target, error::Result, metrics};
+use sha1::{Digest, Sha1};
+use tokio::time::Instant;
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "consistent_hash_strategy_adapter";
+
+/// Returns the stable rendezvous score of the player for the target, higher scores are preferred.
+///
+/// The score is shared by all places that pin players to targets, so the same player is mapped to
+/// the same target everywhere.
+pub fn rendezvous_score(player: &Player, target: &Target) -> u64 {
+    let mut hasher = Sha1::new();
+    hasher.update(player.id.as_bytes());
+    hasher.update(target.identifier.as_bytes());
+    let hash = hasher.finalize();
+    u64::from_be_bytes(
+        hash[..8]
+            .try_into()
+            .expect("sha1 hash has at least 8 bytes"),
+    )
+}
+
+/// Discovery action adapter that keeps players on the same target (sticky routing).
+///
+/// Targets are sorted first by priority, then by a score that is computed from the player id and
+/// the target identifier (rendezvous hashing). The same player is therefore always sent to the same
+/// target, as long as it is available. If a target is removed, only its players are moved to other
+/// targets. After sorting, priorities are recomputed starting from zero.
+#[derive(Debug, Default)]
+pub struct ConsistentHashStrategyAdapter;
+
+impl ConsistentHashStrategyAdapter {
+    /// Creates a new `ConsistentHashStrategyAdapter`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DiscoveryActionAdapter for ConsistentHashStrategyAdapter {
+    #[tracing::instrument(skip_all)]
+    async fn apply(
+        &self,
+        _client: &Client,
+        player: &Player,
+        targets: &mut Vec<Target>,
+    ) -> Result<()> {
+        let start = Instant::now();
+        reprioritize(targets, |target| {
+            std::cmp::Reverse(rendezvous_score(player, target))
+        });
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery_action::tests::{create_player, create_target};
+    use uuid::uuid;
+
+    async fn select(player: &Player, ids: &[&str]) -> String {
+        let mut targets = ids.iter().map(|id| create_target(id, 0, &[])).collect();
+        ConsistentHashStrategyAdapter::new()
+            .apply(&Client::default(), player, &mut targets)
+            .await
+            .unwrap();
+        targets[0].identifier.clone()
+    }
+
+    #[tokio::test]
+    async fn test_sticky_selection() {
+        let player = Player {
+            id: uuid!("09879557-e479-45a9-b434-a56377674627"),
+            ..create_player()
+        };
+        let ids = ["t1", "t2", "t3", "t4"];
+
+        // the order of the targets does not matter
+        let selected = select(&player, &ids).await;
+        assert_eq!(select(&player, &["t4", "t3", "t2", "t1"]).await, selected);
+
+        // removing another target does not move the player
+        let remaining: Vec<_> = ids
+            .iter()
+            .copied()
+            .filter(|id| *id == selected || *id != "t1" && *id != "t2")
+            .collect();
+        assert_eq!(select(&player, &remaining).await, selected);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::ping::{Ping, ping};
+use crate::{Client, DiscoveryActionAdapter, Player, Target, error::Result, metrics};
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "health_check_adapter";
+
+/// The number of check intervals after which a target that is no longer discovered is forgotten.
+const FORGET_INTERVALS: u32 = 10;
+
+/// The health state of a single target address.
+#[derive(Debug, Clone)]
+struct TargetHealth {
+    /// Whether the target is currently considered healthy.
+    healthy: bool,
+
+    /// The number of consecutive failed checks.
+    failures: u32,
+
+    /// The number of consecutive successful checks.
+    successes: u32,
+
+    /// The result of the last successful check.
+    ping: Option<Ping>,
+
+    /// The last time the target was part of the discovered targets.
+    last_seen: Instant,
+}
+
+impl TargetHealth {
+    /// Creates the state of a newly discovered target. It is healthy until proven otherwise.
+    fn new(now: Instant) -> Self {
+        Self {
+            healthy: true,
+            failures: 0,
+            successes: 0,
+            ping: None,
+            last_seen: now,
+        }
+    }
+
+    /// Records the result of a check and updates the health, returning whether it changed.
+    fn record(&mut self, ping: Option<Ping>, thresholds: Thresholds) -> bool {
+        let healthy = self.healthy;
+        match ping {
+            Some(ping) => {
+                self.failures = 0;
+                self.successes = self.successes.saturating_add(1);
+                self.ping = Some(ping);
+                if !self.healthy && self.successes >= thresholds.healthy {
+                    self.healthy = true;
+                }
+            }
+            None => {
+                self.successes = 0;
+                self.failures = self.failures.saturating_add(1);
+                if self.healthy && self.failures >= thresholds.unhealthy {
+                    self.healthy = false;
+                }
+            }
+        }
+        healthy != self.healthy
+    }
+}
+
+/// The number of consecutive checks that change the health of a target.
+#[derive(Debug, Clone, Copy)]
+struct Thresholds {
+    /// The number of consecutive failures after which a target is unhealthy.
+    unhealthy: u32,
+
+    /// The number of consecutive successes after which an unhealthy target is healthy again.
+    healthy: u32,
+}
+
+/// Discovery action adapter that removes unhealthy targets.
+///
+/// On creation, the adapter starts a background task that periodically pings every known target
+/// with a status request (server list ping). Targets become known once they pass the adapter and are
+/// forgotten if they are not discovered for a while. A target is unhealthy after `unhealthy_threshold`
+/// consecutive failed checks and recovers after `healthy_threshold` consecutive successful checks.
+///
+/// Healthy targets are annotated with the `online`, `max`, `latency_ms` and `version` metadata of
+/// the last successful check. The task is automatically stopped once the adapter is dropped.
+pub struct HealthCheckAdapter {
+    /// The health of the known targets by address. This thread-safe container is shared between the
+    /// instance and its check task.
+    inner: Arc<RwLock<HashMap<SocketAddr, TargetHealth>>>,
+
+    /// The cancellation token used to stop the background check task.
+    token: CancellationToken,
+}
+
+impl Debug for HealthCheckAdapter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HealthCheckAdapter")
+    }
+}
+
+impl HealthCheckAdapter {
+    /// Creates a new `HealthCheckAdapter` and starts the background check task.
+    ///
+    /// Each known target is checked every `interval`, and each check has to complete within `timeout`.
+    pub fn new(
+        interval: Duration,
+        timeout: Duration,
+        unhealthy_threshold: u32,
+        healthy_threshold: u32,
+    ) -> Self {
+        let inner: Arc<RwLock<HashMap<SocketAddr, TargetHealth>>> = Arc::default();
+        let token = CancellationToken::new();
+        let thresholds = Thresholds {
+            unhealthy: unhealthy_threshold.max(1),
+            healthy: healthy_threshold.max(1),
+        };
+        let forget_after = interval * FORGET_INTERVALS;
+
+        // Start the background check task with the cancellation token for stopping and the shared
+        // health container. The task is configured to skip any missed ticks in case the checks are delayed.
+        let _inner = Arc::clone(&inner);
+        let _token = token.clone();
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        tokio::spawn(async move {
+            info!("starting health check background task");
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = _token.cancelled() => break,
+                    _ = interval.tick() => {
+                        debug!("checking target health");
+                    },
+                }
+                Self::check(&_inner, timeout, thresholds, forget_after).await;
+            }
+            info!("stopping health check background task");
+        });
+
+        Self { inner, token }
+    }
+
+    /// Checks all known targets concurrently and records the results.
+    async fn check(
+        inner: &RwLock<HashMap<SocketAddr, TargetHealth>>,
+        timeout: Duration,
+        thresholds: Thresholds,
+        forget_after: Duration,
+    ) {
+        // forget targets that are no longer discovered
+        let addresses: Vec<SocketAddr> = {
+            let mut inner = inner.write().await;
+            inner.retain(|_, health| health.last_seen.elapsed() < forget_after);
+            inner.keys().copied().collect()
+        };
+
+        let results = join_all(
+            addresses
+                .iter()
+                .map(|address| ping(ADAPTER_TYPE, *address, timeout)),
+        )
+        .await;
+
+        let mut inner = inner.write().await;
+        for (address, result) in addresses.into_iter().zip(results) {
+            let Some(health) = inner.get_mut(&address) else {
+                continue;
+            };
+            let ping = match result {
+                Ok(ping) => Some(ping),
+                Err(err) => {
+                    debug!(address = %address, err = %err, "target health check failed");
+                    None
+                }
+            };
+            if health.record(ping, thresholds) {
+                if health.healthy {
+                    info!(address = %address, "target is healthy again");
+                } else {
+                    warn!(address = %address, failures = health.failures, "target is unhealthy");
+                }
+            }
+        }
+    }
+
+    /// Writes the status of the last successful check into the target metadata.
+    fn annotate(target: &mut Target, ping: &Ping) {
+        if let Some(players) = &ping.status.players {
+            target
+                .meta
+                .insert("online".to_string(), players.online.to_string());
+            target
+                .meta
+                .insert("max".to_string(), players.max.to_string());
+        }
+        target.meta.insert(
+            "latency_ms".to_string(),
+            ping.latency.as_millis().to_string(),
+        );
+        target
+            .meta
+            .insert("version".to_string(), ping.status.version.name.clone());
+    }
+}
+
+impl Drop for HealthCheckAdapter {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+impl DiscoveryActionAdapter for HealthCheckAdapter {
+    #[tracing::instrument(skip_all)]
+    async fn apply(
+        &self,
+        _client: &Client,
+        _player: &Player,
+        targets: &mut Vec<Target>,
+    ) -> Result<()> {
+        let start = Instant::now();
+        let mut inner = self.inner.write().await;
+        targets.retain_mut(|target| {
+            let health = inner
+                .entry(target.address)
+                .or_insert_with(|| TargetHealth::new(start));
+            health.last_seen = start;
+            if !health.healthy {
+                return false;
+            }
+            if let Some(ping) = &health.ping {
+                Self::annotate(target, ping);
+            }
+            true
+        });
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery_action::tests::{self, create_player};
+    use crate::{ServerPlayers, ServerStatus, ServerVersion};
+
+    const THRESHOLDS: Thresholds = Thresholds {
+        unhealthy: 2,
+        healthy: 3,
+    };
+
+    fn create_target(id: &str, address: &str) -> Target {
+        Target {
+            address: address.parse().unwrap(),
+            ..tests::create_target(id, 0, &[])
+        }
+    }
+
+    fn create_ping() -> Ping {
+        Ping {
+            status: ServerStatus {
+                version: ServerVersion {
+                    name: "1.21.5".to_string(),
+                    protocol: 770,
+                },
+                players: Some(ServerPlayers {
+                    online: 3,
+                    max: 20,
+                    sample: None,
+                }),
+                ..Default::default()
+            },
+            latency: Duration::from_millis(12),
+        }
+    }
+
+    #[test]
+    fn unhealthy_after_failures() {
+        let mut health = TargetHealth::new(Instant::now());
+        assert!(!health.record(None, THRESHOLDS));
+        assert!(health.healthy);
+        assert!(health.record(None, THRESHOLDS));
+        assert!(!health.healthy);
+    }
+
+    #[test]
+    fn healthy_after_successes() {
+        let mut health = TargetHealth::new(Instant::now());
+        health.record(None, THRESHOLDS);
+        health.record(None, THRESHOLDS);
+        assert!(!health.record(Some(create_ping()), THRESHOLDS));
+        assert!(!health.record(Some(create_ping()), THRESHOLDS));
+        // a failure resets the consecutive successes
+        assert!(!health.record(None, THRESHOLDS));
+        assert!(!health.record(Some(create_ping()), THRESHOLDS));
+        assert!(!health.record(Some(create_ping()), THRESHOLDS));
+        assert!(health.record(Some(create_ping()), THRESHOLDS));
+        assert!(health.healthy);
+    }
+
+    #[tokio::test]
+    async fn apply_removes_unhealthy() {
+        let adapter =
+            HealthCheckAdapter::new(Duration::from_secs(60), Duration::from_secs(1), 2, 3);
+        let player = create_player();
+        let mut targets = vec![
+            create_target("t1", "127.0.0.1:25565"),
+            create_target("t2", "127.0.0.1:25566"),
+        ];
+        {
+            let mut inner = adapter.inner.write().await;
+            let mut healthy = TargetHealth::new(Instant::now());
+            healthy.record(Some(create_ping()), THRESHOLDS);
+            inner.insert(targets[0].address, healthy);
+            let mut unhealthy = TargetHealth::new(Instant::now());
+            unhealthy.record(None, THRESHOLDS);
+            unhealthy.record(None, THRESHOLDS);
+            inner.insert(targets[1].address, unhealthy);
+        }
+        adapter
+            .apply(&Client::default(), &player, &mut targets)
+            .await
+            .unwrap();
+
+        assert_eq!(targets.len(), 1);
+        let meta = &targets[0].meta;
+        assert_eq!(meta.get("online").map(String::as_str), Some("3"));
+        assert_eq!(meta.get("max").map(String::as_str), Some("20"));
+        assert_eq!(meta.get("latency_ms").map(String::as_str), Some("12"));
+        assert_eq!(meta.get("version").map(String::as_str), Some("1.21.5"));
+    }
+
+    #[tokio::test]
+    async fn apply_keeps_unknown() {
+        let adapter =
+            HealthCheckAdapter::new(Duration::from_secs(60), Duration::from_secs(1), 2, 3);
+        let player = create_player();
+        let mut targets = vec![create_target("t1", "127.0.0.1:25565")];
+        adapter
+            .apply(&Client::default(), &player, &mut targets)
+            .await
+            .unwrap();
+
+        assert_eq!(targets.len(), 1);
+        assert!(targets[0].meta.is_empty());
+        assert!(adapter.inner.read().await.contains_key(&targets[0].address));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::discovery_action::reprioritize;
+use crate::{Client, DiscoveryActionAdapter, Player, Target, error::Result, metrics};
+use tokio::time::Instant;
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "least_players_strategy_adapter";
+
+/// Discovery action adapter that re-orders targets to spread players evenly.
+///
+/// Targets are sorted first by priority, then by ascending player count from the metadata field
+/// named `field`. Targets without a (parseable) player count are sorted last. After sorting,
+/// priorities are recomputed starting from zero.
+#[derive(Debug, Default)]
+pub struct LeastPlayersStrategyAdapter {
+    field: String,
+}
+
+impl LeastPlayersStrategyAdapter {
+    /// Creates a new `LeastPlayersStrategyAdapter`.
+    ///
+    /// `field` is the metadata key on each [`Target`](crate::Target) that holds the current player
+    /// count as a parseable `u32`.
+    pub fn new(field: String) -> Self {
+        Self { field }
+    }
+
+    fn get_players(&self, target: &Target) -> Option<u32> {
+        target
+            .meta
+            .get(&self.field)
+            .and_then(|players| players.parse::<u32>().ok())
+    }
+}
+
+impl DiscoveryActionAdapter for LeastPlayersStrategyAdapter {
+    #[tracing::instrument(skip_all)]
+    async fn apply(
+        &self,
+        _client: &Client,
+        _player: &Player,
+        targets: &mut Vec<Target>,
+    ) -> Result<()> {
+        let start = Instant::now();
+        reprioritize(targets, |target| {
+            self.get_players(target).map_or(u64::MAX, u64::from)
+        });
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery_action::tests::{create_player, create_target, order};
+
+    #[tokio::test]
+    async fn test_least_players_first() {
+        let adapter = LeastPlayersStrategyAdapter::new("players".to_string());
+        let player = create_player();
+        let mut targets = vec![
+            create_target("t1", 0, &[("players", "20")]),
+            create_target("t2", 0, &[]),
+            create_target("t3", 0, &[("players", "5")]),
+            create_target("t4", 0, &[("players", "5")]),
+        ];
+        adapter
+            .apply(&Client::default(), &player, &mut targets)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            order(&targets),
+            vec![("t3", 0), ("t4", 0), ("t1", 1), ("t2", 2)]
+        );
+    }
+}
@@ -1,46 +1,290 @@
 use crate::discovery_action::DiscoveryActionAdapter;
+use crate::geoip::{GeoIpDatabase, GeoLocation};
 use crate::{Client, Player, Target, error::Result, metrics};
+use regex::Regex;
+use semver::{Version, VersionReq};
+use std::borrow::Cow;
+use std::str::FromStr;
+use std::sync::Arc;
 use tokio::time::Instant;
 
 /// The name of the adapter. It is primarily used for logging and metrics.
 const ADAPTER_TYPE: &str = "meta_filter_adapter";
 
-/// Filter operation to apply to a target field.
+/// The error that is returned if a field reference cannot be parsed.
+#[derive(thiserror::Error, Debug)]
+#[error("unknown filter field: {0}")]
+pub struct UnknownFieldError(String);
+
+/// A reference to a field of the target, client or player that is resolved for each request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldRef {
+    /// The identifier of the target (`target.identifier`).
+    TargetIdentifier,
+    /// The address of the target (`target.address`).
+    TargetAddress,
+    /// The priority of the target (`target.priority`).
+    TargetPriority,
+    /// A metadata value of the target (`meta.<key>` or `target.meta.<key>`).
+    TargetMeta(String),
+    /// The protocol version of the client (`client.protocol_version`).
+    ClientProtocolVersion,
+    /// The server address presented by the client (`client.server_address`).
+    ClientServerAddress,
+    /// The server port presented by the client (`client.server_port`).
+    ClientServerPort,
+    /// The address of the client (`client.address`).
+    ClientAddress,
+    /// The ip address of the client (`client.ip`).
+    ClientIp,
+    /// The ISO country code of the client address (`client.country`, requires a GeoIP database).
+    ClientCountry,
+    /// The continent code of the client address (`client.continent`, requires a GeoIP database).
+    ClientContinent,
+    /// The name of the player (`player.name`).
+    PlayerName,
+    /// The id of the player (`player.id`).
+    PlayerId,
+    /// A verified claim of the player (`player.claims.<key>`).
+    PlayerClaim(String),
+    /// A decoded cookie of the player (`player.cookies.<key>`).
+    PlayerCookie(String),
+    /// The id of the session (`session.id`).
+    SessionId,
+    /// The number of previous passes of the session (`session.hops`).
+    SessionHops,
+    /// The time the session was first seen (`session.first_seen`).
+    SessionFirstSeen,
+    /// The target the session was last transferred to (`session.last_target`).
+    SessionLastTarget,
+    /// The Passage instance that created the session (`session.instance`).
+    SessionInstance,
+}
+
+impl FromStr for FieldRef {
+    type Err = UnknownFieldError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let field = match s {
+            "target.identifier" => Self::TargetIdentifier,
+            "target.address" => Self::TargetAddress,
+            "target.priority" => Self::TargetPriority,
+            "client.protocol_version" => Self::ClientProtocolVersion,
+            "client.server_address" => Self::ClientServerAddress,
+            "client.server_port" => Self::ClientServerPort,
+            "client.address" => Self::ClientAddress,
+            "client.ip" => Self::ClientIp,
+            "client.country" => Self::ClientCountry,
+            "client.continent" => Self::ClientContinent,
+            "player.name" => Self::PlayerName,
+            "player.id" => Self::PlayerId,
+            "session.id" => Self::SessionId,
+            "session.hops" => Self::SessionHops,
+            "session.first_seen" => Self::SessionFirstSeen,
+            "session.last_target" => Self::SessionLastTarget,
+            "session.instance" => Self::SessionInstance,
+            _ => {
+                if let Some(key) = s
+                    .strip_prefix("meta.")
+                    .or_else(|| s.strip_prefix("target.meta."))
+                {
+                    Self::TargetMeta(key.to_string())
+                } else if let Some(key) = s.strip_prefix("player.claims.") {
+                    Self::PlayerClaim(key.to_string())
+                } else if let Some(key) = s.strip_prefix("player.cookies.") {
+                    Self::PlayerCookie(key.to_string())
+                } else {
+                    return Err(UnknownFieldError(s.to_string()));
+                }
+            }
+        };
+        Ok(field)
+    }
+}
+
+impl FieldRef {
+    /// Resolves the value of this field for a request, if present.
+    fn resolve<'a>(&self, context: &FilterContext<'a>) -> Option<Cow<'a, str>> {
+        let session = context.player.session.as_ref();
+        let value = match self {
+            Self::TargetIdentifier => Cow::Borrowed(context.target.identifier.as_str()),
+            Self::TargetAddress => Cow::Owned(context.target.address.to_string()),
+            Self::TargetPriority => Cow::Owned(context.target.priority.to_string()),
+            Self::TargetMeta(key) => Cow::Borrowed(context.target.meta.get(key)?.as_str()),
+            Self::ClientProtocolVersion => Cow::Owned(context.client.protocol_version.to_string()),
+            Self::ClientServerAddress => Cow::Borrowed(context.client.server_address.as_str()),
+            Self::ClientServerPort => Cow::Owned(context.client.server_port.to_string()),
+            Self::ClientAddress => Cow::Owned(context.client.address.to_string()),
+            Self::ClientIp => Cow::Owned(context.client.address.ip().to_string()),
+            Self::ClientCountry => Cow::Borrowed(context.location?.country.as_deref()?),
+            Self::ClientContinent => Cow::Borrowed(context.location?.continent.as_deref()?),
+            Self::PlayerName => Cow::Borrowed(context.player.name.as_str()),
+            Self::PlayerId => Cow::Owned(context.player.id.to_string()),
+            Self::PlayerClaim(key) => Cow::Borrowed(context.player.claims.get(key)?.as_str()),
+            Self::PlayerCookie(key) => match context.player.cookies.get(key)? {
+                serde_json::Value::String(value) => Cow::Borrowed(value.as_str()),
+                serde_json::Value::Null => return None,
+                value => Cow::Owned(value.to_string()),
+            },
+            Self::SessionId => Cow::Owned(session?.id.to_string()),
+            Self::SessionHops => Cow::Owned(session?.hops.to_string()),
+            Self::SessionFirstSeen => Cow::Owned(session?.first_seen.to_string()),
+            Self::SessionLastTarget => Cow::Borrowed(session?.last_target()?),
+            Self::SessionInstance => Cow::Borrowed(session?.instance.as_deref()?),
+        };
+        Some(value)
+    }
+}
+
+/// The value of a filter operation, either a literal or a reference to a field of the request.
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterValue {
+    /// A literal value.
+    Literal(String),
+    /// A field that is resolved for each request.
+    Field(FieldRef),
+}
+
+impl FilterValue {
+    /// Resolves the value for a request. Missing fields resolve to [`None`].
+    fn resolve<'a>(&'a self, context: &FilterContext<'a>) -> Option<Cow<'a, str>> {
+        match self {
+            FilterValue::Literal(value) => Some(Cow::Borrowed(value.as_str())),
+            FilterValue::Field(field) => field.resolve(context),
+        }
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        Self::Literal(value)
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        Self::Literal(value.to_string())
+    }
+}
+
+impl From<FieldRef> for FilterValue {
+    fn from(value: FieldRef) -> Self {
+        Self::Field(value)
+    }
+}
+
+/// The request data that filters are evaluated against.
+struct FilterContext<'a> {
+    client: &'a Client,
+    player: &'a Player,
+    target: &'a Target,
+    location: Option<&'a GeoLocation>,
+}
+
+/// Filter operation to apply to a target field.
+#[derive(Debug, Clone)]
 pub enum FilterOperation {
     /// Field must equal the specified value.
-    Equals(String),
+    Equals(FilterValue),
     /// Field must not equal the specified value.
-    NotEquals(String),
+    NotEquals(FilterValue),
     /// Field must exist (have any value).
     Exists,
     /// Field must not exist.
     NotExists,
     /// Field must be one of the specified values.
-    In(Vec<String>),
+    In(Vec<FilterValue>),
     /// Field must not be any of the specified values.
-    NotIn(Vec<String>),
+    NotIn(Vec<FilterValue>),
+    /// Field must be a number less than the specified value.
+    LessThan(FilterValue),
+    /// Field must be a number less than or equal to the specified value.
+    LessThanOrEquals(FilterValue),
+    /// Field must be a number greater than the specified value.
+    GreaterThan(FilterValue),
+    /// Field must be a number greater than or equal to the specified value.
+    GreaterThanOrEquals(FilterValue),
+    /// Field must be a version that satisfies the specified requirement.
+    Version(VersionReq),
+    /// Field must match the specified regex.
+    Matches(Regex),
+    /// Field must not match the specified regex.
+    NotMatches(Regex),
 }
 
 impl FilterOperation {
     /// Check if a field value matches this filter operation.
-    fn matches(&self, field_value: Option<&str>) -> bool {
+    fn matches<'a>(&'a self, field_value: Option<&str>, context: &FilterContext<'a>) -> bool {
         match self {
-            FilterOperation::Equals(value) => field_value == Some(value.as_str()),
-            FilterOperation::NotEquals(value) => field_value != Some(value.as_str()),
+            FilterOperation::Equals(value) => {
+                field_value.is_some_and(|v| value.resolve(context).as_deref() == Some(v))
+            }
+            FilterOperation::NotEquals(value) => {
+                field_value.is_none_or(|v| value.resolve(context).as_deref() != Some(v))
+            }
             FilterOperation::Exists => field_value.is_some(),
             FilterOperation::NotExists => field_value.is_none(),
-            FilterOperation::In(values) => {
-                field_value.is_some_and(|v| values.iter().any(|val| val == v))
+            FilterOperation::In(values) => field_value.is_some_and(|v| {
+                values
+                    .iter()
+                    .any(|val| val.resolve(context).as_deref() == Some(v))
+            }),
+            FilterOperation::NotIn(values) => !field_value.is_some_and(|v| {
+                values
+                    .iter()
+                    .any(|val| val.resolve(context).as_deref() == Some(v))
+            }),
+            FilterOperation::LessThan(value) => {
+                compare_numbers(field_value, value.resolve(context), |a, b| a < b)
             }
-            FilterOperation::NotIn(values) => {
-                field_value.is_none()
-                    || field_value.is_some_and(|v| !values.iter().any(|val| val == v))
+            FilterOperation::LessThanOrEquals(value) => {
+                compare_numbers(field_value, value.resolve(context), |a, b| a <= b)
             }
+            FilterOperation::GreaterThan(value) => {
+                compare_numbers(field_value, value.resolve(context), |a, b| a > b)
+            }
+            FilterOperation::GreaterThanOrEquals(value) => {
+                compare_numbers(field_value, value.resolve(context), |a, b| a >= b)
+            }
+            FilterOperation::Version(requirement) => field_value
+                .and_then(parse_version)
+                .is_some_and(|version| requirement.matches(&version)),
+            FilterOperation::Matches(regex) => field_value.is_some_and(|v| regex.is_match(v)),
+            FilterOperation::NotMatches(regex) => field_value.is_none_or(|v| !regex.is_match(v)),
         }
     }
 }
 
+/// Compares two values numerically. Missing or non-numeric values never match.
+fn compare_numbers(
+    field_value: Option<&str>,
+    value: Option<Cow<str>>,
+    compare: impl Fn(f64, f64) -> bool,
+) -> bool {
+    let Some(field_value) = field_value.and_then(|v| v.trim().parse::<f64>().ok()) else {
+        return false;
+    };
+    let Some(value) = value.and_then(|v| v.trim().parse::<f64>().ok()) else {
+        return false;
+    };
+    compare(field_value, value)
+}
+
+/// Parses a version leniently, missing minor and patch components (e.g. `1.21`) are filled with
+/// zeros and a leading `v` is ignored.
+pub fn parse_version(value: &str) -> Option<Version> {
+    let value = value.trim();
+    let value = value.strip_prefix('v').unwrap_or(value);
+    let end = value.find(['-', '+']).unwrap_or(value.len());
+    let (core, suffix) = value.split_at(end);
+    let padding = match core.matches('.').count() {
+        0 => ".0.0",
+        1 => ".0",
+        _ => "",
+    };
+    Version::parse(&format!("{core}{padding}{suffix}")).ok()
+}
+
 /// A single filter rule.
 #[derive(Debug, Clone)]
 pub struct FilterRule {
@@ -52,42 +296,111 @@ pub struct FilterRule {
 
 impl FilterRule {
     /// Check if a target matches this filter rule.
-    fn matches(&self, target: &Target) -> bool {
-        let field_value = target.meta.get(&self.key).map(|s| s.as_str());
-        self.operation.matches(field_value)
+    fn matches<'a>(&'a self, context: &FilterContext<'a>) -> bool {
+        let field_value = context.target.meta.get(&self.key).map(|s| s.as_str());
+        self.operation.matches(field_value, context)
+    }
+}
+
+/// A filter that is either a single rule or a logical group of filters.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// A single filter rule.
+    Rule(FilterRule),
+    /// All filters must match (AND logic). An empty group matches.
+    All(Vec<Filter>),
+    /// Any filter must match (OR logic). An empty group does not match.
+    Any(Vec<Filter>),
+    /// The filter must not match.
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    /// Check if a target matches this filter.
+    fn matches<'a>(&'a self, context: &FilterContext<'a>) -> bool {
+        match self {
+            Filter::Rule(rule) => rule.matches(context),
+            Filter::All(filters) => filters.iter().all(|filter| filter.matches(context)),
+            Filter::Any(filters) => filters.iter().any(|filter| filter.matches(context)),
+            Filter::Not(filter) => !filter.matches(context),
+        }
+    }
+}
+
+impl From<FilterRule> for Filter {
+    fn from(value: FilterRule) -> Self {
+        Self::Rule(value)
     }
 }
 
 /// Discovery action adapter that removes targets that do not match a set of metadata rules.
 ///
-/// All rules must match (AND logic). An empty rule set accepts every target.
+/// All rules must match (AND logic). An empty rule set accepts every target. Rules can be nested
+/// into any-of, all-of and not groups. The location of the client is resolved with the GeoIP
+/// database (if set), so rules can reference it.
 #[derive(Debug, Default)]
 pub struct MetaFilterAdapter {
-    /// List of filter rules. All rules must match (AND logic).
-    rules: Vec<FilterRule>,
+    /// List of filters. All filters must match (AND logic).
+    rules: Vec<Filter>,
+
+    /// The database that resolves the location of the client (disabled if empty).
+    geoip: Option<Arc<GeoIpDatabase>>,
 }
 
 impl MetaFilterAdapter {
-    /// Creates a new `MetaFilterAdapter` with the given filter rules.
-    pub fn new(rules: Vec<FilterRule>) -> Self {
-        Self { rules }
+    /// Creates a new `MetaFilterAdapter` with the given filters.
+    pub fn new(rules: Vec<Filter>) -> Self {
+        Self { rules, geoip: None }
+    }
+
+    /// Sets the database that resolves the location of the client (builder style).
+    pub fn with_geoip(mut self, geoip: Option<Arc<GeoIpDatabase>>) -> Self {
+        self.geoip = geoip;
+        self
     }
 
     /// Adds a single filter rule and returns the updated adapter (builder style).
-    pub fn add_rule(mut self, key: String, operation: FilterOperation) -> Self {
-        self.rules.push(FilterRule { key, operation });
+    pub fn add_rule(self, key: String, operation: FilterOperation) -> Self {
+        self.add_filter(Filter::Rule(FilterRule { key, operation }))
+    }
+
+    /// Adds a filter and returns the updated adapter (builder style).
+    pub fn add_filter(mut self, filter: Filter) -> Self {
+        self.rules.push(filter);
         self
     }
 
     /// Returns `true` if `target` satisfies every rule in this adapter.
-    pub fn matches_filters(&self, target: &Target) -> bool {
+    pub fn matches_filters(&self, client: &Client, player: &Player, target: &Target) -> bool {
+        let location = self.locate(client);
+        self.matches_location(client, player, target, location.as_ref())
+    }
+
+    /// Resolves the location of the client, if a database is set.
+    fn locate(&self, client: &Client) -> Option<GeoLocation> {
+        self.geoip.as_ref()?.lookup(client.address.ip())
+    }
+
+    fn matches_location(
+        &self,
+        client: &Client,
+        player: &Player,
+        target: &Target,
+        location: Option<&GeoLocation>,
+    ) -> bool {
         // Empty rules means accept all targets
         if self.rules.is_empty() {
             return true;
         }
 
         // All rules must match (AND logic)
-        self.rules.iter().all(|rule| rule.matches(target))
+        let context = FilterContext {
+            client,
+            player,
+            target,
+            location,
+        };
+        self.rules.iter().all(|rule| rule.matches(&context))
     }
 }
 
@@ -95,12 +408,13 @@ impl DiscoveryActionAdapter for MetaFilterAdapter {
     #[tracing::instrument(skip_all)]
     async fn apply(
         &self,
-        _client: &Client,
-        _player: &Player,
+        client: &Client,
+        player: &Player,
         targets: &mut Vec<Target>,
     ) -> Result<()> {
         let start = Instant::now();
-        targets.retain(|target| self.matches_filters(target));
+        let location = self.locate(client);
+        targets.retain(|target| self.matches_location(client, player, target, location.as_ref()));
         metrics::adapter_duration::record(ADAPTER_TYPE, start);
         Ok(())
     }
@@ -109,6 +423,21 @@ impl DiscoveryActionAdapter for MetaFilterAdapter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::geoip::tests::{create_database, location};
+
+    fn create_player() -> Player {
+        Player {
+            name: "Hydrofin".to_string(),
+            id: Default::default(),
+            claims: [("region".to_string(), "eu-west".to_string())].into(),
+            cookies: Default::default(),
+            session: None,
+        }
+    }
+
+    fn matches(filter: &MetaFilterAdapter, target: &Target) -> bool {
+        filter.matches_filters(&Client::default(), &create_player(), target)
+    }
 
     fn create_target(id: &str, meta: Vec<(&str, &str)>) -> Target {
         Target {
@@ -119,6 +448,7 @@ mod tests {
                 .into_iter()
                 .map(|(k, v)| (k.to_string(), v.to_string()))
                 .collect(),
+            claims: Default::default(),
         }
     }
 
@@ -126,28 +456,28 @@ mod tests {
     fn test_equals_filter() {
         let filter = MetaFilterAdapter::default().add_rule(
             "region".to_string(),
-            FilterOperation::Equals("us-west".to_string()),
+            FilterOperation::Equals("us-west".into()),
         );
 
         let target1 = create_target("t1", vec![("region", "us-west")]);
         let target2 = create_target("t2", vec![("region", "us-east")]);
 
-        assert!(filter.matches_filters(&target1));
-        assert!(!filter.matches_filters(&target2));
+        assert!(matches(&filter, &target1));
+        assert!(!matches(&filter, &target2));
     }
 
     #[test]
     fn test_not_equals_filter() {
         let filter = MetaFilterAdapter::default().add_rule(
             "region".to_string(),
-            FilterOperation::NotEquals("us-west".to_string()),
+            FilterOperation::NotEquals("us-west".into()),
         );
 
         let target1 = create_target("t1", vec![("region", "us-west")]);
         let target2 = create_target("t2", vec![("region", "us-east")]);
 
-        assert!(!filter.matches_filters(&target1));
-        assert!(filter.matches_filters(&target2));
+        assert!(!matches(&filter, &target1));
+        assert!(matches(&filter, &target2));
     }
 
     #[test]
@@ -158,8 +488,8 @@ mod tests {
         let target1 = create_target("t1", vec![("region", "us-west")]);
         let target2 = create_target("t2", vec![]);
 
-        assert!(filter.matches_filters(&target1));
-        assert!(!filter.matches_filters(&target2));
+        assert!(matches(&filter, &target1));
+        assert!(!matches(&filter, &target2));
     }
 
     #[test]
@@ -170,22 +500,22 @@ mod tests {
         let target1 = create_target("t1", vec![("region", "us-west")]);
         let target2 = create_target("t2", vec![]);
 
-        assert!(!filter.matches_filters(&target1));
-        assert!(filter.matches_filters(&target2));
+        assert!(!matches(&filter, &target1));
+        assert!(matches(&filter, &target2));
     }
 
     #[test]
     fn test_in_filter() {
         let filter = MetaFilterAdapter::default().add_rule(
             "region".to_string(),
-            FilterOperation::In(vec!["us-west".to_string(), "us-east".to_string()]),
+            FilterOperation::In(vec!["us-west".into(), "us-east".into()]),
         );
 
         let target1 = create_target("t1", vec![("region", "us-west")]);
         let target2 = create_target("t2", vec![("region", "eu-west")]);
 
-        assert!(filter.matches_filters(&target1));
-        assert!(!filter.matches_filters(&target2));
+        assert!(matches(&filter, &target1));
+        assert!(!matches(&filter, &target2));
     }
 
     #[test]
@@ -193,11 +523,11 @@ mod tests {
         let filter = MetaFilterAdapter::default()
             .add_rule(
                 "region".to_string(),
-                FilterOperation::Equals("us-west".to_string()),
+                FilterOperation::Equals("us-west".into()),
             )
             .add_rule(
                 "environment".to_string(),
-                FilterOperation::Equals("production".to_string()),
+                FilterOperation::Equals("production".into()),
             );
 
         let target1 = create_target(
@@ -213,9 +543,9 @@ mod tests {
             vec![("region", "us-east"), ("environment", "production")],
         );
 
-        assert!(filter.matches_filters(&target1));
-        assert!(!filter.matches_filters(&target2));
-        assert!(!filter.matches_filters(&target3));
+        assert!(matches(&filter, &target1));
+        assert!(!matches(&filter, &target2));
+        assert!(!matches(&filter, &target3));
     }
 
     #[test]
@@ -224,6 +554,125 @@ mod tests {
 
         let target = create_target("t1", vec![("region", "us-west")]);
 
-        assert!(filter.matches_filters(&target));
+        assert!(matches(&filter, &target));
+    }
+
+    #[test]
+    fn test_numeric_filter() {
+        let filter = MetaFilterAdapter::default().add_rule(
+            "players".to_string(),
+            FilterOperation::LessThan(FieldRef::TargetMeta("max_players".to_string()).into()),
+        );
+
+        let target1 = create_target("t1", vec![("players", "9"), ("max_players", "10")]);
+        let target2 = create_target("t2", vec![("players", "10"), ("max_players", "10")]);
+        let target3 = create_target("t3", vec![("players", "many"), ("max_players", "10")]);
+
+        assert!(matches(&filter, &target1));
+        assert!(!matches(&filter, &target2));
+        assert!(!matches(&filter, &target3));
+    }
+
+    #[test]
+    fn test_version_filter() {
+        let filter = MetaFilterAdapter::default().add_rule(
+            "version".to_string(),
+            FilterOperation::Version(VersionReq::parse(">=1.21").unwrap()),
+        );
+
+        let target1 = create_target("t1", vec![("version", "1.21")]);
+        let target2 = create_target("t2", vec![("version", "1.21.4")]);
+        let target3 = create_target("t3", vec![("version", "1.20.6")]);
+
+        assert!(matches(&filter, &target1));
+        assert!(matches(&filter, &target2));
+        assert!(!matches(&filter, &target3));
+    }
+
+    #[test]
+    fn test_regex_filter() {
+        let filter = MetaFilterAdapter::default().add_rule(
+            "region".to_string(),
+            FilterOperation::Matches(Regex::new("^eu-").unwrap()),
+        );
+
+        let target1 = create_target("t1", vec![("region", "eu-west")]);
+        let target2 = create_target("t2", vec![("region", "us-east")]);
+
+        assert!(matches(&filter, &target1));
+        assert!(!matches(&filter, &target2));
+    }
+
+    #[test]
+    fn test_field_reference() {
+        let filter = MetaFilterAdapter::default().add_rule(
+            "region".to_string(),
+            FilterOperation::Equals("player.claims.region".parse::<FieldRef>().unwrap().into()),
+        );
+
+        let target1 = create_target("t1", vec![("region", "eu-west")]);
+        let target2 = create_target("t2", vec![("region", "us-east")]);
+
+        assert!(matches(&filter, &target1));
+        assert!(!matches(&filter, &target2));
+        assert!("player.unknown".parse::<FieldRef>().is_err());
+    }
+
+    #[test]
+    fn test_client_location_reference() {
+        let geoip = GeoIpDatabase::from_bytes(create_database(
+            &location("DE", "EU"),
+            &location("US", "NA"),
+        ))
+        .unwrap();
+        let filter = MetaFilterAdapter::default()
+            .add_rule(
+                "country".to_string(),
+                FilterOperation::Equals("client.country".parse::<FieldRef>().unwrap().into()),
+            )
+            .with_geoip(Some(Arc::new(geoip)));
+
+        let target1 = create_target("t1", vec![("country", "DE")]);
+        let target2 = create_target("t2", vec![("country", "US")]);
+
+        let client = Client {
+            address: "10.0.0.1:0".parse().unwrap(),
+            ..Default::default()
+        };
+        let player = create_player();
+        assert!(filter.matches_filters(&client, &player, &target1));
+        assert!(!filter.matches_filters(&client, &player, &target2));
+
+        // unknown locations never match
+        let client = Client {
+            address: "[2001:db8::1]:0".parse().unwrap(),
+            ..Default::default()
+        };
+        assert!(!filter.matches_filters(&client, &player, &target1));
+    }
+
+    #[test]
+    fn test_nested_groups() {
+        let rule = |key: &str, value: &str| {
+            Filter::Rule(FilterRule {
+                key: key.to_string(),
+                operation: FilterOperation::Equals(value.into()),
+            })
+        };
+        let filter = MetaFilterAdapter::default().add_filter(Filter::Any(vec![
+            rule("type", "lobby"),
+            Filter::All(vec![
+                rule("type", "game"),
+                Filter::Not(Box::new(rule("status", "full"))),
+            ]),
+        ]));
+
+        let target1 = create_target("t1", vec![("type", "lobby")]);
+        let target2 = create_target("t2", vec![("type", "game"), ("status", "open")]);
+        let target3 = create_target("t3", vec![("type", "game"), ("status", "full")]);
+
+        assert!(matches(&filter, &target1));
+        assert!(matches(&filter, &target2));
+        assert!(!matches(&filter, &target3));
     }
 }
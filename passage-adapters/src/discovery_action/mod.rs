@@ -1,17 +1,27 @@
 use crate::{Client, DiscoveryAdapter, Player, Target, error::Result};
+use std::cmp::Ordering::Equal;
 use std::fmt::Debug;
+use std::sync::Arc;
 
+pub mod consistent_hash_strategy;
+pub mod health_check;
+pub mod least_players_strategy;
 pub mod meta_filter;
 pub mod player_allow_filter;
 pub mod player_block_filter;
 pub mod player_fill_strategy;
+pub mod power_of_two_strategy;
+pub mod region_strategy;
+pub mod round_robin_strategy;
+pub mod weighted_random_strategy;
 
 /// The [`DiscoveryActionAdapter`] is used to mutate/provide (Minecraft server) targets for players
 /// to connect to. The discovery and discovery actions are applied as a chain. Each modifying the result
 /// of the previous link. Beware; discovery actions may alter the list of targets in any way, even
 /// replacing it completely.
 ///
-/// After passing the discovery and discovery actions, the first target in the list is selected. The
+/// After passing the discovery and discovery actions, a target with the lowest priority is selected
+/// (the first one, unless the route uses another tie-breaker). The
 /// target priority is used to pass partial ordering information between the adapters. Adapters should
 /// order the targets based on their priority, with lower values indicating higher priority. The priority
 /// value itself may be updated by any adapter in any way (ephemeral).
@@ -60,3 +70,113 @@ where
         Ok(())
     }
 }
+
+/// An `Arc` of an adapter applies the shared adapter, so a pipeline can be used by multiple owners.
+impl<T> DiscoveryActionAdapter for Arc<T>
+where
+    T: DiscoveryActionAdapter,
+{
+    fn apply(
+        &self,
+        client: &Client,
+        player: &Player,
+        targets: &mut Vec<Target>,
+    ) -> impl Future<Output = Result<()>> + Send {
+        (**self).apply(client, player, targets)
+    }
+}
+
+/// Orders the targets by their priority and then by the key of each target (ascending). After that,
+/// the priorities are fully recomputed starting from zero. Targets with the same priority and key
+/// share their new priority. If there are too many targets, then the rest gets the max priority.
+pub(crate) fn reprioritize<K, F>(targets: &mut Vec<Target>, mut key: F)
+where
+    K: PartialOrd,
+    F: FnMut(&Target) -> K,
+{
+    // compute the keys once, as they might be random or expensive
+    let mut keyed: Vec<(K, Target)> = targets
+        .drain(..)
+        .map(|target| (key(&target), target))
+        .collect();
+    let cmp = |a: &(K, Target), b: &(K, Target)| {
+        a.1.priority
+            .cmp(&b.1.priority)
+            .then_with(|| a.0.partial_cmp(&b.0).unwrap_or(Equal))
+    };
+    keyed.sort_by(cmp);
+
+    // determine the ties before the priorities are overwritten
+    let ties: Vec<bool> = keyed
+        .windows(2)
+        .map(|pair| cmp(&pair[0], &pair[1]) == Equal)
+        .collect();
+    let mut priority = 0u16;
+    for (i, (_, target)) in keyed.iter_mut().enumerate() {
+        if i > 0 && !ties[i - 1] {
+            priority = priority.saturating_add(1);
+        }
+        target.priority = priority;
+    }
+
+    targets.extend(keyed.into_iter().map(|(_, target)| target));
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Creates a target with the given priority and metadata.
+    pub(crate) fn create_target(id: &str, priority: u16, meta: &[(&str, &str)]) -> Target {
+        Target {
+            identifier: id.to_string(),
+            address: "127.0.0.1:8080".parse().unwrap(),
+            priority,
+            meta: meta
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            claims: Default::default(),
+        }
+    }
+
+    /// Creates a player without claims, cookies or session.
+    pub(crate) fn create_player() -> Player {
+        Player {
+            name: "Hydrofin".to_string(),
+            id: Default::default(),
+            claims: Default::default(),
+            cookies: Default::default(),
+            session: None,
+        }
+    }
+
+    /// Returns the identifiers and priorities of the targets in their order.
+    pub(crate) fn order(targets: &[Target]) -> Vec<(&str, u16)> {
+        targets
+            .iter()
+            .map(|target| (target.identifier.as_str(), target.priority))
+            .collect()
+    }
+
+    #[test]
+    fn test_reprioritize() {
+        let mut targets = vec![
+            create_target("c", 7, &[]),
+            create_target("b", 3, &[]),
+            create_target("a", 3, &[]),
+            create_target("d", 3, &[]),
+        ];
+        reprioritize(
+            &mut targets,
+            |target| {
+                if target.identifier == "d" { 1 } else { 0 }
+            },
+        );
+
+        assert_eq!(
+            order(&targets),
+            vec![("b", 0), ("a", 0), ("d", 1), ("c", 2)]
+        );
+    }
+}
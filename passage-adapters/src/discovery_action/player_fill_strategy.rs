@@ -1,5 +1,5 @@
+use crate::discovery_action::reprioritize;
 use crate::{Client, DiscoveryActionAdapter, Player, Target, error::Result, metrics};
-use std::cmp::Ordering::Equal;
 use tokio::time::Instant;
 
 /// The name of the adapter. It is primarily used for logging and metrics.
@@ -33,25 +33,14 @@ impl PlayerFillStrategyAdapter {
             .and_then(|players| players.parse::<u32>().ok())
     }
 
-    fn cmp(&self, a: &Target, b: &Target) -> std::cmp::Ordering {
-        // Compare by priority.
-        let order = a.priority.cmp(&b.priority);
-        if order != Equal {
-            return order;
-        }
-
-        // Compare by player count, get the target with the most players first.
-        let players_a = self.get_players(a).unwrap_or(0);
-        let players_b = self.get_players(b).unwrap_or(0);
-        let full_a = players_a >= self.max_players;
-        let full_b = players_b >= self.max_players;
-        match (full_a, full_b) {
-            // If both serves are full, then they get the same priority.
-            (true, true) => Equal,
-            // If both aren't full, get the one with the most players.
-            (false, false) => players_a.cmp(&players_b).reverse(),
-            // If one server is full, get the one with the fewest players (over the max).
-            _ => players_a.cmp(&players_b),
+    /// Returns the ordering key of a target. Servers below `max_players` come first, the most
+    /// filled one first. All full servers share the same key.
+    fn key(&self, target: &Target) -> (bool, i64) {
+        let players = self.get_players(target).unwrap_or(0);
+        if players >= self.max_players {
+            (true, 0)
+        } else {
+            (false, -i64::from(players))
         }
     }
 }
@@ -66,22 +55,9 @@ impl DiscoveryActionAdapter for PlayerFillStrategyAdapter {
     ) -> Result<()> {
         let start = Instant::now();
 
-        // Ensure that there are at least two targets. Otherwise, the following loop will panic.
-        if targets.len() <= 1 {
-            return Ok(());
-        }
-
-        // First, order the targets by priority and then by this comparator. After that, update the
-        // priorities. The priorities are fully recomputed. If there are too many targets, then the
-        // rest gets the max priority.
-        targets.sort_by(|a, b| self.cmp(a, b));
-        let mut priority = 0u16;
-        for i in 0..targets.len() {
-            targets[i].priority = priority;
-            if i < targets.len() - 1 && self.cmp(&targets[i], &targets[i + 1]) != Equal {
-                priority = priority.saturating_add(1);
-            }
-        }
+        // First, order the targets by priority and then by their fill level. After that, update the
+        // priorities. The priorities are fully recomputed.
+        reprioritize(targets, |target| self.key(target));
 
         metrics::adapter_duration::record(ADAPTER_TYPE, start);
         Ok(())
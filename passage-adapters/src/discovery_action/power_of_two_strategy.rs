@@ -0,0 +1,142 @@
+use crate::discovery_action::reprioritize;
+use crate::{Client, DiscoveryActionAdapter, Player, Target, error::Result, metrics};
+use rand::{Rng, RngExt};
+use tokio::time::Instant;
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "power_of_two_strategy_adapter";
+
+/// Discovery action adapter that picks the less loaded of two random targets.
+///
+/// Among the targets with the best priority, two are chosen at random and the one with fewer
+/// players from the metadata field named `field` is moved to the front. Targets without a
+/// (parseable) player count are treated as full. This spreads players nearly as well as
+/// the least-players strategy, but avoids sending bursts of players to the same target while its
+/// player count is outdated. After sorting, priorities are recomputed starting from zero.
+#[derive(Debug, Default)]
+pub struct PowerOfTwoStrategyAdapter {
+    field: String,
+}
+
+impl PowerOfTwoStrategyAdapter {
+    /// Creates a new `PowerOfTwoStrategyAdapter`.
+    ///
+    /// `field` is the metadata key on each [`Target`](crate::Target) that holds the current player
+    /// count as a parseable `u32`.
+    pub fn new(field: String) -> Self {
+        Self { field }
+    }
+
+    fn get_players(&self, target: &Target) -> u32 {
+        target
+            .meta
+            .get(&self.field)
+            .and_then(|players| players.parse::<u32>().ok())
+            .unwrap_or(u32::MAX)
+    }
+
+    /// Moves the less loaded of two targets, chosen with the random numbers of `rng`, to the front.
+    fn select(&self, targets: &mut Vec<Target>, rng: &mut impl Rng) {
+        // Find the targets with the best priority, the choice is only made between them.
+        let Some(best) = targets.iter().map(|target| target.priority).min() else {
+            return;
+        };
+        let candidates: Vec<usize> = (0..targets.len())
+            .filter(|i| targets[*i].priority == best)
+            .collect();
+
+        // Pick two distinct random candidates and prefer the one with fewer players.
+        let winner = if candidates.len() < 2 {
+            candidates[0]
+        } else {
+            let first = rng.random_range(0..candidates.len());
+            let second = (first + rng.random_range(1..candidates.len())) % candidates.len();
+            let (first, second) = (candidates[first], candidates[second]);
+            if self.get_players(&targets[second]) < self.get_players(&targets[first]) {
+                second
+            } else {
+                first
+            }
+        };
+
+        // Move the winner to the front of its priority, keeping the order of the other targets.
+        let mut index = 0;
+        reprioritize(targets, |_| {
+            index += 1;
+            index - 1 != winner
+        });
+    }
+}
+
+impl DiscoveryActionAdapter for PowerOfTwoStrategyAdapter {
+    #[tracing::instrument(skip_all)]
+    async fn apply(
+        &self,
+        _client: &Client,
+        _player: &Player,
+        targets: &mut Vec<Target>,
+    ) -> Result<()> {
+        let start = Instant::now();
+        self.select(targets, &mut rand::rng());
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery_action::tests::{create_player, create_target, order};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn create_adapter() -> PowerOfTwoStrategyAdapter {
+        PowerOfTwoStrategyAdapter::new("players".to_string())
+    }
+
+    #[tokio::test]
+    async fn less_loaded_of_two() {
+        let adapter = create_adapter();
+        let mut targets = vec![
+            create_target("t1", 0, &[("players", "20")]),
+            create_target("t2", 0, &[("players", "5")]),
+            create_target("backup", 1, &[("players", "0")]),
+        ];
+        adapter
+            .apply(&Client::default(), &create_player(), &mut targets)
+            .await
+            .unwrap();
+
+        assert_eq!(order(&targets), vec![("t2", 0), ("t1", 1), ("backup", 2)]);
+    }
+
+    #[test]
+    fn most_loaded_never_selected() {
+        let adapter = create_adapter();
+        for seed in 0..100 {
+            let mut targets = vec![
+                create_target("t1", 0, &[("players", "5")]),
+                create_target("t2", 0, &[]),
+                create_target("t3", 0, &[("players", "10")]),
+            ];
+            adapter.select(&mut targets, &mut StdRng::seed_from_u64(seed));
+
+            // targets without player count are treated as full
+            assert_ne!(targets[0].identifier, "t2");
+            assert_eq!(targets[0].priority, 0);
+            assert_eq!(targets[1].priority, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn single_target_kept() {
+        let adapter = create_adapter();
+        let mut targets = vec![create_target("t1", 3, &[])];
+        adapter
+            .apply(&Client::default(), &create_player(), &mut targets)
+            .await
+            .unwrap();
+
+        assert_eq!(order(&targets), vec![("t1", 0)]);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::discovery_action::reprioritize;
+use crate::geoip::{GeoIpDatabase, GeoLocation};
+use crate::{Client, DiscoveryActionAdapter, Player, Target, error::Result, metrics};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::Instant;
+use tracing::{Span, field, trace};
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "region_strategy_adapter";
+
+/// Discovery action adapter that re-orders targets to prefer the regions nearest to the client.
+///
+/// The client address is resolved to its country and continent with a GeoIP database. The region of
+/// the client is mapped from the country code (or else the continent code), falling back to the
+/// default region. Targets are sorted first by priority, then by the distance between the region of
+/// the client and the region in the metadata field named `field`. Equal regions have a distance of
+/// zero, other distances are looked up in the distance table (in both directions). Targets without a
+/// region or a known distance are sorted last. After sorting, priorities are recomputed starting from
+/// zero. If the region of the client is unknown, the targets are kept as they are.
+///
+/// The resolved country and continent are recorded in the span. Later actions can reference them
+/// through the `client.country` and `client.continent` fields of the meta filter.
+#[derive(Debug)]
+pub struct RegionStrategyAdapter {
+    geoip: Arc<GeoIpDatabase>,
+    field: String,
+    countries: HashMap<String, String>,
+    continents: HashMap<String, String>,
+    distances: HashMap<String, HashMap<String, u32>>,
+    default_region: Option<String>,
+}
+
+impl RegionStrategyAdapter {
+    /// Creates a new `RegionStrategyAdapter`.
+    ///
+    /// `countries` and `continents` map the codes of the client location to regions, `distances`
+    /// holds the distances between regions.
+    pub fn new(
+        geoip: Arc<GeoIpDatabase>,
+        field: String,
+        countries: HashMap<String, String>,
+        continents: HashMap<String, String>,
+        distances: HashMap<String, HashMap<String, u32>>,
+    ) -> Self {
+        let uppercase = |codes: HashMap<String, String>| {
+            codes
+                .into_iter()
+                .map(|(code, region)| (code.to_uppercase(), region))
+                .collect()
+        };
+        Self {
+            geoip,
+            field,
+            countries: uppercase(countries),
+            continents: uppercase(continents),
+            distances,
+            default_region: None,
+        }
+    }
+
+    /// Sets the region that is used if the region of the client is unknown.
+    pub fn with_default_region(mut self, default_region: Option<String>) -> Self {
+        self.default_region = default_region;
+        self
+    }
+
+    /// Returns the region of the client location.
+    fn region(&self, location: &GeoLocation) -> Option<&str> {
+        let country = location
+            .country
+            .as_ref()
+            .and_then(|country| self.countries.get(country));
+        let continent = location
+            .continent
+            .as_ref()
+            .and_then(|continent| self.continents.get(continent));
+        country
+            .or(continent)
+            .or(self.default_region.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Returns the distance between the region and the region of the target.
+    fn distance(&self, region: &str, target: &Target) -> Option<u32> {
+        let target_region = target.meta.get(&self.field)?;
+        if region == target_region {
+            return Some(0);
+        }
+        let lookup = |from: &str, to: &str| self.distances.get(from)?.get(to).copied();
+        lookup(region, target_region).or_else(|| lookup(target_region, region))
+    }
+}
+
+impl DiscoveryActionAdapter for RegionStrategyAdapter {
+    #[tracing::instrument(skip_all, fields(country = field::Empty, continent = field::Empty, region = field::Empty))]
+    async fn apply(
+        &self,
+        client: &Client,
+        _player: &Player,
+        targets: &mut Vec<Target>,
+    ) -> Result<()> {
+        let start = Instant::now();
+        let location = self.geoip.lookup(client.address.ip()).unwrap_or_default();
+        let span = Span::current();
+        if let Some(country) = &location.country {
+            span.record("country", country.as_str());
+        }
+        if let Some(continent) = &location.continent {
+            span.record("continent", continent.as_str());
+        }
+
+        match self.region(&location) {
+            Some(region) => {
+                span.record("region", region);
+                reprioritize(targets, |target| {
+                    self.distance(region, target).map_or(u64::MAX, u64::from)
+                });
+            }
+            None => trace!("unknown client region, keeping targets"),
+        }
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery_action::tests::{create_player, create_target, order};
+    use crate::geoip::tests::{create_database, location};
+    use std::net::SocketAddr;
+
+    fn create_adapter() -> RegionStrategyAdapter {
+        let geoip = GeoIpDatabase::from_bytes(create_database(
+            &location("DE", "EU"),
+            &location("US", "NA"),
+        ))
+        .unwrap();
+        RegionStrategyAdapter::new(
+            Arc::new(geoip),
+            "region".to_string(),
+            HashMap::from([("de".to_string(), "eu-central".to_string())]),
+            HashMap::from([
+                ("EU".to_string(), "eu-west".to_string()),
+                ("NA".to_string(), "us-east".to_string()),
+            ]),
+            HashMap::from([(
+                "eu-central".to_string(),
+                HashMap::from([("eu-west".to_string(), 1), ("us-east".to_string(), 5)]),
+            )]),
+        )
+    }
+
+    fn client(address: &str) -> Client {
+        Client {
+            address: SocketAddr::new(address.parse().unwrap(), 0),
+            ..Default::default()
+        }
+    }
+
+    fn create_targets() -> Vec<Target> {
+        vec![
+            create_target("t1", 0, &[("region", "us-east")]),
+            create_target("t2", 0, &[("region", "eu-central")]),
+            create_target("t3", 0, &[]),
+            create_target("t4", 0, &[("region", "eu-west")]),
+        ]
+    }
+
+    #[tokio::test]
+    async fn nearest_region_by_country() {
+        let adapter = create_adapter();
+        let mut targets = create_targets();
+        adapter
+            .apply(&client("10.0.0.1"), &create_player(), &mut targets)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            order(&targets),
+            vec![("t2", 0), ("t4", 1), ("t1", 2), ("t3", 3)]
+        );
+        // the client location is not added to the target metadata
+        assert_eq!(targets[0].meta.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn nearest_region_by_continent() {
+        let adapter = create_adapter();
+        let mut targets = create_targets();
+        adapter
+            .apply(&client("203.0.113.1"), &create_player(), &mut targets)
+            .await
+            .unwrap();
+
+        // the distance to eu-central is looked up in reverse, eu-west has no distance
+        assert_eq!(
+            order(&targets),
+            vec![("t1", 0), ("t2", 1), ("t3", 2), ("t4", 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn default_region() {
+        let adapter = create_adapter().with_default_region(Some("eu-west".to_string()));
+        let mut targets = create_targets();
+        adapter
+            .apply(&client("2001:db8::1"), &create_player(), &mut targets)
+            .await
+            .unwrap();
+
+        assert_eq!(targets[0].identifier, "t4");
+    }
+
+    #[tokio::test]
+    async fn unknown_region() {
+        let adapter = create_adapter();
+        let mut targets = create_targets();
+        adapter
+            .apply(&client("2001:db8::1"), &create_player(), &mut targets)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            order(&targets),
+            vec![("t1", 0), ("t2", 0), ("t3", 0), ("t4", 0)]
+        );
+    }
+}
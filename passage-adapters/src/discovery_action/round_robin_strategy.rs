@@ -0,0 +1,98 @@
+use crate::discovery_action::reprioritize;
+use crate::{Client, DiscoveryActionAdapter, Player, Target, error::Result, metrics};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::time::Instant;
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "round_robin_strategy_adapter";
+
+/// Discovery action adapter that rotates through the targets on each request.
+///
+/// Targets are sorted first by priority, then by their identifier. Within each priority, the
+/// targets are rotated by a counter that is incremented on each request, so consecutive players
+/// are sent to consecutive targets. The counter is kept per adapter (and therefore per route).
+/// After sorting, priorities are recomputed starting from zero.
+#[derive(Debug, Default)]
+pub struct RoundRobinStrategyAdapter {
+    counter: AtomicUsize,
+}
+
+impl RoundRobinStrategyAdapter {
+    /// Creates a new `RoundRobinStrategyAdapter`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl DiscoveryActionAdapter for RoundRobinStrategyAdapter {
+    #[tracing::instrument(skip_all)]
+    async fn apply(
+        &self,
+        _client: &Client,
+        _player: &Player,
+        targets: &mut Vec<Target>,
+    ) -> Result<()> {
+        let start = Instant::now();
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+
+        // First, order the targets by priority and identifier to get a stable order. After that,
+        // rotate each priority group by the counter and recompute the priorities.
+        targets.sort_by(|a, b| {
+            a.priority
+                .cmp(&b.priority)
+                .then_with(|| a.identifier.cmp(&b.identifier))
+        });
+        let mut rank = vec![0usize; targets.len()];
+        let mut group_start = 0;
+        while group_start < targets.len() {
+            let priority = targets[group_start].priority;
+            let group_len = targets[group_start..]
+                .iter()
+                .take_while(|target| target.priority == priority)
+                .count();
+            let offset = counter % group_len;
+            for (i, rank) in rank[group_start..group_start + group_len]
+                .iter_mut()
+                .enumerate()
+            {
+                *rank = (i + group_len - offset) % group_len;
+            }
+            group_start += group_len;
+        }
+        let mut ranks = rank.into_iter();
+        reprioritize(targets, |_| ranks.next().unwrap_or_default());
+
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery_action::tests::{create_player, create_target};
+
+    #[tokio::test]
+    async fn test_rotates_targets() {
+        let adapter = RoundRobinStrategyAdapter::new();
+        let player = create_player();
+
+        let mut first = Vec::new();
+        for _ in 0..4 {
+            let mut targets = vec![
+                create_target("t3", 0, &[]),
+                create_target("t1", 0, &[]),
+                create_target("t2", 0, &[]),
+                create_target("backup", 1, &[]),
+            ];
+            adapter
+                .apply(&Client::default(), &player, &mut targets)
+                .await
+                .unwrap();
+            assert_eq!(targets[3].identifier, "backup");
+            assert_eq!(targets[3].priority, 3);
+            first.push(targets[0].identifier.clone());
+        }
+        assert_eq!(first, vec!["t1", "t2", "t3", "t1"]);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::discovery_action::reprioritize;
+use crate::{Client, DiscoveryActionAdapter, Player, Target, error::Result, metrics};
+use rand::{Rng, RngExt};
+use tokio::time::Instant;
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "weighted_random_strategy_adapter";
+
+/// Discovery action adapter that shuffles targets randomly, proportional to their weight.
+///
+/// Targets are sorted first by priority, then in a random order in which targets with a higher
+/// weight from the metadata field named `field` are more likely to come first. Targets without a
+/// (parseable) weight use `default_weight`, targets with a weight of zero are sorted last. After
+/// sorting, priorities are recomputed starting from zero.
+#[derive(Debug, Default)]
+pub struct WeightedRandomStrategyAdapter {
+    field: String,
+    default_weight: f64,
+}
+
+impl WeightedRandomStrategyAdapter {
+    /// Creates a new `WeightedRandomStrategyAdapter`.
+    ///
+    /// `field` is the metadata key on each [`Target`](crate::Target) that holds the weight as a
+    /// parseable `f64`. `default_weight` is used for targets without a weight.
+    pub fn new(field: String, default_weight: f64) -> Self {
+        Self {
+            field,
+            default_weight,
+        }
+    }
+
+    fn get_weight(&self, target: &Target) -> f64 {
+        target
+            .meta
+            .get(&self.field)
+            .and_then(|weight| weight.parse::<f64>().ok())
+            .filter(|weight| weight.is_finite() && *weight >= 0.0)
+            .unwrap_or(self.default_weight)
+    }
+
+    /// Shuffles the targets with the random numbers of `rng`.
+    fn shuffle(&self, targets: &mut Vec<Target>, rng: &mut impl Rng) {
+        // Weighted random sampling without replacement (Efraimidis-Spirakis): every target gets
+        // the key `u^(1/weight)` and the targets with the largest keys come first.
+        reprioritize(targets, |target| {
+            let weight = self.get_weight(target);
+            if weight <= 0.0 {
+                return 0.0;
+            }
+            -rng.random::<f64>().powf(1.0 / weight)
+        });
+    }
+}
+
+impl DiscoveryActionAdapter for WeightedRandomStrategyAdapter {
+    #[tracing::instrument(skip_all)]
+    async fn apply(
+        &self,
+        _client: &Client,
+        _player: &Player,
+        targets: &mut Vec<Target>,
+    ) -> Result<()> {
+        let start = Instant::now();
+        self.shuffle(targets, &mut rand::rng());
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery_action::tests::{create_player, create_target, order};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn create_adapter() -> WeightedRandomStrategyAdapter {
+        WeightedRandomStrategyAdapter::new("weight".to_string(), 1.0)
+    }
+
+    #[test]
+    fn zero_weight_last() {
+        let adapter = create_adapter();
+        for seed in 0..100 {
+            let mut targets = vec![
+                create_target("t1", 0, &[("weight", "0")]),
+                create_target("t2", 0, &[("weight", "5")]),
+                create_target("t3", 0, &[]),
+                create_target("backup", 1, &[("weight", "5")]),
+            ];
+            adapter.shuffle(&mut targets, &mut StdRng::seed_from_u64(seed));
+
+            assert_eq!(targets[2].identifier, "t1");
+            assert_eq!(targets[3].identifier, "backup");
+            assert_eq!(targets[3].priority, 3);
+        }
+    }
+
+    #[test]
+    fn weight_preferred() {
+        let adapter = create_adapter();
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut heavy = 0;
+        for _ in 0..1_000 {
+            let mut targets = vec![
+                create_target("light", 0, &[("weight", "1")]),
+                create_target("heavy", 0, &[("weight", "9")]),
+            ];
+            adapter.shuffle(&mut targets, &mut rng);
+            if targets[0].identifier == "heavy" {
+                heavy += 1;
+            }
+        }
+
+        // the heavy target is expected to come first in 90% of the cases
+        assert!((850..950).contains(&heavy), "heavy first {heavy} times");
+    }
+
+    #[tokio::test]
+    async fn single_target_kept() {
+        let adapter = create_adapter();
+        let mut targets = vec![create_target("t1", 4, &[("weight", "0")])];
+        adapter
+            .apply(&Client::default(), &create_player(), &mut targets)
+            .await
+            .unwrap();
+
+        assert_eq!(order(&targets), vec![("t1", 0)]);
+    }
+}
@@ -46,6 +46,9 @@ pub enum Error {
 
         /// The reason for the rejection. This is a localizable message key.
         reason: Option<String>,
+
+        /// The parameters that are substituted into the localized reason message.
+        params: Vec<(&'static str, String)>,
     },
 }
 
@@ -54,6 +57,7 @@ pub fn reject(adapter_type: &'static str) -> Error {
     Error::Rejected {
         adapter_type,
         reason: None,
+        params: Vec::new(),
     }
 }
 
@@ -62,6 +66,21 @@ pub fn reject_reason(adapter_type: &'static str, reason: impl Into<String>) -> E
     Error::Rejected {
         adapter_type,
         reason: Some(reason.into()),
+        params: Vec::new(),
+    }
+}
+
+/// Constructs a [`Error::Rejected`] with a localizable message key as the reason and parameters
+/// that are substituted into the localized message.
+pub fn reject_reason_with(
+    adapter_type: &'static str,
+    reason: impl Into<String>,
+    params: Vec<(&'static str, String)>,
+) -> Error {
+    Error::Rejected {
+        adapter_type,
+        reason: Some(reason.into()),
+        params,
     }
 }
 
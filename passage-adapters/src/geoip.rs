@@ -0,0 +1,359 @@
+//! This module contains the GeoIP lookup that resolves the location (or autonomous system) of
+//! client addresses. A single database can be shared (e.g. in an [`Arc`](std::sync::Arc)) among
+//! all adapters that need it.
+
+use maxminddb::{MaxMindDbError, Reader};
+use serde::Deserialize;
+use std::fmt::{Debug, Formatter};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// The location of an address, as far as it is known to the database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeoLocation {
+    /// The ISO 3166-1 alpha-2 code of the country (e.g. `DE`).
+    pub country: Option<String>,
+
+    /// The code of the continent (e.g. `EU`).
+    pub continent: Option<String>,
+}
+
+/// The country record of the database.
+#[derive(Deserialize)]
+struct CountryRecord {
+    country: Option<CountryCode>,
+    continent: Option<ContinentCode>,
+}
+
+#[derive(Deserialize)]
+struct CountryCode {
+    iso_code: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ContinentCode {
+    code: Option<String>,
+}
+
+/// The autonomous system record of the database.
+#[derive(Deserialize)]
+struct AsnRecord {
+    autonomous_system_number: Option<u32>,
+}
+
+/// [`GeoIpDatabase`] looks up the location of addresses in a local MaxMind database (e.g.
+/// GeoLite2-Country or GeoLite2-City) or their autonomous system (e.g. GeoLite2-ASN).
+///
+/// If a reload interval is set, a background task re-reads the database file whenever its
+/// modification time changes. The task is automatically stopped once the database is dropped. The
+/// database is only replaced if the new file could be read.
+pub struct GeoIpDatabase {
+    /// The current database. This thread-safe container is shared between the instance and its
+    /// reload task.
+    reader: Arc<RwLock<Reader<Vec<u8>>>>,
+
+    /// The path of the database file (if it was read from a file).
+    path: Option<PathBuf>,
+
+    /// The cancellation token used to stop the background reload task.
+    token: CancellationToken,
+}
+
+impl GeoIpDatabase {
+    /// Opens the database at the given path. The database is read into memory.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, MaxMindDbError> {
+        Ok(Self {
+            reader: Arc::new(RwLock::new(Reader::open_readfile(&path)?)),
+            path: Some(path.as_ref().to_path_buf()),
+            token: CancellationToken::new(),
+        })
+    }
+
+    /// Creates the database from its raw content.
+    pub fn from_bytes(buffer: Vec<u8>) -> Result<Self, MaxMindDbError> {
+        Ok(Self {
+            reader: Arc::new(RwLock::new(Reader::from_source(buffer)?)),
+            path: None,
+            token: CancellationToken::new(),
+        })
+    }
+
+    /// Starts the background task that checks the database file for modifications every
+    /// `interval` (disabled if zero or the database was not read from a file).
+    pub fn with_reload_interval(self, interval: Duration) -> Self {
+        let Some(path) = self.path.clone() else {
+            return self;
+        };
+        if interval.is_zero() {
+            return self;
+        }
+
+        let reader = self.reader.clone();
+        let token = self.token.clone();
+        let mut modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        tokio::spawn(async move {
+            info!("starting GeoIP database reload task");
+            loop {
+                select! {
+                    biased;
+                    _ = token.cancelled() => break,
+                    _ = interval.tick() => Self::reload(&path, &mut modified, &reader).await,
+                }
+            }
+            info!("stopped GeoIP database reload task");
+        });
+
+        self
+    }
+
+    /// Re-reads the database file if it was modified since it was last read. Failures are logged and
+    /// the previous database is kept.
+    async fn reload(
+        path: &Path,
+        modified: &mut Option<SystemTime>,
+        reader: &RwLock<Reader<Vec<u8>>>,
+    ) {
+        let next_modified = match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata.modified().ok(),
+            Err(err) => {
+                warn!(err = %err, path = ?path, "failed to read GeoIP database metadata");
+                return;
+            }
+        };
+        if *modified == next_modified && next_modified.is_some() {
+            return;
+        }
+
+        debug!(path = ?path, "reading GeoIP database");
+        let next = match tokio::fs::read(path).await {
+            Ok(buffer) => Reader::from_source(buffer),
+            Err(err) => {
+                warn!(err = %err, path = ?path, "failed to read GeoIP database");
+                return;
+            }
+        };
+        match next {
+            Ok(next) => {
+                *reader.write().expect("geoip lock poisoned") = next;
+                *modified = next_modified;
+                info!(path = ?path, "reloaded GeoIP database");
+            }
+            Err(err) => warn!(err = %err, path = ?path, "failed to parse GeoIP database"),
+        }
+    }
+
+    /// Looks up the location of the address. Returns nothing if the address is not in the database.
+    pub fn lookup(&self, address: IpAddr) -> Option<GeoLocation> {
+        let reader = self.reader.read().expect("geoip lock poisoned");
+        let record = reader
+            .lookup(address.to_canonical())
+            .ok()?
+            .decode::<CountryRecord>()
+            .ok()??;
+        Some(GeoLocation {
+            country: record.country.and_then(|country| country.iso_code),
+            continent: record.continent.and_then(|continent| continent.code),
+        })
+    }
+
+    /// Looks up the autonomous system number of the address. Returns nothing if the address is not
+    /// in the database.
+    pub fn lookup_asn(&self, address: IpAddr) -> Option<u32> {
+        let reader = self.reader.read().expect("geoip lock poisoned");
+        reader
+            .lookup(address.to_canonical())
+            .ok()?
+            .decode::<AsnRecord>()
+            .ok()??
+            .autonomous_system_number
+    }
+}
+
+impl Debug for GeoIpDatabase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let reader = self.reader.read().expect("geoip lock poisoned");
+        f.debug_struct("GeoIpDatabase")
+            .field("database_type", &reader.metadata().database_type)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl Drop for GeoIpDatabase {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Encodes the control byte(s) of a field with the given type and size.
+    fn control(kind: u8, size: usize, out: &mut Vec<u8>) {
+        if kind <= 7 {
+            out.push(kind << 5 | size as u8);
+        } else {
+            out.extend([size as u8, kind - 7]);
+        }
+    }
+
+    fn string(value: &str, out: &mut Vec<u8>) {
+        control(2, value.len(), out);
+        out.extend(value.as_bytes());
+    }
+
+    fn uint(kind: u8, value: u64, out: &mut Vec<u8>) {
+        let bytes = value.to_be_bytes();
+        let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(8);
+        control(kind, 8 - start, out);
+        out.extend(&bytes[start..]);
+    }
+
+    fn record(location: &GeoLocation, asn: u32, out: &mut Vec<u8>) {
+        control(7, 3, out);
+        string("country", out);
+        control(7, 1, out);
+        string("iso_code", out);
+        string(location.country.as_deref().unwrap_or_default(), out);
+        string("continent", out);
+        control(7, 1, out);
+        string("code", out);
+        string(location.continent.as_deref().unwrap_or_default(), out);
+        string("autonomous_system_number", out);
+        uint(6, u64::from(asn), out);
+    }
+
+    /// Creates an IPv4 database that resolves `0.0.0.0/1` to the first and `128.0.0.0/1` to the
+    /// second location (with the autonomous systems 64500 and 64501).
+    pub(crate) fn create_database(first: &GeoLocation, second: &GeoLocation) -> Vec<u8> {
+        let mut data = vec![];
+        record(first, 64500, &mut data);
+        let second_offset = data.len();
+        record(second, 64501, &mut data);
+
+        // a single node whose records point into the data section (after the 16 byte separator)
+        let mut buffer = vec![];
+        for offset in [0, second_offset] {
+            buffer.extend(&((1 + 16 + offset) as u32).to_be_bytes()[1..]);
+        }
+        buffer.extend([0; 16]);
+        buffer.extend(data);
+
+        buffer.extend(b"\xab\xcd\xefMaxMind.com");
+        control(7, 9, &mut buffer);
+        string("binary_format_major_version", &mut buffer);
+        uint(5, 2, &mut buffer);
+        string("binary_format_minor_version", &mut buffer);
+        uint(5, 0, &mut buffer);
+        string("build_epoch", &mut buffer);
+        uint(9, 0, &mut buffer);
+        string("database_type", &mut buffer);
+        string("Test-Country", &mut buffer);
+        string("description", &mut buffer);
+        control(7, 0, &mut buffer);
+        string("ip_version", &mut buffer);
+        uint(5, 4, &mut buffer);
+        string("languages", &mut buffer);
+        control(11, 0, &mut buffer);
+        string("node_count", &mut buffer);
+        uint(6, 1, &mut buffer);
+        string("record_size", &mut buffer);
+        uint(5, 24, &mut buffer);
+        buffer
+    }
+
+    pub(crate) fn location(country: &str, continent: &str) -> GeoLocation {
+        GeoLocation {
+            country: Some(country.to_string()),
+            continent: Some(continent.to_string()),
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        let database = GeoIpDatabase::from_bytes(create_database(
+            &location("DE", "EU"),
+            &location("US", "NA"),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            database.lookup("10.0.0.1".parse().unwrap()),
+            Some(location("DE", "EU"))
+        );
+        assert_eq!(
+            database.lookup("::ffff:203.0.113.1".parse().unwrap()),
+            Some(location("US", "NA"))
+        );
+        assert_eq!(database.lookup("2001:db8::1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn lookup_asn() {
+        let database = GeoIpDatabase::from_bytes(create_database(
+            &location("DE", "EU"),
+            &location("US", "NA"),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            database.lookup_asn("10.0.0.1".parse().unwrap()),
+            Some(64500)
+        );
+        assert_eq!(
+            database.lookup_asn("::ffff:203.0.113.1".parse().unwrap()),
+            Some(64501)
+        );
+        assert_eq!(database.lookup_asn("2001:db8::1".parse().unwrap()), None);
+    }
+
+    #[tokio::test]
+    async fn reload_modified_file() {
+        let path =
+            std::env::temp_dir().join(format!("passage-geoip-{}.mmdb", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            create_database(&location("DE", "EU"), &location("US", "NA")),
+        )
+        .unwrap();
+        let database = GeoIpDatabase::open(&path).unwrap();
+        let mut modified = std::fs::metadata(&path).unwrap().modified().ok();
+        let address = "10.0.0.1".parse().unwrap();
+        assert_eq!(database.lookup(address), Some(location("DE", "EU")));
+
+        // an unmodified file is not read again
+        std::fs::write(
+            &path,
+            create_database(&location("JP", "AS"), &location("US", "NA")),
+        )
+        .unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(modified.unwrap()).unwrap();
+        GeoIpDatabase::reload(&path, &mut modified, &database.reader).await;
+        assert_eq!(database.lookup(address), Some(location("DE", "EU")));
+
+        // ensure the modification time changes
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        GeoIpDatabase::reload(&path, &mut modified, &database.reader).await;
+        assert_eq!(database.lookup(address), Some(location("JP", "AS")));
+
+        // the last database is kept if the file is invalid
+        std::fs::write(&path, b"invalid").unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(20))
+            .unwrap();
+        GeoIpDatabase::reload(&path, &mut modified, &database.reader).await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(database.lookup(address), Some(location("JP", "AS")));
+    }
+}
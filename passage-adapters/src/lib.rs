@@ -11,8 +11,10 @@ pub mod backoff;
 pub mod discovery;
 pub mod discovery_action;
 pub mod error;
+pub mod geoip;
 pub mod localization;
 pub mod metrics;
+pub mod ping;
 pub mod status;
 
 // reexport errors types
@@ -26,19 +28,38 @@ pub use localization::LocalizationAdapter;
 pub use status::StatusAdapter;
 
 // reexport adapters
+pub use authentication::ban::BanAuthenticationAdapter;
+pub use authentication::chain::ChainAuthenticationAdapter;
 pub use authentication::disabled::DisabledAuthenticationAdapter;
 pub use authentication::fixed::FixedAuthenticationAdapter;
+pub use authentication::maintenance::MaintenanceAuthenticationAdapter;
+pub use authentication::offline::OfflineAuthenticationAdapter;
 pub use discovery::fixed::FixedDiscoveryAdapter;
+pub use discovery_action::consistent_hash_strategy::ConsistentHashStrategyAdapter;
+pub use discovery_action::health_check::HealthCheckAdapter;
+pub use discovery_action::least_players_strategy::LeastPlayersStrategyAdapter;
 pub use discovery_action::meta_filter::MetaFilterAdapter;
 pub use discovery_action::player_allow_filter::PlayerAllowFilterAdapter;
 pub use discovery_action::player_block_filter::PlayerBlockFilterAdapter;
 pub use discovery_action::player_fill_strategy::PlayerFillStrategyAdapter;
+pub use discovery_action::power_of_two_strategy::PowerOfTwoStrategyAdapter;
+pub use discovery_action::region_strategy::RegionStrategyAdapter;
+pub use discovery_action::round_robin_strategy::RoundRobinStrategyAdapter;
+pub use discovery_action::weighted_random_strategy::WeightedRandomStrategyAdapter;
 pub use localization::fixed::FixedLocalizationAdapter;
+pub use status::aggregate::AggregateStatusAdapter;
+pub use status::favicon::FaviconStatusAdapter;
 pub use status::fixed::FixedStatusAdapter;
+pub use status::maintenance::MaintenanceStatusAdapter;
+pub use status::motd::MotdStatusAdapter;
+pub use status::post_process::PostProcessStatusAdapter;
 
 /// The Minecraft protocol version type.
 pub type Protocol = i32;
 
+/// The key/value claims that are signed into the auth cookie (e.g. a party id or queue ticket).
+pub type Claims = HashMap<String, String>;
+
 /// Contains the client information sent to the server on the initial handshake.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Client {
@@ -74,6 +95,48 @@ pub struct Player {
 
     /// The id of the player.
     pub id: uuid::Uuid,
+
+    /// The verified claims of the player, restored from the auth cookie of a previous transfer.
+    #[serde(default)]
+    pub claims: Claims,
+
+    /// The decoded payloads of the additional cookies requested by the route, by cookie key.
+    #[serde(default)]
+    pub cookies: HashMap<String, serde_json::Value>,
+
+    /// The (unverified) session of the client, restored from the session cookie of a previous pass.
+    #[serde(default)]
+    pub session: Option<Session>,
+}
+
+/// Contains the (unverified) session information of a client across transfers.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Session {
+    /// The id of the session.
+    pub id: uuid::Uuid,
+
+    /// The time (unix seconds) at which the session was first seen.
+    #[serde(default)]
+    pub first_seen: u64,
+
+    /// The number of previous passes through Passage.
+    #[serde(default)]
+    pub hops: u32,
+
+    /// The identifiers of the previous targets, the most recent last.
+    #[serde(default)]
+    pub targets: Vec<String>,
+
+    /// The Passage instance that created the session.
+    #[serde(default)]
+    pub instance: Option<String>,
+}
+
+impl Session {
+    /// Returns the identifier of the target the client was last transferred to.
+    pub fn last_target(&self) -> Option<&str> {
+        self.targets.last().map(String::as_str)
+    }
 }
 
 /// A target gameserver that can be connected to.
@@ -93,6 +156,10 @@ pub struct Target {
     /// Any metadata attached to the target that may be used by the adapters.
     #[serde(default)]
     pub meta: HashMap<String, String>,
+
+    /// The claims that are signed into the auth cookie if the target is selected.
+    #[serde(default)]
+    pub claims: Claims,
 }
 
 /// The information on the protocol version of a server.
@@ -2,6 +2,7 @@ pub mod fixed;
 
 use crate::error::Result;
 use std::fmt::Debug;
+use std::sync::Arc;
 
 /// A [`LocalizationAdapter`] is used to localize messages based on a message key, locale, and template
 /// params. The `key` identifies a message template. The `locale` is the Mojang locale tag reported
@@ -17,3 +18,14 @@ pub trait LocalizationAdapter: Debug + Send + Sync {
         params: &[(&'static str, String)],
     ) -> impl Future<Output = Result<String>> + Send;
 }
+
+impl<T: LocalizationAdapter> LocalizationAdapter for Arc<T> {
+    fn localize(
+        &self,
+        locale: Option<&str>,
+        key: &str,
+        params: &[(&'static str, String)],
+    ) -> impl Future<Output = Result<String>> + Send {
+        (**self).localize(locale, key, params)
+    }
+}
@@ -49,3 +49,31 @@ pub mod adapter_duration {
         )
     }
 }
+
+/// The metric `authentication_chain_links` counts the outcome of each link of an authentication
+/// chain that was tried for a player.
+pub mod authentication_chain_links {
+    use crate::metrics::METER;
+    use opentelemetry::KeyValue;
+    use opentelemetry::metrics::Counter;
+    use std::sync::LazyLock;
+
+    static INSTRUMENT: LazyLock<Counter<u64>> = LazyLock::new(|| {
+        METER
+            .u64_counter("authentication_chain_links")
+            .with_description("The outcomes of the links of an authentication chain")
+            .build()
+    });
+
+    /// Increments the counter for the given link name and outcome. The `outcome` is one of
+    /// `authenticated`, `rejected`, `failed` or `timeout`.
+    pub fn inc(link: String, outcome: &'static str) {
+        INSTRUMENT.add(
+            1,
+            &[
+                KeyValue::new("link", link),
+                KeyValue::new("outcome", outcome),
+            ],
+        )
+    }
+}
@@ -0,0 +1,166 @@
+//! This module contains the server list ping that is used by adapters to query the status of targets.
+
+use crate::{Error, ServerPlayers, ServerStatus, ServerVersion, error::Result};
+use futures::{SinkExt, StreamExt};
+use passage_packets::State;
+use passage_packets::codec::PacketCodec;
+use passage_packets::handshake::serverbound::HandshakePacket;
+use passage_packets::status::clientbound::StatusResponsePacket;
+use passage_packets::status::serverbound::StatusRequestPacket;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+use tokio_util::codec::Framed;
+
+/// The maximum length of a status response (it may include a favicon).
+const MAX_STATUS_LENGTH: usize = 1024 * 1024;
+
+/// The result of a successful server list ping.
+#[derive(Debug, Clone)]
+pub struct Ping {
+    /// The status reported by the server.
+    pub status: ServerStatus,
+
+    /// The time between sending the status request and receiving the status response.
+    pub latency: Duration,
+}
+
+/// The status as it is reported by servers. Unlike the configured [`ServerStatus`], the description
+/// may be any JSON text component.
+#[derive(Debug, Deserialize)]
+struct RawStatus {
+    #[serde(default)]
+    version: Option<ServerVersion>,
+    #[serde(default)]
+    players: Option<ServerPlayers>,
+    #[serde(default)]
+    description: Option<Box<RawValue>>,
+    #[serde(default)]
+    favicon: Option<String>,
+    #[serde(default, rename = "enforcesSecureChat")]
+    enforces_secure_chat: Option<bool>,
+}
+
+impl From<RawStatus> for ServerStatus {
+    fn from(value: RawStatus) -> Self {
+        Self {
+            version: value.version.unwrap_or_default(),
+            players: value.players,
+            description: value.description,
+            favicon: value.favicon,
+            enforces_secure_chat: value.enforces_secure_chat,
+        }
+    }
+}
+
+/// Requests the status of the server at `address` like a client would (server list ping). The
+/// whole exchange has to complete within `timeout`. Errors are reported for `adapter_type`.
+pub async fn ping(
+    adapter_type: &'static str,
+    address: SocketAddr,
+    timeout: Duration,
+) -> Result<Ping> {
+    let (body, latency) = tokio::time::timeout(timeout, request_status(address))
+        .await
+        .map_err(|_| Error::FailedFetch {
+            adapter_type,
+            cause: std::io::Error::from(ErrorKind::TimedOut).into(),
+        })?
+        .map_err(|err| Error::FailedFetch {
+            adapter_type,
+            cause: err.into(),
+        })?;
+
+    let status: RawStatus = serde_json::from_str(&body).map_err(|err| Error::FailedParse {
+        adapter_type,
+        cause: err.into(),
+    })?;
+    Ok(Ping {
+        status: status.into(),
+        latency,
+    })
+}
+
+/// Sends the handshake and status request and returns the raw status response body.
+async fn request_status(address: SocketAddr) -> Result<(String, Duration), passage_packets::Error> {
+    let stream = TcpStream::connect(address).await?;
+    let mut stream = Framed::new(stream, PacketCodec::new(MAX_STATUS_LENGTH));
+    stream
+        .send(HandshakePacket {
+            protocol_version: -1,
+            server_address: address.ip().to_string(),
+            server_port: address.port(),
+            next_state: State::Status,
+        })
+        .await?;
+
+    let start = Instant::now();
+    stream.send(StatusRequestPacket).await?;
+    let frame = stream
+        .next()
+        .await
+        .ok_or_else(|| std::io::Error::from(ErrorKind::UnexpectedEof))??;
+    let latency = start.elapsed();
+    let packet = frame.try_into::<StatusResponsePacket>()?;
+    Ok((packet.body, latency))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passage_packets::codec::PacketFrame;
+    use tokio::net::TcpListener;
+
+    /// Accepts a single status request and answers with `body`.
+    async fn serve_status(listener: TcpListener, body: &'static str) {
+        let (stream, _) = listener.accept().await.expect("accept failed");
+        let mut stream = Framed::new(stream, PacketCodec::new(MAX_STATUS_LENGTH));
+        let handshake: PacketFrame = stream.next().await.expect("no handshake").expect("frame");
+        handshake.try_into::<HandshakePacket>().expect("handshake");
+        let request: PacketFrame = stream.next().await.expect("no request").expect("frame");
+        request.try_into::<StatusRequestPacket>().expect("request");
+        stream
+            .send(StatusResponsePacket {
+                body: body.to_string(),
+            })
+            .await
+            .expect("send failed");
+    }
+
+    #[tokio::test]
+    async fn ping_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind failed");
+        let address = listener.local_addr().expect("local address");
+        tokio::spawn(serve_status(
+            listener,
+            r#"{"version":{"name":"1.21.5","protocol":770},"players":{"online":3,"max":20},"description":{"text":"hello"}}"#,
+        ));
+
+        let ping = ping("test", address, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(ping.status.version.name, "1.21.5");
+        assert_eq!(ping.status.version.protocol, 770);
+        let players = ping.status.players.expect("players");
+        assert_eq!((players.online, players.max), (3, 20));
+        assert_eq!(
+            ping.status.description.expect("description").get(),
+            r#"{"text":"hello"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn ping_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind failed");
+        let address = listener.local_addr().expect("local address");
+        let _accept = tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.expect("accept failed");
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+
+        let result = ping("test", address, Duration::from_millis(100)).await;
+        assert!(matches!(result, Err(Error::FailedFetch { .. })));
+    }
+}
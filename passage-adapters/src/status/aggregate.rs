@@ -0,0 +1,360 @@
+use crate::ping::ping;
+use crate::status::StatusAdapter;
+use crate::{
+    Client, DiscoveryActionAdapter, Error, Player, ServerPlayer, ServerPlayers, ServerStatus,
+    Target, error::Result, metrics,
+};
+use futures::future::join_all;
+use serde_json::value::RawValue;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, trace};
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "aggregate_status_adapter";
+
+/// The default maximum number of merged sample players (the vanilla client shows up to 12).
+pub const DEFAULT_MAX_SAMPLES: usize = 12;
+
+/// [`AggregateSource`] defines where the player counts of the targets are taken from.
+#[derive(Debug, Clone)]
+pub enum AggregateSource {
+    /// The player counts are read from the target metadata (e.g., as annotated by a health check).
+    Meta {
+        /// The metadata key that holds the number of online players.
+        online_field: String,
+        /// The metadata key that holds the maximum number of players.
+        max_field: String,
+    },
+    /// The targets are pinged with a status request. Unreachable targets are skipped.
+    Ping {
+        /// The timeout of a single status request.
+        timeout: Duration,
+    },
+}
+
+/// [`AggregateMotd`] defines where the description (MOTD) of the aggregated status is taken from.
+#[derive(Debug, Clone, Default)]
+pub enum AggregateMotd {
+    /// The description of the wrapped status adapter is kept.
+    #[default]
+    Inner,
+    /// The description of the target with the highest priority is used (requires pinging targets).
+    Top,
+    /// The description is rendered from a JSON text component template.
+    Template(MotdTemplate),
+}
+
+/// [`MotdTemplate`] is a JSON text component template. The placeholders `{online}`, `{max}` and
+/// `{targets}` are replaced with the aggregated values.
+#[derive(Debug, Clone)]
+pub struct MotdTemplate(String);
+
+impl MotdTemplate {
+    /// Creates a new `MotdTemplate`. Fails if the template is not valid JSON once the placeholders
+    /// are filled in, as the placeholders are only ever replaced with numbers.
+    pub fn new(template: impl Into<String>) -> Result<Self> {
+        let template = Self(template.into());
+        template
+            .render(0, 0, 0)
+            .map_err(|err| Error::FailedInitialization {
+                adapter_type: ADAPTER_TYPE,
+                cause: Box::new(err),
+            })?;
+        Ok(template)
+    }
+
+    /// Renders the description with the given aggregated values.
+    fn render(&self, online: u32, max: u32, targets: usize) -> serde_json::Result<Box<RawValue>> {
+        let description = self
+            .0
+            .replace("{online}", &online.to_string())
+            .replace("{max}", &max.to_string())
+            .replace("{targets}", &targets.to_string());
+        RawValue::from_string(description)
+    }
+}
+
+/// The values aggregated over all targets.
+#[derive(Debug, Clone, Default)]
+struct Aggregate {
+    online: u32,
+    max: u32,
+    targets: usize,
+    sample: Vec<ServerPlayer>,
+    description: Option<Box<RawValue>>,
+}
+
+/// Status adapter that derives the player counts of another status adapter from the targets of a
+/// dedicated discovery pipeline.
+///
+/// The online and maximum player counts are summed over all targets, and the player samples are
+/// merged. The pipeline runs on status requests for an anonymous player, so it should not be shared
+/// with the route, whose actions may allocate servers or advance stateful strategies. The aggregated
+/// values are cached for `cache_duration`. All other status fields are passed through.
+#[derive(Debug)]
+pub struct AggregateStatusAdapter<S, D> {
+    adapter: S,
+    discovery: D,
+    source: AggregateSource,
+    motd: AggregateMotd,
+    max_samples: usize,
+    cache_duration: Duration,
+    cache: Mutex<Option<(Instant, Aggregate)>>,
+}
+
+impl<S, D> AggregateStatusAdapter<S, D> {
+    /// Creates a new `AggregateStatusAdapter` wrapping the given status adapter.
+    pub fn new(adapter: S, discovery: D, source: AggregateSource) -> Self {
+        Self {
+            adapter,
+            discovery,
+            source,
+            motd: AggregateMotd::default(),
+            max_samples: DEFAULT_MAX_SAMPLES,
+            cache_duration: Duration::ZERO,
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Sets where the description of the aggregated status is taken from.
+    pub fn with_motd(mut self, motd: AggregateMotd) -> Self {
+        self.motd = motd;
+        self
+    }
+
+    /// Sets the maximum number of merged sample players.
+    pub fn with_max_samples(mut self, max_samples: usize) -> Self {
+        self.max_samples = max_samples;
+        self
+    }
+
+    /// Sets the time the aggregated values are cached (disabled if zero).
+    pub fn with_cache_duration(mut self, cache_duration: Duration) -> Self {
+        self.cache_duration = cache_duration;
+        self
+    }
+
+    /// Returns the cached aggregate if it is not expired.
+    fn cached(&self, now: Instant) -> Option<Aggregate> {
+        let cache = self.cache.lock().expect("aggregate cache lock poisoned");
+        cache
+            .as_ref()
+            .filter(|(expiry, _)| *expiry > now)
+            .map(|(_, aggregate)| aggregate.clone())
+    }
+
+    /// Sums the player counts from the metadata of the targets.
+    fn aggregate_meta(targets: &[Target], online_field: &str, max_field: &str) -> Aggregate {
+        let count = |target: &Target, field: &str| {
+            target
+                .meta
+                .get(field)
+                .and_then(|value| value.parse::<u32>().ok())
+                .unwrap_or(0)
+        };
+        targets.iter().fold(
+            Aggregate {
+                targets: targets.len(),
+                ..Default::default()
+            },
+            |mut aggregate, target| {
+                aggregate.online = aggregate.online.saturating_add(count(target, online_field));
+                aggregate.max = aggregate.max.saturating_add(count(target, max_field));
+                aggregate
+            },
+        )
+    }
+
+    /// Pings all targets concurrently and sums the reported player counts.
+    async fn aggregate_ping(&self, targets: &[Target], timeout: Duration) -> Aggregate {
+        let results = join_all(
+            targets
+                .iter()
+                .map(|target| ping(ADAPTER_TYPE, target.address, timeout)),
+        )
+        .await;
+
+        let mut aggregate = Aggregate::default();
+        let mut ids = HashSet::new();
+        for (target, result) in targets.iter().zip(results) {
+            let status = match result {
+                Ok(ping) => ping.status,
+                Err(err) => {
+                    debug!(target = target.identifier, err = %err, "skipping unreachable target");
+                    continue;
+                }
+            };
+            aggregate.targets += 1;
+            if aggregate.description.is_none() {
+                aggregate.description = status.description;
+            }
+            let Some(players) = status.players else {
+                continue;
+            };
+            aggregate.online = aggregate.online.saturating_add(players.online);
+            aggregate.max = aggregate.max.saturating_add(players.max);
+            for player in players.sample.into_iter().flatten() {
+                if aggregate.sample.len() < self.max_samples && ids.insert(player.id.clone()) {
+                    aggregate.sample.push(player);
+                }
+            }
+        }
+        aggregate
+    }
+}
+
+impl<S, D> AggregateStatusAdapter<S, D>
+where
+    S: StatusAdapter,
+    D: DiscoveryActionAdapter,
+{
+    /// Returns the aggregate over the discovered targets, either from the cache or by discovering them.
+    async fn aggregate(&self, client: &Client) -> Result<Aggregate> {
+        let now = Instant::now();
+        if let Some(aggregate) = self.cached(now) {
+            trace!("using cached aggregate");
+            return Ok(aggregate);
+        }
+
+        let player = Player {
+            name: String::new(),
+            id: uuid::Uuid::nil(),
+            claims: Default::default(),
+            cookies: Default::default(),
+            session: None,
+        };
+        let mut targets = vec![];
+        self.discovery.apply(client, &player, &mut targets).await?;
+        targets.sort_by_key(|target| target.priority);
+
+        let aggregate = match &self.source {
+            AggregateSource::Meta {
+                online_field,
+                max_field,
+            } => Self::aggregate_meta(&targets, online_field, max_field),
+            AggregateSource::Ping { timeout } => self.aggregate_ping(&targets, *timeout).await,
+        };
+        debug!(
+            targets = aggregate.targets,
+            online = aggregate.online,
+            max = aggregate.max,
+            "aggregated target status"
+        );
+
+        if !self.cache_duration.is_zero() {
+            let mut cache = self.cache.lock().expect("aggregate cache lock poisoned");
+            *cache = Some((now + self.cache_duration, aggregate.clone()));
+        }
+        Ok(aggregate)
+    }
+}
+
+impl<S, D> StatusAdapter for AggregateStatusAdapter<S, D>
+where
+    S: StatusAdapter,
+    D: DiscoveryActionAdapter,
+{
+    #[tracing::instrument(skip_all)]
+    async fn status(&self, client: &Client) -> Result<Option<ServerStatus>> {
+        let mut status = self.adapter.status(client).await?.unwrap_or_default();
+        let start = Instant::now();
+        let aggregate = self.aggregate(client).await?;
+
+        match &self.motd {
+            AggregateMotd::Inner => {}
+            AggregateMotd::Top => {
+                if aggregate.description.is_some() {
+                    status.description = aggregate.description;
+                }
+            }
+            AggregateMotd::Template(template) => {
+                // the template is validated on construction and only filled in with numbers
+                status.description = template
+                    .render(aggregate.online, aggregate.max, aggregate.targets)
+                    .ok();
+            }
+        }
+        status.players = Some(ServerPlayers {
+            online: aggregate.online,
+            max: aggregate.max,
+            sample: (!aggregate.sample.is_empty()).then_some(aggregate.sample),
+        });
+
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(Some(status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FixedDiscoveryAdapter, FixedStatusAdapter};
+
+    fn create_target(id: &str, priority: u16, online: &str, max: &str) -> Target {
+        Target {
+            identifier: id.to_string(),
+            address: "127.0.0.1:25565".parse().unwrap(),
+            priority,
+            meta: [
+                ("online".to_string(), online.to_string()),
+                ("max".to_string(), max.to_string()),
+            ]
+            .into(),
+            claims: Default::default(),
+        }
+    }
+
+    fn create_adapter(
+        motd: AggregateMotd,
+    ) -> AggregateStatusAdapter<FixedStatusAdapter, FixedDiscoveryAdapter> {
+        let discovery = FixedDiscoveryAdapter::new(vec![
+            create_target("t1", 0, "10", "50"),
+            create_target("t2", 1, "5", "50"),
+            create_target("t3", 1, "invalid", "20"),
+        ]);
+        let status = FixedStatusAdapter::new(Some(ServerStatus::default()), 770, 0, 0);
+        AggregateStatusAdapter::new(
+            status,
+            discovery,
+            AggregateSource::Meta {
+                online_field: "online".to_string(),
+                max_field: "max".to_string(),
+            },
+        )
+        .with_motd(motd)
+    }
+
+    #[tokio::test]
+    async fn aggregate_meta() {
+        let adapter = create_adapter(AggregateMotd::Inner);
+        let status = adapter.status(&Client::default()).await.unwrap().unwrap();
+
+        let players = status.players.expect("players");
+        assert_eq!((players.online, players.max), (15, 120));
+        assert!(players.sample.is_none());
+        assert_eq!(status.version.protocol, 770);
+        assert!(status.description.is_none());
+    }
+
+    #[tokio::test]
+    async fn aggregate_template() {
+        let template = MotdTemplate::new(r#"{"text":"{online}/{max} on {targets}"}"#).unwrap();
+        let adapter = create_adapter(AggregateMotd::Template(template));
+        let status = adapter.status(&Client::default()).await.unwrap().unwrap();
+
+        assert_eq!(
+            status.description.expect("description").get(),
+            r#"{"text":"15/120 on 3"}"#
+        );
+    }
+
+    #[test]
+    fn reject_invalid_template() {
+        let result = MotdTemplate::new(r#"{"text":"{online} players"#);
+
+        assert!(matches!(result, Err(Error::FailedInitialization { .. })));
+    }
+}
@@ -0,0 +1,461 @@
+use crate::status::StatusAdapter;
+use crate::{Client, ServerStatus, error::Result, metrics};
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use image::imageops::FilterType;
+use image::{ImageFormat, ImageReader};
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::select;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, trace, warn};
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "favicon_status_adapter";
+
+/// The width and height of a favicon in pixels.
+pub const FAVICON_SIZE: u32 = 64;
+
+/// The prefix of a favicon data URI.
+const DATA_URI_PREFIX: &str = "data:image/png;base64,";
+
+/// The signature at the start of every PNG file.
+pub const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The maximum number of favicons that are kept in a [`FaviconCache`].
+const CACHE_CAPACITY: usize = 16;
+
+/// An error that occurs while converting an image into a favicon.
+#[derive(thiserror::Error, Debug)]
+pub enum FaviconError {
+    /// The data URI does not contain a base64 encoded PNG.
+    #[error("invalid favicon data uri, expected prefix {DATA_URI_PREFIX}")]
+    InvalidDataUri,
+
+    /// The base64 encoding of the data URI is invalid.
+    #[error("invalid favicon base64 encoding: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+
+    /// The image could not be decoded or encoded.
+    #[error("invalid favicon image: {0}")]
+    InvalidImage(#[from] image::ImageError),
+}
+
+/// Converts the image into a favicon data URI. Clients silently ignore favicons that are not a
+/// 64x64 PNG, so other images are resized and re-encoded.
+pub fn encode(bytes: &[u8]) -> Result<String, FaviconError> {
+    let image = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(image::ImageError::IoError)?
+        .decode()?;
+    if image.width() == FAVICON_SIZE
+        && image.height() == FAVICON_SIZE
+        && bytes.starts_with(PNG_SIGNATURE)
+    {
+        return Ok(format!(
+            "{DATA_URI_PREFIX}{}",
+            BASE64_STANDARD.encode(bytes)
+        ));
+    }
+
+    debug!(
+        width = image.width(),
+        height = image.height(),
+        "re-encoding favicon"
+    );
+    let image = image.resize_exact(FAVICON_SIZE, FAVICON_SIZE, FilterType::Lanczos3);
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(format!("{DATA_URI_PREFIX}{}", BASE64_STANDARD.encode(png)))
+}
+
+/// Validates the favicon data URI and re-encodes it, if it is not a 64x64 PNG.
+pub fn encode_data_uri(uri: &str) -> Result<String, FaviconError> {
+    let data = uri
+        .strip_prefix(DATA_URI_PREFIX)
+        .ok_or(FaviconError::InvalidDataUri)?;
+    encode(&BASE64_STANDARD.decode(data)?)
+}
+
+/// Cache of encoded favicons, keyed by a hash of their raw content.
+///
+/// Favicons that are received on every status request (e.g. from a gRPC service) would otherwise be
+/// decoded and possibly resized every time. The cache is cleared once it holds [`CACHE_CAPACITY`]
+/// favicons, failures are not cached.
+#[derive(Debug, Default)]
+pub struct FaviconCache {
+    favicons: Mutex<HashMap<[u8; 20], String>>,
+}
+
+impl FaviconCache {
+    /// Creates a new empty `FaviconCache`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Converts the favicon into a validated data URI. The favicon is either a raw PNG image or a
+    /// data URI, both of which are re-encoded if they are not a 64x64 PNG.
+    pub fn encode(&self, favicon: &[u8]) -> Result<String, FaviconError> {
+        let key: [u8; 20] = Sha1::digest(favicon).into();
+        if let Some(encoded) = self
+            .favicons
+            .lock()
+            .expect("favicon cache poisoned")
+            .get(&key)
+        {
+            return Ok(encoded.clone());
+        }
+
+        let encoded = if favicon.starts_with(PNG_SIGNATURE) {
+            encode(favicon)?
+        } else {
+            let uri = std::str::from_utf8(favicon).map_err(|_| FaviconError::InvalidDataUri)?;
+            encode_data_uri(uri)?
+        };
+
+        let mut favicons = self.favicons.lock().expect("favicon cache poisoned");
+        if favicons.len() >= CACHE_CAPACITY {
+            favicons.clear();
+        }
+        favicons.insert(key, encoded.clone());
+        Ok(encoded)
+    }
+}
+
+/// A [`FaviconSource`] provides a favicon data URI. If no favicon is returned (e.g., because it could
+/// not be loaded), then the favicon of the status is kept.
+pub trait FaviconSource: Debug + Send + Sync {
+    fn favicon(&self) -> impl Future<Output = Option<String>> + Send;
+}
+
+/// Favicon source that always returns the same favicon.
+#[derive(Debug)]
+pub struct FixedFaviconSource {
+    favicon: String,
+}
+
+impl FixedFaviconSource {
+    /// Creates a new `FixedFaviconSource` from a favicon data URI, which is validated (and
+    /// re-encoded if needed).
+    pub fn new(uri: &str) -> Result<Self, FaviconError> {
+        Ok(Self {
+            favicon: encode_data_uri(uri)?,
+        })
+    }
+}
+
+impl FaviconSource for FixedFaviconSource {
+    async fn favicon(&self) -> Option<String> {
+        Some(self.favicon.clone())
+    }
+}
+
+/// Favicon source that reads the favicon from an image file.
+///
+/// If a reload interval is set, a background task re-reads the file whenever its modification time
+/// changes. The task is automatically stopped once the source is dropped. Failures are logged and
+/// the previously read favicon is kept.
+#[derive(Debug)]
+pub struct FileFaviconSource {
+    /// The encoded favicon. This thread-safe container is shared between the instance and its
+    /// reload task.
+    inner: Arc<RwLock<Option<String>>>,
+
+    /// The path of the favicon file.
+    path: PathBuf,
+
+    /// The modification time of the file when it was read.
+    modified: Option<SystemTime>,
+
+    /// The cancellation token used to stop the background reload task.
+    token: CancellationToken,
+}
+
+impl FileFaviconSource {
+    /// Creates a new `FileFaviconSource` and reads the file. If the file cannot be read, no favicon
+    /// is returned until it is reloaded.
+    pub fn new(path: PathBuf) -> Self {
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        debug!(path = ?path, "reading favicon file");
+        let favicon = std::fs::read(&path)
+            .inspect_err(|err| warn!(err = %err, path = ?path, "failed to read favicon file"))
+            .ok()
+            .and_then(|content| {
+                encode(&content)
+                    .inspect_err(
+                        |err| warn!(err = %err, path = ?path, "failed to encode favicon file"),
+                    )
+                    .ok()
+            });
+        Self {
+            inner: Arc::new(RwLock::new(favicon)),
+            path,
+            modified,
+            token: CancellationToken::new(),
+        }
+    }
+
+    /// Starts the background task that checks the file for modifications every `interval`
+    /// (disabled if zero).
+    pub fn with_reload_interval(self, interval: Duration) -> Self {
+        if interval.is_zero() {
+            return self;
+        }
+
+        let inner = self.inner.clone();
+        let token = self.token.clone();
+        let path = self.path.clone();
+        let mut modified = self.modified;
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        tokio::spawn(async move {
+            info!(path = ?path, "starting favicon file reload task");
+            loop {
+                select! {
+                    biased;
+                    _ = token.cancelled() => break,
+                    _ = interval.tick() => Self::reload(&path, &mut modified, &inner).await,
+                }
+            }
+            info!(path = ?path, "stopped favicon file reload task");
+        });
+
+        self
+    }
+
+    /// Re-reads the file if it was modified since it was last read.
+    async fn reload(
+        path: &Path,
+        modified: &mut Option<SystemTime>,
+        inner: &RwLock<Option<String>>,
+    ) {
+        let next_modified = match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata.modified().ok(),
+            Err(err) => {
+                warn!(err = %err, path = ?path, "failed to read favicon file metadata");
+                return;
+            }
+        };
+        if *modified == next_modified && next_modified.is_some() {
+            return;
+        }
+
+        debug!(path = ?path, "reading favicon file");
+        let favicon = match tokio::fs::read(path).await {
+            Ok(content) => encode(&content),
+            Err(err) => {
+                warn!(err = %err, path = ?path, "failed to read favicon file");
+                return;
+            }
+        };
+        match favicon {
+            Ok(favicon) => {
+                *inner.write().await = Some(favicon);
+                *modified = next_modified;
+            }
+            Err(err) => warn!(err = %err, path = ?path, "failed to encode favicon file"),
+        }
+    }
+}
+
+impl Drop for FileFaviconSource {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+impl FaviconSource for FileFaviconSource {
+    async fn favicon(&self) -> Option<String> {
+        self.inner.read().await.clone()
+    }
+}
+
+/// Status adapter that replaces the favicon of another status adapter.
+///
+/// If multiple sources are configured, they are rotated every `rotation_interval` (or on every
+/// status request, if the interval is zero). All other status fields are passed through.
+#[derive(Debug)]
+pub struct FaviconStatusAdapter<S, F> {
+    adapter: S,
+    sources: Vec<F>,
+    rotation_interval: Duration,
+    counter: AtomicUsize,
+}
+
+impl<S, F> FaviconStatusAdapter<S, F> {
+    /// Creates a new `FaviconStatusAdapter` wrapping the given adapter.
+    pub fn new(adapter: S, sources: Vec<F>, rotation_interval: Duration) -> Self {
+        Self {
+            adapter,
+            sources,
+            rotation_interval,
+            counter: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the index of the current source.
+    fn index(&self) -> usize {
+        if self.rotation_interval.is_zero() {
+            return self.counter.fetch_add(1, Ordering::Relaxed) % self.sources.len();
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time error")
+            .as_secs();
+        (now / self.rotation_interval.as_secs().max(1)) as usize % self.sources.len()
+    }
+}
+
+impl<S, F> StatusAdapter for FaviconStatusAdapter<S, F>
+where
+    S: StatusAdapter,
+    F: FaviconSource,
+{
+    #[tracing::instrument(skip_all)]
+    async fn status(&self, client: &Client) -> Result<Option<ServerStatus>> {
+        let mut status = self.adapter.status(client).await?.unwrap_or_default();
+        if self.sources.is_empty() {
+            return Ok(Some(status));
+        }
+        let start = Instant::now();
+
+        let index = self.index();
+        trace!(index = index, "using favicon source");
+        if let Some(favicon) = self.sources[index].favicon().await {
+            status.favicon = Some(favicon);
+        }
+
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(Some(status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    fn create_png(size: u32, red: u8) -> Vec<u8> {
+        let image =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(size, size, Rgba([red, 0, 0, 255])));
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    fn decode(uri: &str) -> DynamicImage {
+        let data = BASE64_STANDARD
+            .decode(uri.strip_prefix(DATA_URI_PREFIX).unwrap())
+            .unwrap();
+        image::load_from_memory_with_format(&data, ImageFormat::Png).unwrap()
+    }
+
+    #[test]
+    fn encode_keeps_valid_favicon() {
+        let png = create_png(64, 0);
+        let favicon = encode(&png).unwrap();
+        assert_eq!(
+            favicon,
+            format!("{DATA_URI_PREFIX}{}", BASE64_STANDARD.encode(&png))
+        );
+    }
+
+    #[test]
+    fn encode_resizes_favicon() {
+        let favicon = encode(&create_png(128, 0)).unwrap();
+        let image = decode(&favicon);
+        assert_eq!((image.width(), image.height()), (64, 64));
+    }
+
+    #[test]
+    fn encode_rejects_invalid_image() {
+        assert!(encode(b"not an image").is_err());
+        assert!(matches!(
+            encode_data_uri("data:image/jpeg;base64,AAAA"),
+            Err(FaviconError::InvalidDataUri)
+        ));
+    }
+
+    #[test]
+    fn cache_encodes_favicons() {
+        let cache = FaviconCache::new();
+        let png = create_png(128, 0);
+        let favicon = cache.encode(&png).unwrap();
+        assert_eq!(decode(&favicon).width(), 64);
+        assert_eq!(cache.encode(&png).unwrap(), favicon);
+        assert_eq!(cache.encode(favicon.as_bytes()).unwrap(), favicon);
+
+        let uri = format!(
+            "{DATA_URI_PREFIX}{}",
+            BASE64_STANDARD.encode(create_png(32, 0))
+        );
+        assert_eq!(decode(&cache.encode(uri.as_bytes()).unwrap()).width(), 64);
+        assert!(matches!(
+            cache.encode(b"data:image/jpeg;base64,AAAA"),
+            Err(FaviconError::InvalidDataUri)
+        ));
+    }
+
+    #[tokio::test]
+    async fn file_source_reloads() {
+        let path =
+            std::env::temp_dir().join(format!("passage-favicon-{}.png", uuid::Uuid::new_v4()));
+        std::fs::write(&path, create_png(64, 0)).unwrap();
+        let source = FileFaviconSource::new(path.clone());
+        let mut modified = source.modified;
+        let first = source.favicon().await.expect("favicon");
+
+        // ensure the modification time changes
+        std::fs::write(&path, create_png(32, 255)).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        FileFaviconSource::reload(&path, &mut modified, &source.inner).await;
+        let second = source.favicon().await.expect("favicon");
+        std::fs::remove_file(&path).unwrap();
+        FileFaviconSource::reload(&path, &mut modified, &source.inner).await;
+
+        assert_ne!(first, second);
+        assert_eq!(decode(&second).width(), 64);
+
+        // the last favicon is kept if the file is gone
+        assert_eq!(source.favicon().await, Some(second));
+    }
+
+    #[tokio::test]
+    async fn rotate_sources() {
+        let first = encode(&create_png(64, 0)).unwrap();
+        let second = encode(&create_png(16, 255)).unwrap();
+        let adapter = FaviconStatusAdapter::new(
+            crate::FixedStatusAdapter::new(Some(ServerStatus::default()), 770, 0, 0),
+            vec![
+                FixedFaviconSource::new(&first).unwrap(),
+                FixedFaviconSource::new(&second).unwrap(),
+            ],
+            Duration::ZERO,
+        );
+
+        let client = Client::default();
+        let favicons = [
+            adapter.status(&client).await.unwrap().unwrap().favicon,
+            adapter.status(&client).await.unwrap().unwrap().favicon,
+            adapter.status(&client).await.unwrap().unwrap().favicon,
+        ];
+        assert_eq!(favicons, [Some(first.clone()), Some(second), Some(first)]);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::status::StatusAdapter;
+use crate::{Client, ServerStatus, error::Result, metrics};
+use serde_json::value::RawValue;
+use tokio::time::Instant;
+use tracing::trace;
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "maintenance_status_adapter";
+
+/// Status adapter that replaces the description (MOTD) of another status adapter while the route
+/// is in maintenance.
+///
+/// All other status fields are passed through. If the inner adapter returns no status, the default
+/// status with the maintenance description is returned.
+#[derive(Debug)]
+pub struct MaintenanceStatusAdapter<T> {
+    adapter: T,
+    description: Option<Box<RawValue>>,
+}
+
+impl<T> MaintenanceStatusAdapter<T> {
+    /// Creates a new `MaintenanceStatusAdapter` wrapping the given adapter.
+    pub fn new(adapter: T, description: Option<Box<RawValue>>) -> Self {
+        Self {
+            adapter,
+            description,
+        }
+    }
+}
+
+impl<T: StatusAdapter> StatusAdapter for MaintenanceStatusAdapter<T> {
+    #[tracing::instrument(skip_all)]
+    async fn status(&self, client: &Client) -> Result<Option<ServerStatus>> {
+        let mut status = self.adapter.status(client).await?.unwrap_or_default();
+
+        trace!("replacing description with maintenance description");
+        metrics::adapter_duration::record(ADAPTER_TYPE, Instant::now());
+        status.description = self.description.clone();
+        Ok(Some(status))
+    }
+}
@@ -1,4 +1,9 @@
+pub mod aggregate;
+pub mod favicon;
 pub mod fixed;
+pub mod maintenance;
+pub mod motd;
+pub mod post_process;
 
 use crate::{Client, ServerStatus, error::Result};
 use std::fmt::Debug;
@@ -8,3 +13,9 @@ use std::fmt::Debug;
 pub trait StatusAdapter: Debug + Send + Sync {
     fn status(&self, client: &Client) -> impl Future<Output = Result<Option<ServerStatus>>> + Send;
 }
+
+impl<T: StatusAdapter> StatusAdapter for Box<T> {
+    fn status(&self, client: &Client) -> impl Future<Output = Result<Option<ServerStatus>>> + Send {
+        (**self).status(client)
+    }
+}
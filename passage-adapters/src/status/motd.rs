@@ -0,0 +1,201 @@
+use crate::geoip::GeoIpDatabase;
+use crate::localization::LocalizationAdapter;
+use crate::status::StatusAdapter;
+use crate::{Client, ServerStatus, error::Result, metrics};
+use serde_json::value::RawValue;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::Instant;
+use tracing::trace;
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "motd_status_adapter";
+
+/// Status adapter that replaces the description (MOTD) of another status adapter with a localized
+/// message.
+///
+/// Clients do not send their locale during the status phase, so the locale is guessed from the
+/// country of the client address (looked up in a GeoIP database). Countries without a configured
+/// locale use the default locale (or the default of the localization adapter, if empty). The message
+/// may be a JSON text component or plain text and can use the `{online}` and `{max}` parameters. If
+/// the key has no translation, the description of the inner adapter is kept.
+#[derive(Debug)]
+pub struct MotdStatusAdapter<S, L> {
+    adapter: S,
+    localization: L,
+    key: String,
+    geoip: Option<Arc<GeoIpDatabase>>,
+    locales: HashMap<String, String>,
+    default_locale: Option<String>,
+}
+
+impl<S, L> MotdStatusAdapter<S, L> {
+    /// Creates a new `MotdStatusAdapter` wrapping the given adapter.
+    pub fn new(adapter: S, localization: L, key: String) -> Self {
+        Self {
+            adapter,
+            localization,
+            key,
+            geoip: None,
+            locales: HashMap::new(),
+            default_locale: None,
+        }
+    }
+
+    /// Sets the database and the locales (by ISO country code) that are used to guess the locale.
+    pub fn with_geoip(
+        mut self,
+        geoip: Arc<GeoIpDatabase>,
+        locales: HashMap<String, String>,
+    ) -> Self {
+        self.geoip = Some(geoip);
+        self.locales = locales
+            .into_iter()
+            .map(|(country, locale)| (country.to_uppercase(), locale))
+            .collect();
+        self
+    }
+
+    /// Sets the locale that is used if no locale could be guessed.
+    pub fn with_default_locale(mut self, default_locale: Option<String>) -> Self {
+        self.default_locale = default_locale;
+        self
+    }
+
+    /// Guesses the locale of the client from the country of its address.
+    fn locale(&self, client: &Client) -> Option<&str> {
+        let country = self
+            .geoip
+            .as_ref()
+            .and_then(|geoip| geoip.lookup(client.address.ip()))
+            .and_then(|location| location.country);
+        trace!(country = ?country, "resolved client country");
+        country
+            .and_then(|country| self.locales.get(&country))
+            .or(self.default_locale.as_ref())
+            .map(String::as_str)
+    }
+}
+
+impl<S, L> StatusAdapter for MotdStatusAdapter<S, L>
+where
+    S: StatusAdapter,
+    L: LocalizationAdapter,
+{
+    #[tracing::instrument(skip_all)]
+    async fn status(&self, client: &Client) -> Result<Option<ServerStatus>> {
+        let mut status = self.adapter.status(client).await?.unwrap_or_default();
+        let start = Instant::now();
+
+        let locale = self.locale(client);
+        let (online, max) = status
+            .players
+            .as_ref()
+            .map_or((0, 0), |players| (players.online, players.max));
+        let params = [("{online}", online.to_string()), ("{max}", max.to_string())];
+        let motd = self
+            .localization
+            .localize(locale, &self.key, &params)
+            .await?;
+        trace!(locale = ?locale, "localized description");
+
+        if motd != self.key {
+            // plain text messages are converted into a JSON string
+            status.description = RawValue::from_string(motd.clone())
+                .or_else(|_| serde_json::value::to_raw_value(&motd))
+                .ok();
+        }
+
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(Some(status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geoip::tests::{create_database, location};
+    use crate::{FixedLocalizationAdapter, FixedStatusAdapter};
+    use std::net::SocketAddr;
+
+    fn create_adapter(
+        key: &str,
+    ) -> MotdStatusAdapter<FixedStatusAdapter, FixedLocalizationAdapter> {
+        let localization = FixedLocalizationAdapter::new(
+            "en".to_string(),
+            HashMap::from([
+                (
+                    "en".to_string(),
+                    HashMap::from([("motd".to_string(), r#"{"text":"Welcome!"}"#.to_string())]),
+                ),
+                (
+                    "de".to_string(),
+                    HashMap::from([("motd".to_string(), "Willkommen!".to_string())]),
+                ),
+            ]),
+            false,
+        );
+        let geoip = GeoIpDatabase::from_bytes(create_database(
+            &location("DE", "EU"),
+            &location("US", "NA"),
+        ))
+        .unwrap();
+        MotdStatusAdapter::new(
+            FixedStatusAdapter::new(Some(ServerStatus::default()), 770, 0, 0),
+            localization,
+            key.to_string(),
+        )
+        .with_geoip(
+            Arc::new(geoip),
+            HashMap::from([("de".to_string(), "de".to_string())]),
+        )
+    }
+
+    fn client(address: &str) -> Client {
+        Client {
+            address: SocketAddr::new(address.parse().unwrap(), 0),
+            ..Default::default()
+        }
+    }
+
+    async fn description(
+        adapter: &MotdStatusAdapter<FixedStatusAdapter, FixedLocalizationAdapter>,
+        address: &str,
+    ) -> Option<String> {
+        let status = adapter.status(&client(address)).await.unwrap().unwrap();
+        status
+            .description
+            .map(|description| description.get().to_string())
+    }
+
+    #[tokio::test]
+    async fn locale_by_country() {
+        let adapter = create_adapter("motd");
+
+        assert_eq!(
+            description(&adapter, "10.0.0.1").await.as_deref(),
+            Some(r#""Willkommen!""#)
+        );
+        assert_eq!(
+            description(&adapter, "203.0.113.1").await.as_deref(),
+            Some(r#"{"text":"Welcome!"}"#)
+        );
+    }
+
+    #[tokio::test]
+    async fn default_locale() {
+        let adapter = create_adapter("motd").with_default_locale(Some("de".to_string()));
+
+        assert_eq!(
+            description(&adapter, "203.0.113.1").await.as_deref(),
+            Some(r#""Willkommen!""#)
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_key() {
+        let adapter = create_adapter("unknown");
+
+        assert_eq!(description(&adapter, "10.0.0.1").await, None);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::localization::LocalizationAdapter;
+use crate::status::StatusAdapter;
+use crate::{Client, Protocol, ServerPlayer, ServerPlayers, ServerStatus, error::Result, metrics};
+use serde_json::Value;
+use tokio::time::Instant;
+use tracing::trace;
+
+/// The name of the adapter. It is primarily used for logging and metrics.
+const ADAPTER_TYPE: &str = "post_process_status_adapter";
+
+/// The id of the sample entries that only display text (the nil UUID).
+const SAMPLE_TEXT_ID: &str = "00000000-0000-0000-0000-000000000000";
+
+/// A [`StatusPostProcessor`] modifies the players of a status for clients within its protocol range.
+///
+/// The online count is first offset and then clamped. Sample lines are localization keys (or
+/// literal texts) that are localized with the `{online}` and `{max}` parameters of the modified
+/// counts. Clients render sample lines as plain text, so localized JSON text components are
+/// flattened to their text.
+#[derive(Debug, Clone, Default)]
+pub struct StatusPostProcessor {
+    /// The minimum protocol version of the clients (inclusive, unbounded if empty).
+    pub min_version: Option<Protocol>,
+
+    /// The maximum protocol version of the clients (inclusive, unbounded if empty).
+    pub max_version: Option<Protocol>,
+
+    /// The offset that is added to the online count.
+    pub online_offset: i64,
+
+    /// The lower bound of the online count.
+    pub min_online: Option<u32>,
+
+    /// The upper bound of the online count.
+    pub max_online: Option<u32>,
+
+    /// Whether the maximum count is set to one more than the online count.
+    pub max_from_online: bool,
+
+    /// The lines that replace the player sample (unchanged if empty).
+    pub sample: Option<Vec<String>>,
+
+    /// Whether the player counts are hidden entirely.
+    pub hide_players: bool,
+}
+
+impl StatusPostProcessor {
+    /// Returns whether the processor applies to the protocol version of the client.
+    pub fn matches(&self, protocol_version: Protocol) -> bool {
+        self.min_version.is_none_or(|min| min <= protocol_version)
+            && self.max_version.is_none_or(|max| protocol_version <= max)
+    }
+
+    /// Applies the processor to the status, localizing the sample lines with the adapter.
+    async fn apply(
+        &self,
+        status: &mut ServerStatus,
+        localization: &impl LocalizationAdapter,
+    ) -> Result<()> {
+        if let Some(players) = &mut status.players {
+            let online = (i64::from(players.online) + self.online_offset).clamp(0, u32::MAX.into());
+            let mut online = online as u32;
+            if let Some(min) = self.min_online {
+                online = online.max(min);
+            }
+            if let Some(max) = self.max_online {
+                online = online.min(max);
+            }
+            players.online = online;
+            if self.max_from_online {
+                players.max = online.saturating_add(1);
+            }
+        }
+
+        if let Some(lines) = &self.sample {
+            let players = status.players.get_or_insert(ServerPlayers {
+                online: 0,
+                max: 0,
+                sample: None,
+            });
+            let params = [
+                ("{online}", players.online.to_string()),
+                ("{max}", players.max.to_string()),
+            ];
+            let mut sample = Vec::with_capacity(lines.len());
+            for line in lines {
+                let text = localization.localize(None, line, &params).await?;
+                sample.push(ServerPlayer {
+                    name: plain_text(text),
+                    id: SAMPLE_TEXT_ID.to_string(),
+                });
+            }
+            players.sample = Some(sample);
+        }
+
+        if self.hide_players {
+            status.players = None;
+        }
+        Ok(())
+    }
+}
+
+/// Flattens a JSON text component to its plain text, dropping all styles. Texts that are no JSON
+/// text component are returned unchanged.
+fn plain_text(text: String) -> String {
+    fn flatten(component: &Value, out: &mut String) {
+        match component {
+            Value::String(text) => out.push_str(text),
+            Value::Array(components) => components.iter().for_each(|c| flatten(c, out)),
+            Value::Object(fields) => {
+                if let Some(Value::String(text)) = fields.get("text") {
+                    out.push_str(text);
+                }
+                if let Some(Value::Array(extra)) = fields.get("extra") {
+                    extra.iter().for_each(|c| flatten(c, out));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match serde_json::from_str::<Value>(&text) {
+        Ok(component @ (Value::Object(_) | Value::Array(_))) => {
+            let mut out = String::new();
+            flatten(&component, &mut out);
+            out
+        }
+        _ => text,
+    }
+}
+
+/// Status adapter that applies a stack of [`StatusPostProcessor`] to the status of another status
+/// adapter.
+///
+/// The processors are applied in order, skipping those that do not match the protocol version of
+/// the client. If the inner adapter returns no status, the default status is processed instead.
+#[derive(Debug)]
+pub struct PostProcessStatusAdapter<S, L> {
+    adapter: S,
+    localization: L,
+    processors: Vec<StatusPostProcessor>,
+}
+
+impl<S, L> PostProcessStatusAdapter<S, L> {
+    /// Creates a new `PostProcessStatusAdapter` wrapping the given adapter.
+    pub fn new(adapter: S, localization: L, processors: Vec<StatusPostProcessor>) -> Self {
+        Self {
+            adapter,
+            localization,
+            processors,
+        }
+    }
+}
+
+impl<S, L> StatusAdapter for PostProcessStatusAdapter<S, L>
+where
+    S: StatusAdapter,
+    L: LocalizationAdapter,
+{
+    #[tracing::instrument(skip_all)]
+    async fn status(&self, client: &Client) -> Result<Option<ServerStatus>> {
+        let mut status = self.adapter.status(client).await?.unwrap_or_default();
+        let start = Instant::now();
+
+        for processor in &self.processors {
+            if !processor.matches(client.protocol_version) {
+                continue;
+            }
+            trace!(processor = ?processor, "applying status post processor");
+            processor.apply(&mut status, &self.localization).await?;
+        }
+
+        metrics::adapter_duration::record(ADAPTER_TYPE, start);
+        Ok(Some(status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FixedLocalizationAdapter, FixedStatusAdapter};
+    use std::collections::HashMap;
+
+    fn create_adapter(
+        processors: Vec<StatusPostProcessor>,
+    ) -> PostProcessStatusAdapter<FixedStatusAdapter, FixedLocalizationAdapter> {
+        let status = ServerStatus {
+            players: Some(ServerPlayers {
+                online: 10,
+                max: 100,
+                sample: None,
+            }),
+            ..Default::default()
+        };
+        let localization = FixedLocalizationAdapter::new(
+            "en".to_string(),
+            HashMap::from([(
+                "en".to_string(),
+                HashMap::from([
+                    (
+                        "status_queue".to_string(),
+                        "{online} of {max} playing".to_string(),
+                    ),
+                    (
+                        "status_discord".to_string(),
+                        r#"{"text":"Join ","color":"gray","extra":[{"text":"discord.gg/example","bold":true}]}"#
+                            .to_string(),
+                    ),
+                ]),
+            )]),
+            false,
+        );
+        PostProcessStatusAdapter::new(
+            FixedStatusAdapter::new(Some(status), 770, 0, 0),
+            localization,
+            processors,
+        )
+    }
+
+    fn client(protocol_version: Protocol) -> Client {
+        Client {
+            protocol_version,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn offset_and_clamp() {
+        let adapter = create_adapter(vec![StatusPostProcessor {
+            online_offset: 50,
+            max_online: Some(42),
+            max_from_online: true,
+            ..Default::default()
+        }]);
+        let status = adapter.status(&client(770)).await.unwrap().unwrap();
+
+        let players = status.players.expect("players");
+        assert_eq!((players.online, players.max), (42, 43));
+    }
+
+    #[tokio::test]
+    async fn negative_offset() {
+        let adapter = create_adapter(vec![StatusPostProcessor {
+            online_offset: -20,
+            ..Default::default()
+        }]);
+        let status = adapter.status(&client(770)).await.unwrap().unwrap();
+
+        assert_eq!(status.players.expect("players").online, 0);
+    }
+
+    #[tokio::test]
+    async fn localized_sample() {
+        let adapter = create_adapter(vec![StatusPostProcessor {
+            sample: Some(vec![
+                "status_queue".to_string(),
+                "discord.gg/example".to_string(),
+            ]),
+            ..Default::default()
+        }]);
+        let status = adapter.status(&client(770)).await.unwrap().unwrap();
+
+        let names: Vec<_> = status
+            .players
+            .expect("players")
+            .sample
+            .expect("sample")
+            .into_iter()
+            .map(|player| player.name)
+            .collect();
+        assert_eq!(names, vec!["10 of 100 playing", "discord.gg/example"]);
+    }
+
+    #[tokio::test]
+    async fn flattened_sample() {
+        let adapter = create_adapter(vec![StatusPostProcessor {
+            sample: Some(vec!["status_discord".to_string(), "\"quoted\"".to_string()]),
+            ..Default::default()
+        }]);
+        let status = adapter.status(&client(770)).await.unwrap().unwrap();
+
+        let names: Vec<_> = status
+            .players
+            .expect("players")
+            .sample
+            .expect("sample")
+            .into_iter()
+            .map(|player| player.name)
+            .collect();
+        assert_eq!(names, vec!["Join discord.gg/example", "\"quoted\""]);
+    }
+
+    #[tokio::test]
+    async fn per_protocol_version() {
+        let adapter = create_adapter(vec![
+            StatusPostProcessor {
+                max_version: Some(765),
+                hide_players: true,
+                ..Default::default()
+            },
+            StatusPostProcessor {
+                min_version: Some(766),
+                min_online: Some(20),
+                ..Default::default()
+            },
+        ]);
+
+        let status = adapter.status(&client(700)).await.unwrap().unwrap();
+        assert!(status.players.is_none());
+
+        let status = adapter.status(&client(770)).await.unwrap().unwrap();
+        assert_eq!(status.players.expect("players").online, 20);
+    }
+}
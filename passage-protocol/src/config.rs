@@ -1,4 +1,8 @@
+use crate::cookie::Keyring;
+use crate::ip_binding::IpBinding;
+use crate::nonce_store::{NonceStore, ReplayPolicy};
 use serde::Deserialize;
+use std::sync::Arc;
 
 /// The max packet length in bytes. Larger packets are rejected.
 pub const DEFAULT_MAX_PACKET_LENGTH: usize = 10_000;
@@ -9,10 +13,13 @@ pub const DEFAULT_AUTH_COOKIE_EXPIRY: u64 = 6 * 60 * 60;
 /// The default timeout for a single connection in seconds.
 pub const DEFAULT_CONNECTION_TIMEOUT: u64 = 120;
 
+/// The default number of previous targets kept in the session cookie.
+pub const DEFAULT_SESSION_HISTORY: usize = 5;
+
 #[derive(Debug, Clone)]
 pub struct Config {
-    /// The auth secret used to sign and verify auth cookies.
-    pub auth_secret: Option<String>,
+    /// The keyring used to sign and verify auth cookies.
+    pub auth_keyring: Option<Keyring>,
 
     /// The max packet length in bytes. Larger packets are rejected.
     pub max_packet_length: usize,
@@ -20,17 +27,57 @@ pub struct Config {
     /// The auth cookie expiry time in seconds.
     pub auth_cookie_expiry: u64,
 
+    /// The replay policy of auth cookies.
+    pub auth_cookie_replay_policy: ReplayPolicy,
+
+    /// The binding policy of the auth cookie client address.
+    pub auth_cookie_ip_binding: IpBinding,
+
+    /// The store of used auth cookie nonces. It is required for any replay policy other than
+    /// [`ReplayPolicy::Reusable`].
+    pub nonce_store: Option<Arc<dyn NonceStore>>,
+
     /// Whether to enable the proxy protocol.
     pub proxy_protocol: Option<ProxyProtocol>,
 
     /// The timeout for a single connection in seconds.
     pub connection_timeout: u64,
+
+    /// The number of previous targets kept in the session cookie.
+    pub session_history: usize,
+
+    /// The name of this Passage instance. It is recorded in new session cookies.
+    pub instance: Option<String>,
 }
 
 impl Config {
-    /// Sets the HMAC secret used to sign and verify session cookies (builder style).
+    /// Sets the HMAC secret used to sign and verify session cookies (builder style). The secret is
+    /// used as the only key of the keyring.
     pub fn with_auth_secret(mut self, auth_secret: Option<String>) -> Self {
-        self.auth_secret = auth_secret;
+        self.auth_keyring = auth_secret.map(Keyring::single);
+        self
+    }
+
+    /// Sets the auth cookie replay policy and the store of used nonces (builder style).
+    pub fn with_replay_policy(
+        mut self,
+        policy: ReplayPolicy,
+        nonce_store: Option<Arc<dyn NonceStore>>,
+    ) -> Self {
+        self.auth_cookie_replay_policy = policy;
+        self.nonce_store = nonce_store;
+        self
+    }
+
+    /// Sets the binding policy of the auth cookie client address (builder style).
+    pub fn with_ip_binding(mut self, ip_binding: IpBinding) -> Self {
+        self.auth_cookie_ip_binding = ip_binding;
+        self
+    }
+
+    /// Sets the keyring used to sign and verify session cookies (builder style).
+    pub fn with_auth_keyring(mut self, auth_keyring: Option<Keyring>) -> Self {
+        self.auth_keyring = auth_keyring;
         self
     }
 }
@@ -38,11 +85,16 @@ impl Config {
 impl Default for Config {
     fn default() -> Self {
         Self {
-            auth_secret: None,
+            auth_keyring: None,
             max_packet_length: DEFAULT_MAX_PACKET_LENGTH,
             auth_cookie_expiry: DEFAULT_AUTH_COOKIE_EXPIRY,
+            auth_cookie_replay_policy: ReplayPolicy::default(),
+            auth_cookie_ip_binding: IpBinding::default(),
+            nonce_store: None,
             proxy_protocol: None,
             connection_timeout: DEFAULT_CONNECTION_TIMEOUT,
+            session_history: DEFAULT_SESSION_HISTORY,
+            instance: None,
         }
     }
 }
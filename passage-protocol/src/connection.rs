@@ -1,18 +1,24 @@
 use crate::config::Config;
 use crate::cookie::{
-    AUTH_COOKIE_KEY, AuthCookie, CookieDecodeExt, CookieEncodeExt, SESSION_COOKIE_KEY,
-    SessionCookie,
+    AUTH_COOKIE_KEY, AuthCookie, CookieDecodeExt, CookieEncodeExt, Error as CookieError,
+    SESSION_COOKIE_KEY, SessionCookie,
 };
+use crate::crypto::EncryptionFailure;
 pub(crate) use crate::error::Error;
+use crate::nonce_store::ReplayPolicy;
 use crate::routes::{Route, Routes};
 use crate::{crypto, metrics};
 use futures::{SinkExt, StreamExt};
 use opentelemetry::global;
+use opentelemetry::trace::TraceContextExt;
 use passage_adapters::Error::Rejected;
-use passage_adapters::authentication::{AuthenticationAdapter, Profile};
+use passage_adapters::authentication::{
+    AuthenticationAdapter, AuthenticationMode, Profile, UNAUTHENTICATED_KEY,
+};
 use passage_adapters::localization::LocalizationAdapter;
 use passage_adapters::{
-    Client, DiscoveryActionAdapter, Player, ServerStatus, reject_reason, status::StatusAdapter,
+    Claims, Client, DiscoveryActionAdapter, Player, ServerStatus, Session, reject_reason,
+    status::StatusAdapter,
 };
 use passage_packets::codec::{PacketCodec, PacketFrame};
 use passage_packets::configuration::clientbound as conf_out;
@@ -140,7 +146,12 @@ where
         // Build a new status request future.
         let profile_future = async {
             route
-                .authenticate(client, player, shared_secret, &crypto::ENCODED_PUB)
+                .authenticate(
+                    client,
+                    player,
+                    shared_secret,
+                    crypto::key_pair().encoded_public_key(),
+                )
                 .await
         };
 
@@ -154,13 +165,54 @@ where
         // Handle profile not found.
         match profile {
             Ok(profile) => Ok(profile),
-            Err(Rejected { reason, .. }) => {
+            Err(Rejected { reason, params, .. }) => {
                 info!("profile not found, disconnecting");
+                if reason.as_deref() == Some(UNAUTHENTICATED_KEY) {
+                    debug!("client did not authenticate although requested");
+                    metrics::encryption_failures::inc("unauthenticated");
+                }
+                let reason = route
+                    .localize(
+                        self.client_locale.as_deref(),
+                        reason.as_deref().unwrap_or(UNAUTHENTICATED_KEY),
+                        &params,
+                    )
+                    .await?;
+                self.send_packet(login_out::DisconnectPacket { reason })
+                    .await?;
+                Err(Error::ConnectionClosed)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Authorizes the identified player using the [`AuthenticationAdapter`]. If the adapter errors,
+    /// the connection is closed. If the adapter rejects the player, then a disconnect packet is sent
+    /// and the connection is closed.
+    #[instrument(skip_all)]
+    async fn authorize(
+        &mut self,
+        route: &Route<Stat, Disc, Auth, Loca>,
+        client: &Client,
+        player: &Player,
+    ) -> Result<(), Error> {
+        // Wait for the authentication adapter to complete. Stop if the connection is shutdown.
+        let shutdown = self.shutdown.clone();
+        let authorized = tokio::select! {
+            authorized = route.authorize(client, player) => authorized,
+            _ = shutdown.cancelled() => Err(reject_reason("adapters", "disconnect_timeout")),
+        };
+
+        // Handle player not authorized.
+        match authorized {
+            Ok(()) => Ok(()),
+            Err(Rejected { reason, params, .. }) => {
+                info!("player not authorized, disconnecting");
                 let reason = route
                     .localize(
                         self.client_locale.as_deref(),
                         reason.as_deref().unwrap_or("disconnect_unauthenticated"),
-                        &[],
+                        &params,
                     )
                     .await?;
                 self.send_packet(login_out::DisconnectPacket { reason })
@@ -171,6 +223,44 @@ where
         }
     }
 
+    /// Checks the client address of the auth cookie against the configured [`IpBinding`]. Returns
+    /// whether the cookie is accepted. The policy and mismatch reason are recorded in the span.
+    #[instrument(skip_all, fields(policy = self.config.auth_cookie_ip_binding.name(), mismatch = field::Empty))]
+    fn check_binding(&self, cookie: &AuthCookie) -> bool {
+        let checked = self
+            .config
+            .auth_cookie_ip_binding
+            .check(cookie.client_addr.ip(), self.client_address.ip());
+        if let Err(mismatch) = checked {
+            tracing::Span::current().record("mismatch", field::display(mismatch));
+            debug!(
+                cookie_ip = %cookie.client_addr.ip(),
+                client_ip = %self.client_address.ip(),
+                mismatch = %mismatch,
+                "auth cookie ip binding mismatch"
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Checks the auth cookie against the configured [`ReplayPolicy`]. Returns whether the cookie
+    /// is accepted. Cookies without nonce are only accepted by [`ReplayPolicy::Reusable`].
+    #[instrument(skip_all, fields(policy = ?self.config.auth_cookie_replay_policy))]
+    async fn check_replay(&self, cookie: &AuthCookie) -> bool {
+        let policy = self.config.auth_cookie_replay_policy;
+        if policy == ReplayPolicy::Reusable {
+            return true;
+        }
+        let (Some(nonce), Some(store)) = (cookie.nonce, &self.config.nonce_store) else {
+            return false;
+        };
+
+        let ttl = Duration::from_secs(self.config.auth_cookie_expiry);
+        let previous = store.swap(nonce, cookie.timestamp, ttl).await;
+        policy.accepts(previous, cookie.timestamp)
+    }
+
     /// Awaits the next packet from the stream or for the cancellation token to be canceled. If the
     /// cancellation token is canceled, then the connection is closed.
     #[instrument(skip_all, fields(packet_length = field::Empty, packet_id = field::Empty))]
@@ -233,13 +323,23 @@ where
         }
     }
 
-    #[instrument(skip_all)]
-    fn apply_encryption(&mut self, shared_secret: &[u8]) {
-        debug!("enabling encryption");
-        self.stream
-            .codec_mut()
-            .encrypt(shared_secret)
-            .expect("Secret key is always generated to be valid");
+    /// Rejects the connection because of a failed encryption step. The failure is recorded and a
+    /// localized disconnect packet is sent (encrypted, if encryption was already enabled), before
+    /// the connection is closed.
+    #[instrument(skip_all, fields(failure = failure.label()))]
+    async fn reject_encryption(
+        &mut self,
+        route: &Route<Stat, Disc, Auth, Loca>,
+        failure: EncryptionFailure,
+    ) -> Result<(), Error> {
+        debug!("encryption step failed, disconnecting");
+        metrics::encryption_failures::inc(failure.label());
+        let reason = route
+            .localize(self.client_locale.as_deref(), failure.key(), &[])
+            .await?;
+        self.send_packet(login_out::DisconnectPacket { reason })
+            .await?;
+        Err(Error::ConnectionClosed)
     }
 
     #[instrument(skip_all)]
@@ -330,7 +430,7 @@ where
         // handle login request
         debug!("awaiting login start packet");
         let packet = self.next_packet().await?;
-        let mut login_start = match_packet! { packet,
+        let login_start = match_packet! { packet,
             packet = login_in::LoginStartPacket => packet,
             (unexpected, _) => {
                 info!(unexpected = ?unexpected, "expected login start packet, closing connection");
@@ -340,6 +440,9 @@ where
         let mut player = Player {
             name: login_start.user_name,
             id: login_start.user_id,
+            claims: Claims::new(),
+            cookies: HashMap::new(),
+            session: None,
         };
 
         // check session
@@ -362,6 +465,24 @@ where
         debug!("decoding the session cookie");
         let session_cookie = session_packet.decode::<SessionCookie>()?;
 
+        // link the trace of the previous pass and expose the session to the adapters
+        if let Some(session) = &session_cookie {
+            let previous =
+                global::get_text_map_propagator(|propagator| propagator.extract(&session.extra));
+            let span_context = previous.span().span_context().clone();
+            if span_context.is_valid() {
+                tracing::Span::current().add_link(span_context);
+            }
+
+            player.session = Some(Session {
+                id: session.id,
+                first_seen: session.first_seen,
+                hops: session.hops,
+                targets: session.targets.clone(),
+                instance: session.instance.clone(),
+            });
+        }
+
         // In case the client asked to be transferred, then we also request the Passage auth cookie.
         // The auth cookie contains (verified) information about the client session signed using some
         // shared secret. If the same shared secret is also configured for Passage and the signature
@@ -374,9 +495,10 @@ where
         // handle transfer request (verifies auth cookie)
         let mut should_authenticate = true;
         let mut profile_properties = vec![];
+        let mut cookie_nonce = None;
         'transfer: {
             if handshake.next_state == State::Transfer {
-                if self.config.auth_secret.is_none() {
+                if self.config.auth_keyring.is_none() {
                     debug!("no auth secret configured, skipping auth cookie");
                     break 'transfer;
                 }
@@ -397,13 +519,14 @@ where
                     }
                 }?;
 
-                let Some(secret) = &self.config.auth_secret else {
+                let Some(keyring) = &self.config.auth_keyring else {
                     debug!("no auth secret configured, skipping auth cookie");
                     break 'transfer;
                 };
 
-                let Some(cookie) = cookie.decode_verified::<AuthCookie>(secret.as_bytes())? else {
+                let Some(cookie) = cookie.decode_verified::<AuthCookie>(keyring)? else {
                     debug!("decoding or verifying failed, skipping auth cookie");
+                    metrics::auth_cookie_rejections::inc("invalid");
                     break 'transfer;
                 };
 
@@ -413,17 +536,67 @@ where
                     .expect("time error")
                     .as_secs();
 
-                if cookie.client_addr.ip() != self.client_address.ip() || expires_at < now {
-                    debug!("invalid auth cookie payload received, skipping auth cookie");
+                if !self.check_binding(&cookie) {
+                    debug!("auth cookie client address mismatch, skipping auth cookie");
+                    metrics::auth_cookie_rejections::inc("address");
+                    break 'transfer;
+                }
+
+                if expires_at < now {
+                    debug!("auth cookie expired, skipping auth cookie");
+                    metrics::auth_cookie_rejections::inc("expired");
+                    break 'transfer;
+                }
+
+                if !self.check_replay(&cookie).await {
+                    debug!("auth cookie rejected by replay policy, skipping auth cookie");
+                    metrics::auth_cookie_rejections::inc("replayed");
                     break 'transfer;
                 }
 
                 should_authenticate = false;
+                cookie_nonce = cookie.nonce;
 
                 // update state by token
-                login_start.user_name = cookie.user_name;
-                login_start.user_id = cookie.user_id;
+                player.name = cookie.user_name;
+                player.id = cookie.user_id;
                 profile_properties = cookie.profile_properties;
+                player.claims = cookie.extra;
+            }
+        }
+
+        // Routes may request additional cookies that were set by the backend servers (e.g., the last
+        // played game). Only transferred clients may present cookies. The decoded payloads are exposed
+        // to the adapters. Missing or invalid cookies are skipped.
+
+        // handle additional cookies
+        if handshake.next_state == State::Transfer {
+            for rule in &route.cookies.request {
+                debug!(key = rule.key, "sending cookie request packet");
+                self.send_packet(login_out::CookieRequestPacket {
+                    key: rule.key.clone(),
+                })
+                .await?;
+
+                debug!(key = rule.key, "awaiting cookie response packet");
+                let packet = self.next_packet().await?;
+                let cookie = match_packet! { packet,
+                    packet = login_in::CookieResponsePacket => packet,
+                    (unexpected, _) => {
+                        info!(unexpected = ?unexpected, "expected cookie packet, closing connection");
+                        return Err(Error::ConnectionClosed);
+                    }
+                }?;
+
+                let value = cookie
+                    .payload
+                    .filter(|payload| !payload.is_empty())
+                    .and_then(|payload| rule.decode(&payload));
+                let Some(value) = value else {
+                    debug!(key = rule.key, "cookie missing or invalid, skipping cookie");
+                    continue;
+                };
+                player.cookies.insert(rule.key.clone(), value);
             }
         }
 
@@ -434,40 +607,75 @@ where
         // public key is generated on startup for each Passage instance (i.e., each Passage instance
         // poses as a separate Minecraft server).
         // In case the previous transfer step did not succeed, then we tell the client to authenticate
-        // their login request against the Mojang API.
+        // their login request against the Mojang API (unless the route is in offline-mode).
+        // Routes without encryption skip the encryption handshake entirely.
 
         // handle encryption
-        let verify_token = crypto::generate_token()?;
-
-        debug!("sending encryption request packet");
-        self.send_packet(login_out::EncryptionRequestPacket {
-            server_id: String::new(),
-            public_key: crypto::ENCODED_PUB.clone(),
-            verify_token,
-            should_authenticate,
-        })
-        .await?;
+        let mode = route.mode();
+        let shared_secret = if mode == AuthenticationMode::Unencrypted {
+            debug!("skipping encryption for unencrypted route");
+            vec![]
+        } else {
+            let verify_token = crypto::generate_token()?;
+
+            debug!("sending encryption request packet");
+            self.send_packet(login_out::EncryptionRequestPacket {
+                server_id: String::new(),
+                public_key: crypto::key_pair().encoded_public_key().to_vec(),
+                verify_token,
+                should_authenticate: should_authenticate && mode == AuthenticationMode::Online,
+            })
+            .await?;
 
-        debug!("awaiting encryption response packet");
-        let packet = self.next_packet().await?;
-        let encrypt = match_packet! { packet,
-            packet = login_in::EncryptionResponsePacket => packet,
-            (unexpected, _) => {
-                info!(unexpected = ?unexpected, "expected encryption packet, closing connection");
-                return Err(Error::ConnectionClosed);
-            }
-        }?;
+            debug!("awaiting encryption response packet");
+            let packet = self.next_packet().await?;
+            let encrypt = match_packet! { packet,
+                packet = login_in::EncryptionResponsePacket => Some(packet?),
+                (unexpected, _) => {
+                    debug!(unexpected = ?unexpected, "expected encryption packet");
+                    None
+                }
+            };
+            let Some(encrypt) = encrypt else {
+                return self
+                    .reject_encryption(&route, EncryptionFailure::UnexpectedPacket)
+                    .await;
+            };
 
-        // decrypt the shared secret and verify the token
-        let shared_secret = crypto::decrypt(&crypto::KEY_PAIR.0, &encrypt.shared_secret)?;
-        let decrypted_verify_token = crypto::decrypt(&crypto::KEY_PAIR.0, &encrypt.verify_token)?;
+            // decrypt the shared secret, the client can only read the disconnect if it never
+            // enabled encryption
+            debug!("decrypting shared secret");
+            let private_key = crypto::key_pair().private_key();
+            let Ok(shared_secret) = crypto::decrypt(private_key, &encrypt.shared_secret) else {
+                return self
+                    .reject_encryption(&route, EncryptionFailure::SharedSecretDecryption)
+                    .await;
+            };
 
-        // verify the token is correct
-        debug!("verifying verify token");
-        if !crypto::verify_token(verify_token, &decrypted_verify_token) {
-            info!("received invalid verify token, closing connection");
-            return Err(Error::ConnectionClosed);
-        }
+            // enable encryption for the connection using the shared secret, any following
+            // disconnect is sent encrypted so the client is able to display it
+            debug!("enabling encryption");
+            if self.stream.codec_mut().encrypt(&shared_secret).is_err() {
+                return self
+                    .reject_encryption(&route, EncryptionFailure::SharedSecretLength)
+                    .await;
+            }
+
+            // verify the token is correct
+            debug!("verifying verify token");
+            let Ok(decrypted_verify_token) = crypto::decrypt(private_key, &encrypt.verify_token)
+            else {
+                return self
+                    .reject_encryption(&route, EncryptionFailure::VerifyTokenDecryption)
+                    .await;
+            };
+            if !crypto::verify_token(verify_token, &decrypted_verify_token) {
+                return self
+                    .reject_encryption(&route, EncryptionFailure::VerifyTokenMismatch)
+                    .await;
+            }
+            shared_secret
+        };
 
         // If necessary, we now also make an authentication request using the authentication adapter.
         // By default, this entails making an HTTP request against the Mojang API.
@@ -475,9 +683,6 @@ where
         // between the login start packet and adapter. Passage instead uses adapter information.
         // Generally, Passage tries to prevent states that result in clients getting disconnected.
 
-        // enable encryption for the connection using the shared secret
-        self.apply_encryption(&shared_secret);
-
         // handle authentication if not already authenticated by the token
         if should_authenticate {
             debug!("authenticating user");
@@ -489,6 +694,11 @@ where
             player.name = profile.name;
             player.id = profile.id;
             profile_properties = profile.properties;
+            player.claims.extend(profile.claims);
+        } else {
+            // players restored from the auth cookie still have to be authorized (e.g., bans)
+            debug!("authorizing user");
+            self.authorize(&route, &client, &player).await?;
         }
 
         debug!("sending login success packet");
@@ -518,7 +728,7 @@ where
                     maybe_target
                 }
             }
-        });
+        }.in_current_span());
 
         // Next, the login phase completes by receiving the login acknowledged packet. Starting with
         // the configuration phase, the protocol becomes less strict. We primarily wait for the target
@@ -606,13 +816,13 @@ where
         // disconnect if not target found
         let target = match target {
             Ok(target) => target,
-            Err(Rejected { reason, .. }) => {
+            Err(Rejected { reason, params, .. }) => {
                 info!("no transfer target found, disconnecting");
                 let reason = route
                     .localize(
                         self.client_locale.as_deref(),
                         reason.as_deref().unwrap_or("disconnect_no_target"),
-                        &[],
+                        &params,
                     )
                     .await?;
                 self.send_packet(conf_out::DisconnectPacket { reason })
@@ -623,13 +833,26 @@ where
         };
 
         // If the shared secret for the auth cookie is set, then we set a new auth cookie using the
-        // (verified) user information gained from the Mojang API.
-        // We also set the session cookie if it is not set. It includes the current OpenTelemetry
-        // trace id as well as additional client and user information.
+        // (verified) user information gained from the Mojang API. The claims of the previous auth
+        // cookie are kept, but claims of the authentication adapter and the target take precedence.
+        // We also update the session cookie (or create it if it is not set). It includes the current
+        // OpenTelemetry trace context, the previous targets as well as additional client information.
+        // Before that, the cookies configured by the route are cleared.
         // Lastly, we transfer the user to the selected target.
 
+        // clear cookies (before the Passage cookies are written)
+        for key in &route.cookies.clear {
+            debug!(key = key, "clearing cookie");
+            self.send_packet(conf_out::StoreCookiePacket {
+                key: key.clone(),
+                payload: vec![],
+            })
+            .await?;
+        }
+
         // write auth cookie
-        if let Some(secret) = &self.config.auth_secret {
+        player.claims.extend(target.claims);
+        if let Some(keyring) = &self.config.auth_keyring {
             debug!("writing auth cookie");
 
             let cookie = AuthCookie {
@@ -642,35 +865,53 @@ where
                 user_id: player.id,
                 target: Some(target.identifier.clone()),
                 profile_properties,
-                extra: Default::default(),
+                nonce: Some(match self.config.auth_cookie_replay_policy {
+                    ReplayPolicy::Sliding => cookie_nonce.unwrap_or_else(Uuid::new_v4),
+                    _ => Uuid::new_v4(),
+                }),
+                extra: player.claims,
             };
 
-            debug!("sending auth cookie packet");
-            self.send_packet(conf_out::StoreCookiePacket::encode_signed(
-                secret.as_bytes(),
-                &cookie,
-            )?)
-            .await?;
+            match conf_out::StoreCookiePacket::encode_signed(keyring, &cookie) {
+                Ok(packet) => {
+                    debug!("sending auth cookie packet");
+                    self.send_packet(packet).await?;
+                }
+                Err(CookieError::TooLarge { size, max }) => {
+                    warn!(size, max, "auth cookie too large, skipping auth cookie");
+                }
+                Err(err) => return Err(err.into()),
+            }
         }
 
-        // set session id if not exist (does not override the session fields)
-        if session_cookie.is_none() {
-            debug!("sending session cookie packet");
-            let mut extra = HashMap::new();
-            global::get_text_map_propagator(|propagator| {
-                propagator.inject_context(&tracing::Span::current().context(), &mut extra);
-            });
-
-            let cookie = SessionCookie {
-                id: Uuid::new_v4(),
-                server_address: client.server_address.clone(),
-                server_port: client.server_port,
-                extra,
-            };
+        // update session (keeps the initial session fields)
+        let mut cookie = session_cookie.unwrap_or_else(|| SessionCookie {
+            id: Uuid::new_v4(),
+            server_address: client.server_address.clone(),
+            server_port: client.server_port,
+            first_seen: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time error")
+                .as_secs(),
+            hops: 0,
+            targets: vec![],
+            instance: self.config.instance.clone(),
+            extra: HashMap::new(),
+        });
+        cookie.hops = cookie.hops.saturating_add(1);
+        cookie.targets.push(target.identifier.clone());
+        let overflow = cookie
+            .targets
+            .len()
+            .saturating_sub(self.config.session_history);
+        cookie.targets.drain(..overflow);
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&tracing::Span::current().context(), &mut cookie.extra);
+        });
 
-            self.send_packet(conf_out::StoreCookiePacket::encode(&cookie)?)
-                .await?;
-        }
+        debug!("sending session cookie packet");
+        self.send_packet(conf_out::StoreCookiePacket::encode(&cookie)?)
+            .await?;
 
         // create a new transfer packet and send it
         let transfer = conf_out::TransferPacket {
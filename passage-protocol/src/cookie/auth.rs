@@ -35,8 +35,13 @@ pub struct AuthCookie {
     /// The (authenticated) profile properties of the player.
     pub profile_properties: Vec<ProfileProperty>,
 
-    /// Any additional system-specific (secured) information. This includes the OpenTelemetry tracing
-    /// information.
+    /// The unique id (jti) of the cookie. It is used to detect replays of the cookie, depending on the
+    /// configured replay policy.
+    #[serde(default)]
+    pub nonce: Option<Uuid>,
+
+    /// Any additional system-specific (secured) information. This includes the claims of the adapters
+    /// that are handed back to the adapters on the next transfer.
     #[serde(default)]
     pub extra: HashMap<String, String>,
 }
@@ -4,4 +4,8 @@ pub enum Error {
     /// Parsing the cookie failed.
     #[error(transparent)]
     ParsingFailed(#[from] serde_json::Error),
+
+    /// The encoded cookie exceeds the max cookie size accepted by clients.
+    #[error("cookie too large: {size} bytes (max {max} bytes)")]
+    TooLarge { size: usize, max: usize },
 }
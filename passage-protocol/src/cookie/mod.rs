@@ -1,20 +1,58 @@
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use hkdf::Hkdf;
 use hmac::{Hmac, KeyInit, Mac};
 use passage_packets::configuration::clientbound::StoreCookiePacket;
 use passage_packets::login::serverbound::CookieResponsePacket;
+use rand::Rng;
+use rand::rand_core::UnwrapErr;
+use rand::rngs::SysRng;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
+use std::io::{Read, Write};
 
 pub mod auth;
 pub mod error;
+pub mod keyring;
+pub mod rule;
 pub mod session;
 
 pub use auth::*;
 pub use error::*;
+pub use keyring::*;
+pub use rule::*;
 pub use session::*;
 
 /// Hmac type, expects 32 Byte hash
 pub type HmacSha256 = Hmac<Sha256>;
 
+/// The format byte of messages signed with a keyed HMAC-SHA256 signature.
+pub const FORMAT_HMAC: u8 = 0x01;
+
+/// The format byte of messages signed with a keyed Ed25519 signature.
+pub const FORMAT_ED25519: u8 = 0x02;
+
+/// The format byte of messages encrypted with a keyed XChaCha20-Poly1305 cipher.
+pub const FORMAT_ENCRYPTED: u8 = 0x03;
+
+/// The max cookie payload size in bytes accepted by clients.
+pub const MAX_COOKIE_SIZE: usize = 5 * 1024;
+
+/// The message size in bytes above which messages are compressed before encryption.
+const COMPRESSION_THRESHOLD: usize = 512;
+
+/// The compression flags of encrypted messages.
+const COMPRESSION_NONE: u8 = 0x00;
+const COMPRESSION_DEFLATE: u8 = 0x01;
+
+/// The size of the XChaCha20-Poly1305 nonce in bytes.
+const NONCE_SIZE: usize = 24;
+
 pub trait Cookie: Sized {
     const KEY: &'static str;
 }
@@ -48,10 +86,188 @@ pub fn verify<'a>(signed: &'a [u8], secret: &[u8]) -> Option<&'a [u8]> {
     mac.verify_slice(&signed[..32]).ok().map(|_| &signed[32..])
 }
 
+/// Builds the header of a keyed message. The header is part of the signed data, so the key id
+/// cannot be swapped.
+fn keyed_header(format: u8, kid: &str) -> Vec<u8> {
+    let kid = kid.as_bytes();
+    assert!(kid.len() <= u8::MAX as usize, "key id too long");
+    let mut header = Vec::with_capacity(2 + kid.len());
+    header.push(format);
+    header.push(kid.len() as u8);
+    header.extend_from_slice(kid);
+    header
+}
+
+/// Splits a keyed message into its format, key id, header and remaining bytes (signature and
+/// message).
+fn split_keyed(signed: &[u8]) -> Option<(u8, &str, &[u8], &[u8])> {
+    let (&format, rest) = signed.split_first()?;
+    let (&kid_len, rest) = rest.split_first()?;
+    let kid = std::str::from_utf8(rest.get(..kid_len as usize)?).ok()?;
+    let (header, rest) = signed.split_at(2 + kid_len as usize);
+    Some((format, kid, header, rest))
+}
+
+/// Returns the key id of a keyed message, signed with [`sign_hmac`] or [`sign_ed25519`]. Use it to
+/// select the key for verification.
+#[must_use]
+pub fn signed_key_id(signed: &[u8]) -> Option<&str> {
+    split_keyed(signed).map(|(_, kid, _, _)| kid)
+}
+
+/// Signs a message with a secret identified by a key id. Returns the signed message. Use
+/// [`verify_hmac`] to verify and destruct the signed message.
+#[must_use]
+pub fn sign_hmac(message: &[u8], kid: &str, secret: &[u8]) -> Vec<u8> {
+    let mut output = keyed_header(FORMAT_HMAC, kid);
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take key of any size!");
+    mac.update(&output);
+    mac.update(message);
+    output.extend_from_slice(&mac.finalize().into_bytes());
+    output.extend_from_slice(message);
+    output
+}
+
+/// Verifies a message signed with [`sign_hmac`]. Returns the inner message if the signature is
+/// valid. The key id is not checked, use [`signed_key_id`] to select the secret.
+#[must_use]
+pub fn verify_hmac<'a>(signed: &'a [u8], secret: &[u8]) -> Option<&'a [u8]> {
+    let (format, _, header, rest) = split_keyed(signed)?;
+    if format != FORMAT_HMAC || rest.len() < 32 {
+        return None;
+    }
+    let (hash, message) = rest.split_at(32);
+
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take key of any size!");
+    mac.update(header);
+    mac.update(message);
+    mac.verify_slice(hash).ok().map(|_| message)
+}
+
+/// Signs a message with an Ed25519 private key identified by a key id. Returns the signed message.
+/// Use [`verify_ed25519`] to verify and destruct the signed message.
+#[must_use]
+pub fn sign_ed25519(message: &[u8], kid: &str, key: &SigningKey) -> Vec<u8> {
+    let mut output = keyed_header(FORMAT_ED25519, kid);
+    let mut data = Vec::with_capacity(output.len() + message.len());
+    data.extend_from_slice(&output);
+    data.extend_from_slice(message);
+    output.extend_from_slice(&key.sign(&data).to_bytes());
+    output.extend_from_slice(message);
+    output
+}
+
+/// Verifies a message signed with [`sign_ed25519`] using the public key only. Returns the inner
+/// message if the signature is valid. The key id is not checked, use [`signed_key_id`] to select the
+/// public key.
+///
+/// This function is intended for backend servers, that should be able to verify auth cookies
+/// without being able to forge them.
+#[must_use]
+pub fn verify_ed25519<'a>(signed: &'a [u8], key: &VerifyingKey) -> Option<&'a [u8]> {
+    let (format, _, header, rest) = split_keyed(signed)?;
+    if format != FORMAT_ED25519 || rest.len() < Signature::BYTE_SIZE {
+        return None;
+    }
+    let (signature, message) = rest.split_at(Signature::BYTE_SIZE);
+    let signature = Signature::from_slice(signature).ok()?;
+
+    let mut data = Vec::with_capacity(header.len() + message.len());
+    data.extend_from_slice(header);
+    data.extend_from_slice(message);
+    key.verify_strict(&data, &signature).ok().map(|_| message)
+}
+
+/// Derives the 32 byte encryption key for [`seal`] and [`open`] from some key material (e.g. an
+/// HMAC secret) using HKDF-SHA256.
+#[must_use]
+pub fn derive_encryption_key(material: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, material)
+        .expand(b"passage cookie encryption", &mut key)
+        .expect("32 bytes is a valid output length");
+    key
+}
+
+/// Encrypts and authenticates a message with an encryption key identified by a key id. Messages
+/// larger than a threshold are compressed if that reduces their size. Returns the sealed message.
+/// Use [`open`] to verify and decrypt the sealed message.
+#[must_use]
+pub fn seal(message: &[u8], kid: &str, key: &[u8; 32]) -> Vec<u8> {
+    let mut plain = Vec::with_capacity(1 + message.len());
+    plain.push(COMPRESSION_NONE);
+    plain.extend_from_slice(message);
+    if message.len() > COMPRESSION_THRESHOLD {
+        let mut encoder = DeflateEncoder::new(vec![COMPRESSION_DEFLATE], Compression::best());
+        encoder
+            .write_all(message)
+            .expect("writing to vec never fails");
+        let compressed = encoder.finish().expect("writing to vec never fails");
+        if compressed.len() < plain.len() {
+            plain = compressed;
+        }
+    }
+
+    let mut nonce = [0u8; NONCE_SIZE];
+    UnwrapErr(SysRng).fill_bytes(&mut nonce);
+
+    let mut output = keyed_header(FORMAT_ENCRYPTED, kid);
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let encrypted = cipher
+        .encrypt(
+            &XNonce::from(nonce),
+            Payload {
+                msg: &plain,
+                aad: &output,
+            },
+        )
+        .expect("message is never too long");
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&encrypted);
+    output
+}
+
+/// Verifies and decrypts a message sealed with [`seal`]. Returns the inner message if the message
+/// is authentic. The key id is not checked, use [`signed_key_id`] to select the key.
+#[must_use]
+pub fn open(sealed: &[u8], key: &[u8; 32]) -> Option<Vec<u8>> {
+    let (format, _, header, rest) = split_keyed(sealed)?;
+    if format != FORMAT_ENCRYPTED || rest.len() < NONCE_SIZE {
+        return None;
+    }
+    let (nonce, encrypted) = rest.split_at(NONCE_SIZE);
+    let nonce = XNonce::try_from(nonce).ok()?;
+
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let plain = cipher
+        .decrypt(
+            &nonce,
+            Payload {
+                msg: encrypted,
+                aad: header,
+            },
+        )
+        .ok()?;
+
+    let (&compression, message) = plain.split_first()?;
+    match compression {
+        COMPRESSION_NONE => Some(message.to_vec()),
+        COMPRESSION_DEFLATE => {
+            let mut decompressed = Vec::new();
+            DeflateDecoder::new(message)
+                .take(MAX_COOKIE_SIZE as u64 * 64)
+                .read_to_end(&mut decompressed)
+                .ok()?;
+            Some(decompressed)
+        }
+        _ => None,
+    }
+}
+
 pub trait CookieEncodeExt: Sized {
     fn encode<T: Cookie + Serialize>(cookie: &T) -> Result<Self, Error>;
 
-    fn encode_signed<T: Cookie + Serialize>(secret: &[u8], cookie: &T) -> Result<Self, Error>;
+    fn encode_signed<T: Cookie + Serialize>(keyring: &Keyring, cookie: &T) -> Result<Self, Error>;
 }
 
 impl CookieEncodeExt for StoreCookiePacket {
@@ -63,9 +279,15 @@ impl CookieEncodeExt for StoreCookiePacket {
         })
     }
 
-    fn encode_signed<T: Cookie + Serialize>(secret: &[u8], cookie: &T) -> Result<Self, Error> {
+    fn encode_signed<T: Cookie + Serialize>(keyring: &Keyring, cookie: &T) -> Result<Self, Error> {
         let cookie_bytes = serde_json::to_vec(cookie)?;
-        let message = sign(&cookie_bytes, secret);
+        let message = keyring.sign(&cookie_bytes);
+        if message.len() > MAX_COOKIE_SIZE {
+            return Err(Error::TooLarge {
+                size: message.len(),
+                max: MAX_COOKIE_SIZE,
+            });
+        }
         Ok(StoreCookiePacket {
             key: T::KEY.to_owned(),
             payload: message,
@@ -76,31 +298,33 @@ impl CookieEncodeExt for StoreCookiePacket {
 pub trait CookieDecodeExt: Sized {
     fn decode<'de, T: Cookie + Deserialize<'de>>(&'de self) -> Result<Option<T>, Error>;
 
-    fn decode_verified<'de, T: Cookie + Deserialize<'de>>(
-        &'de self,
-        secret: &[u8],
+    fn decode_verified<T: Cookie + DeserializeOwned>(
+        &self,
+        keyring: &Keyring,
     ) -> Result<Option<T>, Error>;
 }
 
 impl CookieDecodeExt for CookieResponsePacket {
     fn decode<'de, T: Cookie + Deserialize<'de>>(&'de self) -> Result<Option<T>, Error> {
-        let Some(message) = &self.payload else {
+        // empty payloads are cleared cookies
+        let Some(message) = self.payload.as_ref().filter(|message| !message.is_empty()) else {
             return Ok(None);
         };
         Ok(serde_json::from_slice(message)?)
     }
 
-    fn decode_verified<'de, T: Cookie + Deserialize<'de>>(
-        &'de self,
-        secret: &[u8],
+    fn decode_verified<T: Cookie + DeserializeOwned>(
+        &self,
+        keyring: &Keyring,
     ) -> Result<Option<T>, Error> {
-        let Some(message) = &self.payload else {
+        // empty payloads are cleared cookies
+        let Some(message) = self.payload.as_ref().filter(|message| !message.is_empty()) else {
             return Ok(None);
         };
-        let Some(message) = verify(message, secret) else {
+        let Some((_, message)) = keyring.verify(message) else {
             return Ok(None);
         };
-        Ok(Some(serde_json::from_slice(message)?))
+        Ok(Some(serde_json::from_slice(&message)?))
     }
 }
 
@@ -131,6 +355,30 @@ mod tests {
         assert_eq!(None, verified);
     }
 
+    #[test]
+    fn sign_verify_ed25519() {
+        let message = b"justchunks";
+        let key = SigningKey::from_bytes(&[7; 32]);
+
+        let signed = sign_ed25519(message, "key", &key);
+        let verified = verify_ed25519(&signed, &key.verifying_key());
+
+        assert_eq!(Some("key"), signed_key_id(&signed));
+        assert_eq!(Some(message.as_slice()), verified);
+    }
+
+    #[test]
+    fn sign_verify_ed25519_invalid_key() {
+        let message = b"justchunks";
+        let key1 = SigningKey::from_bytes(&[7; 32]);
+        let key2 = SigningKey::from_bytes(&[8; 32]);
+
+        let signed = sign_ed25519(message, "key", &key1);
+        let verified = verify_ed25519(&signed, &key2.verifying_key());
+
+        assert_eq!(None, verified);
+    }
+
     #[test]
     fn sign_verify_invalid_message() {
         let message = b"justchunks";
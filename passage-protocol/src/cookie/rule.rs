@@ -0,0 +1,93 @@
+use crate::cookie::verify;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// [`CookieFormat`] defines how the payload of a [`CookieRule`] cookie is decoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CookieFormat {
+    /// The payload is exposed as a (UTF-8) string.
+    #[default]
+    Raw,
+
+    /// The payload is decoded as JSON.
+    Json,
+}
+
+/// [`CookieRule`] defines an additional (non-Passage) cookie that is requested from transferred
+/// clients and exposed to the adapters. Such cookies are usually set by the backend servers.
+#[derive(Debug, Clone)]
+pub struct CookieRule {
+    /// The key of the cookie (e.g. `lobby:last_game`).
+    pub key: String,
+
+    /// The format of the cookie payload.
+    pub format: CookieFormat,
+
+    /// The secret the cookie is signed with (see [`sign`](crate::cookie::sign)). Unsigned cookies
+    /// are accepted if empty.
+    pub secret: Option<Arc<[u8]>>,
+}
+
+impl CookieRule {
+    /// Creates a new [`CookieRule`] for an unsigned cookie.
+    pub fn new(key: impl Into<String>, format: CookieFormat) -> Self {
+        Self {
+            key: key.into(),
+            format,
+            secret: None,
+        }
+    }
+
+    /// Sets the secret the cookie has to be signed with.
+    #[must_use]
+    pub fn with_secret(mut self, secret: impl Into<Arc<[u8]>>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Verifies and decodes the payload of the cookie. Returns [`None`] if the signature is invalid
+    /// or the payload cannot be decoded.
+    pub fn decode(&self, payload: &[u8]) -> Option<Value> {
+        let payload = match &self.secret {
+            Some(secret) => verify(payload, secret)?,
+            None => payload,
+        };
+        match self.format {
+            CookieFormat::Raw => Some(Value::String(std::str::from_utf8(payload).ok()?.to_owned())),
+            CookieFormat::Json => serde_json::from_slice(payload).ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cookie::sign;
+
+    #[test]
+    fn decode_raw() {
+        let rule = CookieRule::new("lobby:last_game", CookieFormat::Raw);
+        assert_eq!(Some(Value::from("bedwars-3")), rule.decode(b"bedwars-3"));
+        assert_eq!(None, rule.decode(&[0xff, 0xfe]));
+    }
+
+    #[test]
+    fn decode_json() {
+        let rule = CookieRule::new("lobby:last_game", CookieFormat::Json);
+        assert_eq!(
+            Some(serde_json::json!({"game": "bedwars-3"})),
+            rule.decode(br#"{"game":"bedwars-3"}"#)
+        );
+        assert_eq!(None, rule.decode(b"bedwars-3"));
+    }
+
+    #[test]
+    fn decode_signed() {
+        let rule =
+            CookieRule::new("lobby:last_game", CookieFormat::Raw).with_secret(b"secret".as_slice());
+        let signed = sign(b"bedwars-3", b"secret");
+        assert_eq!(Some(Value::from("bedwars-3")), rule.decode(&signed));
+        assert_eq!(None, rule.decode(&sign(b"bedwars-3", b"other")));
+        assert_eq!(None, rule.decode(b"bedwars-3"));
+    }
+}
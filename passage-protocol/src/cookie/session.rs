@@ -21,8 +21,24 @@ pub struct SessionCookie {
     /// The port of the server, the client (initially) connected to.
     pub server_port: u16,
 
+    /// The time (unix seconds) at which the session was first seen.
+    #[serde(default)]
+    pub first_seen: u64,
+
+    /// The number of passes through Passage. It is incremented on every pass.
+    #[serde(default)]
+    pub hops: u32,
+
+    /// The identifiers of the (last) targets the client was transferred to, the most recent last.
+    #[serde(default)]
+    pub targets: Vec<String>,
+
+    /// The Passage instance that created the session.
+    #[serde(default)]
+    pub instance: Option<String>,
+
     /// Any additional system-specific (unsecured) information. This includes the OpenTelemetry tracing
-    /// information.
+    /// information of the most recent pass.
     #[serde(default)]
     pub extra: HashMap<String, String>,
 }
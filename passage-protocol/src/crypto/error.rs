@@ -13,6 +13,14 @@ pub enum Error {
     #[error("could not encode the public key: {0}")]
     EncodingFailed(#[from] rsa::pkcs8::spki::Error),
 
+    /// The PKCS#8 private key could not be decoded or encoded.
+    #[error("invalid PKCS#8 private key: {0}")]
+    InvalidPrivateKey(#[from] rsa::pkcs8::Error),
+
+    /// The PKCS#1 private key could not be decoded.
+    #[error("invalid PKCS#1 private key: {0}")]
+    InvalidRsaPrivateKey(#[from] rsa::pkcs1::Error),
+
     /// Failed to create a random value.
     #[error("failed to retrieve randomness: {0}")]
     UnavailableRandom(#[from] rand::rngs::SysError),
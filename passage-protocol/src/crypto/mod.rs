@@ -5,18 +5,102 @@ use passage_packets::VerifyToken;
 use rand::TryRng;
 use rand::rand_core::UnwrapErr;
 use rand::rngs::SysRng;
-use rsa::pkcs8::EncodePublicKey;
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::traits::PublicKeyParts;
 use rsa::{Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
-use std::sync::LazyLock;
+use std::fmt::{Debug, Formatter};
+use std::sync::{LazyLock, OnceLock};
 use tokio::time::Instant;
 
-/// The RSA keypair of the application.
-pub static KEY_PAIR: LazyLock<(RsaPrivateKey, RsaPublicKey)> =
-    LazyLock::new(|| generate_keypair().expect("failed to generate keypair"));
+/// The default size of generated RSA keys in bits.
+pub const DEFAULT_KEY_SIZE: usize = 1024;
 
-/// The encoded public key.
-pub static ENCODED_PUB: LazyLock<Vec<u8>> =
-    LazyLock::new(|| encode_public_key(&KEY_PAIR.1).expect("failed to encode keypair"));
+/// The RSA keypair of the application. It is generated on first use, unless it was initialized
+/// with [`init_key_pair`] beforehand.
+static KEY_PAIR: OnceLock<KeyPair> = OnceLock::new();
+
+/// The RSA keypair used for the encryption handshake, together with the encoded public key.
+pub struct KeyPair {
+    private_key: RsaPrivateKey,
+    public_key: RsaPublicKey,
+    encoded_public_key: Vec<u8>,
+}
+
+impl KeyPair {
+    /// Generates a new RSA keypair with the given size in bits.
+    pub fn generate(bits: usize) -> Result<Self, Error> {
+        let (private_key, public_key) = generate_keypair(bits)?;
+        Self::new(private_key, public_key)
+    }
+
+    /// Decodes an RSA private key from a PKCS#8 (`PRIVATE KEY`) or PKCS#1 (`RSA PRIVATE KEY`) PEM
+    /// document.
+    pub fn from_pem(pem: &str) -> Result<Self, Error> {
+        let private_key = if pem.contains("RSA PRIVATE KEY") {
+            RsaPrivateKey::from_pkcs1_pem(pem)?
+        } else {
+            RsaPrivateKey::from_pkcs8_pem(pem)?
+        };
+        let public_key = RsaPublicKey::from(&private_key);
+        Self::new(private_key, public_key)
+    }
+
+    fn new(private_key: RsaPrivateKey, public_key: RsaPublicKey) -> Result<Self, Error> {
+        let encoded_public_key = encode_public_key(&public_key)?;
+        Ok(Self {
+            private_key,
+            public_key,
+            encoded_public_key,
+        })
+    }
+
+    /// Encodes the private key as a PKCS#8 PEM document.
+    pub fn to_pem(&self) -> Result<String, Error> {
+        Ok(self.private_key.to_pkcs8_pem(LineEnding::LF)?.to_string())
+    }
+
+    /// Returns the private key.
+    pub fn private_key(&self) -> &RsaPrivateKey {
+        &self.private_key
+    }
+
+    /// Returns the public key.
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+
+    /// Returns the public key encoded for the Minecraft protocol.
+    pub fn encoded_public_key(&self) -> &[u8] {
+        &self.encoded_public_key
+    }
+
+    /// Returns the size of the key in bits.
+    pub fn bits(&self) -> usize {
+        self.public_key.n().bits() as usize
+    }
+}
+
+impl Debug for KeyPair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("bits", &self.bits())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Initializes the keypair of the application. Returns `false` if the keypair was already
+/// initialized (or generated on first use).
+pub fn init_key_pair(key_pair: KeyPair) -> bool {
+    KEY_PAIR.set(key_pair).is_ok()
+}
+
+/// Returns the keypair of the application. A keypair of the default size is generated if it was
+/// not initialized.
+pub fn key_pair() -> &'static KeyPair {
+    KEY_PAIR
+        .get_or_init(|| KeyPair::generate(DEFAULT_KEY_SIZE).expect("failed to generate keypair"))
+}
 
 /// A time anchor for generating keep alive packet IDs.
 static TIME_ANCHOR: LazyLock<Instant> = LazyLock::new(Instant::now);
@@ -29,12 +113,12 @@ pub fn generate_keep_alive() -> u64 {
 }
 
 /// Generates a new RSA keypair.
-fn generate_keypair() -> Result<(RsaPrivateKey, RsaPublicKey), Error> {
+fn generate_keypair(bits: usize) -> Result<(RsaPrivateKey, RsaPublicKey), Error> {
     // retrieve a new mutable instance of an OS RNG
     let mut rng = UnwrapErr(SysRng);
 
     // generate the corresponding key pair
-    let private_key = RsaPrivateKey::new(&mut rng, 1024)?;
+    let private_key = RsaPrivateKey::new(&mut rng, bits)?;
     let public_key = RsaPublicKey::from(&private_key);
 
     // return the newly generated key pair
@@ -76,6 +160,46 @@ pub fn verify_token(expected: VerifyToken, actual: &[u8]) -> bool {
     expected == actual
 }
 
+/// A failed step of the encryption handshake. Each failure maps to a distinct metric label and
+/// localized disconnect reason, so broken clients can be told apart from attacks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncryptionFailure {
+    /// The client answered the encryption request with a different packet.
+    UnexpectedPacket,
+    /// The shared secret could not be decrypted with the server private key.
+    SharedSecretDecryption,
+    /// The decrypted shared secret is not a valid AES key.
+    SharedSecretLength,
+    /// The verify token could not be decrypted with the server private key.
+    VerifyTokenDecryption,
+    /// The decrypted verify token does not match the token that was sent.
+    VerifyTokenMismatch,
+}
+
+impl EncryptionFailure {
+    /// Returns the label of this failure, used in metrics and traces.
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::UnexpectedPacket => "unexpected_packet",
+            Self::SharedSecretDecryption => "shared_secret_decryption",
+            Self::SharedSecretLength => "shared_secret_length",
+            Self::VerifyTokenDecryption => "verify_token_decryption",
+            Self::VerifyTokenMismatch => "verify_token_mismatch",
+        }
+    }
+
+    /// Returns the localization key of the disconnect reason for this failure.
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::UnexpectedPacket => "disconnect_encryption_unexpected_packet",
+            Self::SharedSecretDecryption => "disconnect_encryption_shared_secret_decryption",
+            Self::SharedSecretLength => "disconnect_encryption_shared_secret_length",
+            Self::VerifyTokenDecryption => "disconnect_encryption_verify_token_decryption",
+            Self::VerifyTokenMismatch => "disconnect_encryption_verify_token_mismatch",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,15 +208,42 @@ mod tests {
 
     #[test]
     fn create_keypair() {
-        generate_keypair().expect("failed to generate keypair");
+        generate_keypair(DEFAULT_KEY_SIZE).expect("failed to generate keypair");
     }
 
     #[test]
     fn can_create_keypair() {
-        let (_, key) = generate_keypair().expect("failed to generate keypair");
+        let (_, key) = generate_keypair(DEFAULT_KEY_SIZE).expect("failed to generate keypair");
         encode_public_key(&key).expect("failed to encode keypair");
     }
 
+    #[test]
+    fn pem_roundtrip() {
+        let key_pair = KeyPair::generate(DEFAULT_KEY_SIZE).expect("failed to generate keypair");
+        let pem = key_pair.to_pem().expect("failed to encode keypair");
+        let decoded = KeyPair::from_pem(&pem).expect("failed to decode keypair");
+        assert_eq!(key_pair.encoded_public_key(), decoded.encoded_public_key());
+        assert_eq!(DEFAULT_KEY_SIZE, decoded.bits());
+    }
+
+    #[test]
+    fn pem_pkcs1() {
+        use rsa::pkcs1::EncodeRsaPrivateKey;
+
+        let key_pair = KeyPair::generate(DEFAULT_KEY_SIZE).expect("failed to generate keypair");
+        let pem = key_pair
+            .private_key()
+            .to_pkcs1_pem(LineEnding::LF)
+            .expect("failed to encode keypair");
+        let decoded = KeyPair::from_pem(&pem).expect("failed to decode keypair");
+        assert_eq!(key_pair.encoded_public_key(), decoded.encoded_public_key());
+    }
+
+    #[test]
+    fn pem_invalid() {
+        assert!(KeyPair::from_pem("invalid").is_err());
+    }
+
     #[tokio::test(start_paused = true)]
     async fn generate_different_keep_alive() {
         let id1 = generate_keep_alive();
@@ -124,7 +275,7 @@ mod tests {
     #[test]
     fn can_hash() {
         let shared_secret = b"verysecuresecret";
-        let (_, key) = generate_keypair().expect("failed to generate keypair");
+        let (_, key) = generate_keypair(DEFAULT_KEY_SIZE).expect("failed to generate keypair");
         let encoded = encode_public_key(&key).expect("failed to encode keypair");
         let _ = minecraft_hash("justchunks", shared_secret, &encoded);
     }
@@ -0,0 +1,157 @@
+use passage_adapters::geoip::GeoIpDatabase;
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// The default prefix length of IPv4 addresses for [`IpBinding::Prefix`].
+pub const DEFAULT_IPV4_PREFIX: u8 = 24;
+
+/// The default prefix length of IPv6 addresses for [`IpBinding::Prefix`].
+pub const DEFAULT_IPV6_PREFIX: u8 = 64;
+
+/// [`IpBinding`] defines how the client address of an auth cookie has to match the address of
+/// the connecting client.
+#[derive(Debug, Clone, Default)]
+pub enum IpBinding {
+    /// The addresses have to be equal.
+    #[default]
+    Exact,
+
+    /// The addresses have to share the same network prefix (e.g. `/24` for IPv4 and `/64` for IPv6).
+    Prefix { ipv4: u8, ipv6: u8 },
+
+    /// The addresses have to belong to the same autonomous system, looked up in a local database.
+    Asn(Arc<GeoIpDatabase>),
+
+    /// The addresses are not checked.
+    None,
+}
+
+/// [`IpMismatch`] is the reason why the addresses did not match the [`IpBinding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpMismatch {
+    /// The addresses are not equal.
+    Address,
+
+    /// The addresses are of a different family (IPv4 and IPv6).
+    Family,
+
+    /// The addresses have different network prefixes.
+    Prefix,
+
+    /// The addresses belong to different autonomous systems.
+    Asn,
+
+    /// The autonomous system of at least one address is unknown.
+    UnknownAsn,
+}
+
+impl Display for IpMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            IpMismatch::Address => "address",
+            IpMismatch::Family => "family",
+            IpMismatch::Prefix => "prefix",
+            IpMismatch::Asn => "asn",
+            IpMismatch::UnknownAsn => "unknown_asn",
+        })
+    }
+}
+
+impl IpBinding {
+    /// Returns the name of the policy. It is primarily used for logging and tracing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            IpBinding::Exact => "exact",
+            IpBinding::Prefix { .. } => "prefix",
+            IpBinding::Asn(_) => "asn",
+            IpBinding::None => "none",
+        }
+    }
+
+    /// Checks whether the address of the auth cookie matches the address of the client.
+    pub fn check(&self, cookie: IpAddr, client: IpAddr) -> Result<(), IpMismatch> {
+        let (cookie, client) = (cookie.to_canonical(), client.to_canonical());
+        match self {
+            IpBinding::Exact if cookie != client => Err(IpMismatch::Address),
+            IpBinding::Prefix { ipv4, ipv6 } => match (cookie, client) {
+                (IpAddr::V4(cookie), IpAddr::V4(client)) => {
+                    let mask = u32::MAX.checked_shl(32 - u32::from((*ipv4).min(32)));
+                    let mask = mask.unwrap_or(0);
+                    (u32::from(cookie) & mask == u32::from(client) & mask)
+                        .then_some(())
+                        .ok_or(IpMismatch::Prefix)
+                }
+                (IpAddr::V6(cookie), IpAddr::V6(client)) => {
+                    let mask = u128::MAX.checked_shl(128 - u32::from((*ipv6).min(128)));
+                    let mask = mask.unwrap_or(0);
+                    (u128::from(cookie) & mask == u128::from(client) & mask)
+                        .then_some(())
+                        .ok_or(IpMismatch::Prefix)
+                }
+                _ => Err(IpMismatch::Family),
+            },
+            IpBinding::Asn(database) => {
+                match (database.lookup_asn(cookie), database.lookup_asn(client)) {
+                    (Some(cookie), Some(client)) if cookie == client => Ok(()),
+                    (Some(_), Some(_)) => Err(IpMismatch::Asn),
+                    _ => Err(IpMismatch::UnknownAsn),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn ip(value: &str) -> IpAddr {
+        IpAddr::from_str(value).expect("valid address")
+    }
+
+    #[test]
+    fn exact() {
+        let binding = IpBinding::Exact;
+        assert_eq!(Ok(()), binding.check(ip("10.0.0.1"), ip("10.0.0.1")));
+        assert_eq!(Ok(()), binding.check(ip("10.0.0.1"), ip("::ffff:10.0.0.1")));
+        assert_eq!(
+            Err(IpMismatch::Address),
+            binding.check(ip("10.0.0.1"), ip("10.0.0.2"))
+        );
+    }
+
+    #[test]
+    fn prefix() {
+        let binding = IpBinding::Prefix {
+            ipv4: DEFAULT_IPV4_PREFIX,
+            ipv6: DEFAULT_IPV6_PREFIX,
+        };
+        assert_eq!(Ok(()), binding.check(ip("10.0.0.1"), ip("10.0.0.254")));
+        assert_eq!(
+            Err(IpMismatch::Prefix),
+            binding.check(ip("10.0.0.1"), ip("10.0.1.1"))
+        );
+        assert_eq!(
+            Ok(()),
+            binding.check(ip("2001:db8::1"), ip("2001:db8::ffff:1"))
+        );
+        assert_eq!(
+            Err(IpMismatch::Prefix),
+            binding.check(ip("2001:db8::1"), ip("2001:db8:0:1::1"))
+        );
+        assert_eq!(
+            Err(IpMismatch::Family),
+            binding.check(ip("10.0.0.1"), ip("2001:db8::1"))
+        );
+    }
+
+    #[test]
+    fn prefix_zero() {
+        let binding = IpBinding::Prefix { ipv4: 0, ipv6: 0 };
+        assert_eq!(Ok(()), binding.check(ip("10.0.0.1"), ip("192.168.0.1")));
+        assert_eq!(Ok(()), binding.check(ip("2001:db8::1"), ip("fe80::1")));
+    }
+}
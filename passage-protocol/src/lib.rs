@@ -3,8 +3,11 @@ pub mod connection;
 pub mod cookie;
 pub mod crypto;
 pub mod error;
+pub mod ip_binding;
 pub mod listener;
 pub mod metrics;
+pub mod nonce_store;
+pub mod probe;
 pub mod rate_limiter;
 pub mod routes;
 
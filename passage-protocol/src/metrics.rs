@@ -53,6 +53,108 @@ pub(crate) mod rate_limiter_size {
     }
 }
 
+/// The metric `auth_cookie_verifications` tracks the number of verified auth cookies by the key
+/// that verified them. This allows maintainers to observe when an old key stops being used after a
+/// key rotation.
+///
+/// Attributes:
+/// - `key`: The id of the verifying key, or `none` if no key verified the cookie
+pub(crate) mod auth_cookie_verifications {
+    use crate::metrics::METER;
+    use opentelemetry::KeyValue;
+    use opentelemetry::metrics::Counter;
+    use std::sync::LazyLock;
+
+    static INSTRUMENT: LazyLock<Counter<u64>> = LazyLock::new(|| {
+        METER
+            .u64_counter("auth_cookie_verifications")
+            .with_description("The number of auth cookie verifications by key")
+            .build()
+    });
+
+    /// Increments the counter for the verifying key.
+    pub(crate) fn inc(key: Option<&str>) {
+        INSTRUMENT.add(
+            1,
+            &[KeyValue::new("key", key.unwrap_or("none").to_string())],
+        )
+    }
+}
+
+/// The metric `auth_cookie_rejections` tracks the number of auth cookies that were rejected. The
+/// client has to authenticate again in that case.
+///
+/// Attributes:
+/// - `reason`: `invalid` (verification failed), `expired`, `address` (client address mismatch) or
+///   `replayed` (rejected by the replay policy)
+pub(crate) mod auth_cookie_rejections {
+    use crate::metrics::METER;
+    use opentelemetry::KeyValue;
+    use opentelemetry::metrics::Counter;
+    use std::sync::LazyLock;
+
+    static INSTRUMENT: LazyLock<Counter<u64>> = LazyLock::new(|| {
+        METER
+            .u64_counter("auth_cookie_rejections")
+            .with_description("The number of rejected auth cookies by reason")
+            .build()
+    });
+
+    /// Increments the counter for the rejection reason.
+    pub(crate) fn inc(reason: &'static str) {
+        INSTRUMENT.add(1, &[KeyValue::new("reason", reason)])
+    }
+}
+
+/// The metric `encryption_failures` tracks the number of connections that failed the encryption
+/// handshake or the authentication that was requested with it.
+///
+/// Attributes:
+/// - `reason`: `unexpected_packet`, `shared_secret_decryption`, `shared_secret_length`,
+///   `verify_token_decryption`, `verify_token_mismatch` or `unauthenticated` (the client did not
+///   authenticate with Mojang although requested)
+pub(crate) mod encryption_failures {
+    use crate::metrics::METER;
+    use opentelemetry::KeyValue;
+    use opentelemetry::metrics::Counter;
+    use std::sync::LazyLock;
+
+    static INSTRUMENT: LazyLock<Counter<u64>> = LazyLock::new(|| {
+        METER
+            .u64_counter("encryption_failures")
+            .with_description("The number of failed encryption handshakes by reason")
+            .build()
+    });
+
+    /// Increments the counter for the failure reason.
+    pub(crate) fn inc(reason: &'static str) {
+        INSTRUMENT.add(1, &[KeyValue::new("reason", reason)])
+    }
+}
+
+/// The metric `target_probes` tracks the number of reachability probes of selected targets.
+///
+/// Attributes:
+/// - `result`: `reachable`, `unreachable` or `cached` (the result of a recent probe was reused)
+pub(crate) mod target_probes {
+    use crate::metrics::METER;
+    use opentelemetry::KeyValue;
+    use opentelemetry::metrics::Counter;
+    use std::sync::LazyLock;
+
+    static INSTRUMENT: LazyLock<Counter<u64>> = LazyLock::new(|| {
+        METER
+            .u64_counter("target_probes")
+            .with_description("The number of target reachability probes by result")
+            .build()
+    });
+
+    /// Increments the counter for the probe result.
+    pub(crate) fn inc(result: &'static str) {
+        INSTRUMENT.add(1, &[KeyValue::new("result", result)])
+    }
+}
+
 /// The metric `listener_requests` tracks the number of requests accepted by the listener independent
 /// of the connection result. In contrary to the `connection_duration` metric, this metric
 /// tracks any incoming request, not only those that are handled by the protocol.
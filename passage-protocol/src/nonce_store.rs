@@ -0,0 +1,144 @@
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// [`ReplayPolicy`] defines how often an auth cookie (identified by its nonce) may be used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayPolicy {
+    /// Auth cookies can be reused any number of times until they expire.
+    #[default]
+    Reusable,
+
+    /// Auth cookies can only be used once. Each transfer issues a new cookie with a new nonce.
+    SingleUse,
+
+    /// Auth cookies keep their nonce across transfers, but each cookie of a nonce is accepted only
+    /// once and only if it is strictly newer than the last used cookie. As timestamps have a
+    /// resolution of seconds, a cookie issued in the same second as the last used one is rejected.
+    Sliding,
+}
+
+impl ReplayPolicy {
+    /// Returns whether an auth cookie with the given timestamp is accepted, given the timestamp of
+    /// the last used cookie with the same nonce (if any).
+    pub fn accepts(self, previous: Option<u64>, timestamp: u64) -> bool {
+        match self {
+            ReplayPolicy::Reusable => true,
+            ReplayPolicy::SingleUse => previous.is_none(),
+            ReplayPolicy::Sliding => previous.is_none_or(|previous| previous < timestamp),
+        }
+    }
+}
+
+/// [`NonceStore`] records the nonces of used auth cookies to detect replays. Implementations may be
+/// backed by shared state, so replays are detected across multiple instances.
+pub trait NonceStore: Debug + Send + Sync {
+    /// Records the timestamp of an auth cookie with the given nonce and returns the previously
+    /// recorded timestamp (if any). The store keeps the latest timestamp and may forget the nonce
+    /// after the ttl. The operation has to be atomic, so concurrent uses are detected.
+    fn swap(&self, nonce: Uuid, timestamp: u64, ttl: Duration) -> BoxFuture<'_, Option<u64>>;
+}
+
+/// [`MemoryNonceStore`] is an in-memory [`NonceStore`] for a single instance. Expired nonces are
+/// removed periodically.
+#[derive(Debug)]
+pub struct MemoryNonceStore {
+    state: Mutex<MemoryState>,
+    cleanup_interval: Duration,
+}
+
+/// The state of the [`MemoryNonceStore`].
+#[derive(Debug)]
+struct MemoryState {
+    last_cleanup: Instant,
+    nonces: HashMap<Uuid, (u64, Instant)>,
+}
+
+impl MemoryNonceStore {
+    /// Creates a new `MemoryNonceStore` that removes expired nonces at most once per interval.
+    pub fn new(cleanup_interval: Duration) -> Self {
+        Self {
+            state: Mutex::new(MemoryState {
+                last_cleanup: Instant::now(),
+                nonces: HashMap::new(),
+            }),
+            cleanup_interval,
+        }
+    }
+}
+
+impl NonceStore for MemoryNonceStore {
+    fn swap(&self, nonce: Uuid, timestamp: u64, ttl: Duration) -> BoxFuture<'_, Option<u64>> {
+        let now = Instant::now();
+        let mut state = self.state.lock().expect("nonce store lock poisoned");
+        let MemoryState {
+            last_cleanup,
+            nonces,
+        } = &mut *state;
+
+        // remove expired nonces from time to time
+        if now.saturating_duration_since(*last_cleanup) >= self.cleanup_interval {
+            nonces.retain(|_, (_, expires_at)| *expires_at > now);
+            *last_cleanup = now;
+        }
+
+        let previous = nonces
+            .get(&nonce)
+            .filter(|(_, expires_at)| *expires_at > now)
+            .map(|(previous, _)| *previous);
+        let latest = previous.map_or(timestamp, |previous| previous.max(timestamp));
+        nonces.insert(nonce, (latest, now + ttl));
+
+        Box::pin(async move { previous })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sliding_accepts_only_newer() {
+        let policy = ReplayPolicy::Sliding;
+
+        assert!(policy.accepts(None, 5));
+        assert!(policy.accepts(Some(5), 6));
+        assert!(!policy.accepts(Some(5), 5));
+        assert!(!policy.accepts(Some(6), 5));
+    }
+
+    #[test]
+    fn single_use_accepts_once() {
+        let policy = ReplayPolicy::SingleUse;
+
+        assert!(policy.accepts(None, 5));
+        assert!(!policy.accepts(Some(5), 6));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn swap_returns_previous() {
+        let store = MemoryNonceStore::new(Duration::from_secs(60));
+        let nonce = Uuid::new_v4();
+        let ttl = Duration::from_secs(10);
+
+        assert_eq!(None, store.swap(nonce, 5, ttl).await);
+        assert_eq!(Some(5), store.swap(nonce, 7, ttl).await);
+        assert_eq!(Some(7), store.swap(nonce, 6, ttl).await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn swap_forgets_expired() {
+        let store = MemoryNonceStore::new(Duration::from_secs(60));
+        let nonce = Uuid::new_v4();
+        let ttl = Duration::from_secs(10);
+
+        assert_eq!(None, store.swap(nonce, 5, ttl).await);
+        tokio::time::advance(Duration::from_secs(11)).await;
+        assert_eq!(None, store.swap(nonce, 5, ttl).await);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{Error, metrics};
+use passage_adapters::Target;
+use passage_adapters::ping::ping;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use tokio::net::TcpStream;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, instrument};
+
+/// The name of the probe. It is used to report the errors of status probes.
+const PROBE_TYPE: &str = "target_probe";
+
+/// The default time a probe result is cached.
+pub const DEFAULT_CACHE_DURATION: Duration = Duration::from_secs(5);
+
+/// The default number of targets that are probed before the selection gives up.
+pub const DEFAULT_ATTEMPTS: usize = 3;
+
+/// [`ProbeMethod`] defines how the reachability of a target is checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeMethod {
+    /// The target is reachable if a TCP connection can be established.
+    #[default]
+    Tcp,
+
+    /// The target is reachable if it answers a status request (server list ping).
+    Status,
+}
+
+/// [`Probe`] checks whether a selected target is reachable before the client is transferred. The
+/// results are cached per target address for a short time, so not every login probes the target.
+#[derive(Debug)]
+pub struct Probe {
+    method: ProbeMethod,
+    timeout: Duration,
+    cache_duration: Duration,
+    attempts: usize,
+    cache: Mutex<HashMap<SocketAddr, (Instant, bool)>>,
+}
+
+impl Probe {
+    /// Creates a new `Probe` with the given method and timeout.
+    pub fn new(method: ProbeMethod, timeout: Duration) -> Self {
+        Self {
+            method,
+            timeout,
+            cache_duration: DEFAULT_CACHE_DURATION,
+            attempts: DEFAULT_ATTEMPTS,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the time a probe result is cached (disabled if zero).
+    pub fn with_cache_duration(mut self, cache_duration: Duration) -> Self {
+        self.cache_duration = cache_duration;
+        self
+    }
+
+    /// Sets the number of targets that are probed before the selection gives up.
+    pub fn with_attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Returns the number of targets that are probed before the selection gives up.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Returns whether the target is reachable, either from the cache or by probing it.
+    #[instrument(skip_all, fields(target = target.identifier))]
+    pub async fn is_reachable(&self, target: &Target) -> bool {
+        let now = Instant::now();
+        if let Some(reachable) = self.cached(target.address, now) {
+            debug!(reachable = reachable, "using cached probe result");
+            metrics::target_probes::inc("cached");
+            return reachable;
+        }
+
+        let reachable = match self.probe(target).await {
+            Ok(()) => true,
+            Err(err) => {
+                debug!(error = %err, "target probe failed");
+                false
+            }
+        };
+        let label = if reachable {
+            "reachable"
+        } else {
+            "unreachable"
+        };
+        metrics::target_probes::inc(label);
+
+        if !self.cache_duration.is_zero() {
+            let mut cache = self.cache.lock().expect("probe cache lock poisoned");
+            cache.retain(|_, (expiry, _)| *expiry > now);
+            cache.insert(target.address, (now + self.cache_duration, reachable));
+        }
+        reachable
+    }
+
+    /// Returns the cached result for the address if it is not expired.
+    fn cached(&self, address: SocketAddr, now: Instant) -> Option<bool> {
+        let cache = self.cache.lock().expect("probe cache lock poisoned");
+        cache
+            .get(&address)
+            .filter(|(expiry, _)| *expiry > now)
+            .map(|(_, reachable)| *reachable)
+    }
+
+    /// Probes the target once with the configured method within the timeout.
+    async fn probe(&self, target: &Target) -> Result<(), Error> {
+        match self.method {
+            ProbeMethod::Tcp => {
+                tokio::time::timeout(self.timeout, TcpStream::connect(target.address))
+                    .await
+                    .unwrap_or_else(|_| Err(ErrorKind::TimedOut.into()))
+                    .map_err(passage_packets::Error::from)?;
+            }
+            ProbeMethod::Status => {
+                ping(PROBE_TYPE, target.address, self.timeout).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    fn create_target(address: SocketAddr) -> Target {
+        Target {
+            identifier: "t1".to_string(),
+            address,
+            priority: 0,
+            meta: Default::default(),
+            claims: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn tcp_probe() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind failed");
+        let target = create_target(listener.local_addr().expect("local address"));
+        let probe = Probe::new(ProbeMethod::Tcp, Duration::from_secs(1));
+        assert!(probe.is_reachable(&target).await);
+
+        // the result is cached, even if the target is gone
+        drop(listener);
+        assert!(probe.is_reachable(&target).await);
+    }
+
+    #[tokio::test]
+    async fn tcp_probe_unreachable() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind failed");
+        let target = create_target(listener.local_addr().expect("local address"));
+        drop(listener);
+        let probe = Probe::new(ProbeMethod::Tcp, Duration::from_secs(1))
+            .with_cache_duration(Duration::ZERO);
+        assert!(!probe.is_reachable(&target).await);
+    }
+
+    #[tokio::test]
+    async fn status_probe_without_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind failed");
+        let target = create_target(listener.local_addr().expect("local address"));
+        let probe = Probe::new(ProbeMethod::Status, Duration::from_millis(100));
+        let _accept = tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.expect("accept failed");
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        assert!(!probe.is_reachable(&target).await);
+    }
+}
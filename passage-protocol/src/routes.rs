@@ -1,11 +1,16 @@
-use passage_adapters::authentication::Profile;
+use crate::cookie::CookieRule;
+use crate::probe::Probe;
+use passage_adapters::authentication::{AuthenticationMode, Profile};
+use passage_adapters::discovery_action::consistent_hash_strategy::rendezvous_score;
 use passage_adapters::{
     AuthenticationAdapter, Client, DiscoveryActionAdapter, LocalizationAdapter, Player, Result,
     ServerStatus, StatusAdapter, Target, reject_reason,
 };
+use rand::RngExt;
 use regex::Regex;
 use std::fmt::{Debug, Display, Formatter};
 use std::sync::Arc;
+use tracing::{Span, debug, instrument};
 
 /// A shared, immutable slice of routes. The inner `Arc` allows individual routes to be cloned
 /// cheaply across connections.
@@ -28,6 +33,59 @@ pub struct Route<Stat, Disc, Auth, Loca> {
     pub authentication_adapter: Auth,
     /// Adapter used to resolve localised messages for this route.
     pub localization_adapter: Loca,
+    /// Additional cookies that are requested from and cleared for clients of this route.
+    pub cookies: RouteCookies,
+    /// The tie-breaker that chooses among the candidates with the lowest priority.
+    pub tie_breaker: TieBreaker,
+    /// The reachability probe of the selected target (disabled if empty).
+    pub probe: Option<Arc<Probe>>,
+}
+
+/// The tie-breaker of the selection stage. After the discovery pipeline, only the targets with the
+/// lowest priority are candidates. The tie-breaker chooses one of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TieBreaker {
+    /// The first candidate (in pipeline order) is chosen.
+    #[default]
+    First,
+    /// A random candidate is chosen.
+    Random,
+    /// A candidate is chosen by hashing the player id (rendezvous hashing), so the same player is
+    /// always sent to the same candidate. The hash is the same as in the consistent hash strategy.
+    Hash,
+}
+
+impl TieBreaker {
+    /// Returns the label of this tie-breaker, used in logs and traces.
+    pub const fn label(self) -> &'static str {
+        match self {
+            TieBreaker::First => "first",
+            TieBreaker::Random => "random",
+            TieBreaker::Hash => "hash",
+        }
+    }
+
+    /// Returns the index of the chosen candidate. The candidates must not be empty.
+    fn choose(self, player: &Player, candidates: &[Target]) -> usize {
+        match self {
+            TieBreaker::First => 0,
+            TieBreaker::Random => rand::rng().random_range(0..candidates.len()),
+            TieBreaker::Hash => candidates
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, target)| std::cmp::Reverse(rendezvous_score(player, target)))
+                .map_or(0, |(index, _)| index),
+        }
+    }
+}
+
+/// The additional (non-Passage) cookies of a [`Route`].
+#[derive(Clone, Debug, Default)]
+pub struct RouteCookies {
+    /// The cookies that are requested from transferred clients and exposed to the adapters.
+    pub request: Vec<CookieRule>,
+    /// The keys of the cookies that are cleared before the client is transferred.
+    pub clear: Vec<String>,
 }
 
 impl<Stat, Disc, Auth, Loca> Route<Stat, Disc, Auth, Loca>
@@ -37,17 +95,81 @@ where
     Auth: AuthenticationAdapter,
     Loca: LocalizationAdapter,
 {
-    /// Runs the full discovery pipeline and returns the single selected [`Target`].
+    /// Runs the full discovery pipeline and returns the single selected [`Target`]. Only the
+    /// targets with the lowest priority are candidates, the [`TieBreaker`] chooses among them. If a
+    /// [`Probe`] is configured and the chosen target is unreachable, the next targets (by priority)
+    /// are tried instead.
     ///
-    /// Returns `Err` if the pipeline produces no candidates.
+    /// Returns `Err` if the pipeline produces no (reachable) candidates.
+    #[instrument(skip_all, fields(tie_breaker = self.tie_breaker.label(), target, reason))]
     pub async fn select(&self, client: &Client, player: &Player) -> Result<Target> {
         let mut targets = Vec::new();
         self.discovery_adapter
             .apply(client, player, &mut targets)
             .await?;
-        targets
-            .pop()
-            .ok_or_else(|| reject_reason("adapters", "disconnect_no_target"))
+
+        // only the targets with the lowest priority are candidates
+        let Some(priority) = targets.iter().map(|target| target.priority).min() else {
+            debug!("no candidates found");
+            return Err(reject_reason("adapters", "disconnect_no_target"));
+        };
+        targets.sort_by_key(|target| target.priority);
+        let candidates = targets
+            .iter()
+            .take_while(|target| target.priority == priority)
+            .count();
+        debug!(
+            priority = priority,
+            candidates = ?targets[..candidates].iter().map(|target| &target.identifier).collect::<Vec<_>>(),
+            "selecting target"
+        );
+
+        // choose among the candidates, the chosen one is moved to the front
+        let (index, mut reason) = if candidates == 1 {
+            (0, "single_candidate")
+        } else {
+            (
+                self.tie_breaker.choose(player, &targets[..candidates]),
+                self.tie_breaker.label(),
+            )
+        };
+        targets[..=index].rotate_right(1);
+
+        // probe the chosen target and fall back to the next targets if it is unreachable
+        let target = match &self.probe {
+            None => targets.swap_remove(0),
+            Some(probe) => {
+                let mut reachable = None;
+                for (attempt, target) in targets.into_iter().take(probe.attempts()).enumerate() {
+                    if probe.is_reachable(&target).await {
+                        if attempt > 0 {
+                            reason = "fallback";
+                        }
+                        reachable = Some(target);
+                        break;
+                    }
+                    debug!(
+                        target = target.identifier,
+                        "target unreachable, trying next"
+                    );
+                }
+                let Some(target) = reachable else {
+                    debug!("no reachable target found");
+                    return Err(reject_reason("adapters", "disconnect_no_target"));
+                };
+                target
+            }
+        };
+
+        let span = Span::current();
+        span.record("target", target.identifier.as_str());
+        span.record("reason", reason);
+        debug!(
+            target = target.identifier,
+            reason = reason,
+            "selected target"
+        );
+        Ok(target)
     }
 }
 
@@ -65,6 +187,9 @@ where
             .field("discovery_adapter", &self.discovery_adapter)
             .field("authentication_adapter", &self.authentication_adapter)
             .field("localization_adapter", &self.localization_adapter)
+            .field("cookies", &self.cookies)
+            .field("tie_breaker", &self.tie_breaker)
+            .field("probe", &self.probe)
             .finish()
     }
 }
@@ -116,6 +241,14 @@ where
             .authenticate(client, player, shared_secret, encoded_public)
             .await
     }
+
+    fn mode(&self) -> AuthenticationMode {
+        self.authentication_adapter.mode()
+    }
+
+    async fn authorize(&self, client: &Client, player: &Player) -> Result<()> {
+        self.authentication_adapter.authorize(client, player).await
+    }
 }
 
 impl<Stat, Disc, Auth, Loca> LocalizationAdapter for Route<Stat, Disc, Auth, Loca>
@@ -136,3 +269,164 @@ where
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passage_adapters::{
+        ConsistentHashStrategyAdapter, FixedAuthenticationAdapter, FixedDiscoveryAdapter,
+        FixedLocalizationAdapter, FixedStatusAdapter,
+    };
+
+    type FixedRoute = Route<
+        FixedStatusAdapter,
+        FixedDiscoveryAdapter,
+        FixedAuthenticationAdapter,
+        FixedLocalizationAdapter,
+    >;
+
+    fn create_route(priorities: &[u16], tie_breaker: TieBreaker) -> FixedRoute {
+        let targets = priorities
+            .iter()
+            .enumerate()
+            .map(|(i, priority)| Target {
+                identifier: format!("t{i}"),
+                address: "127.0.0.1:25565".parse().expect("valid address"),
+                priority: *priority,
+                meta: Default::default(),
+                claims: Default::default(),
+            })
+            .collect();
+        Route {
+            hostname: Regex::new(".*").expect("valid regex"),
+            status_adapter: FixedStatusAdapter::default(),
+            discovery_adapter: FixedDiscoveryAdapter::new(targets),
+            authentication_adapter: FixedAuthenticationAdapter::new(None),
+            localization_adapter: FixedLocalizationAdapter::default(),
+            cookies: Default::default(),
+            tie_breaker,
+            probe: None,
+        }
+    }
+
+    fn create_player() -> Player {
+        Player {
+            name: "Hydrofin".to_string(),
+            id: uuid::uuid!("09879557-e479-45a9-b434-a56377674627"),
+            claims: Default::default(),
+            cookies: Default::default(),
+            session: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn select_first_lowest_priority() {
+        let route = create_route(&[2, 1, 1, 3], TieBreaker::First);
+        let target = route
+            .select(&Client::default(), &create_player())
+            .await
+            .expect("target selected");
+        assert_eq!(target.identifier, "t1");
+    }
+
+    #[tokio::test]
+    async fn select_random_lowest_priority() {
+        let route = create_route(&[2, 1, 1, 3], TieBreaker::Random);
+        for _ in 0..10 {
+            let target = route
+                .select(&Client::default(), &create_player())
+                .await
+                .expect("target selected");
+            assert_eq!(target.priority, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn select_hash_is_sticky() {
+        let route = create_route(&[0, 0, 0, 0], TieBreaker::Hash);
+        let player = create_player();
+        let first = route
+            .select(&Client::default(), &player)
+            .await
+            .expect("target selected");
+        for _ in 0..5 {
+            let target = route
+                .select(&Client::default(), &player)
+                .await
+                .expect("target selected");
+            assert_eq!(target.identifier, first.identifier);
+        }
+    }
+
+    #[tokio::test]
+    async fn select_hash_matches_consistent_hash_strategy() {
+        let route = create_route(&[0, 0, 0, 0], TieBreaker::Hash);
+        let player = create_player();
+        let target = route
+            .select(&Client::default(), &player)
+            .await
+            .expect("target selected");
+
+        let mut targets = Vec::new();
+        route
+            .discovery_adapter
+            .apply(&Client::default(), &player, &mut targets)
+            .await
+            .expect("targets discovered");
+        ConsistentHashStrategyAdapter::new()
+            .apply(&Client::default(), &player, &mut targets)
+            .await
+            .expect("targets sorted");
+        assert_eq!(target.identifier, targets[0].identifier);
+    }
+
+    #[tokio::test]
+    async fn select_fallback_if_unreachable() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind failed");
+        let reachable = listener.local_addr().expect("local address");
+        let unreachable = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+                .await
+                .expect("bind failed");
+            listener.local_addr().expect("local address")
+        };
+
+        let mut route = create_route(&[0, 0], TieBreaker::First);
+        route.discovery_adapter = FixedDiscoveryAdapter::new(
+            [unreachable, reachable]
+                .into_iter()
+                .enumerate()
+                .map(|(i, address)| Target {
+                    identifier: format!("t{i}"),
+                    address,
+                    priority: 0,
+                    meta: Default::default(),
+                    claims: Default::default(),
+                })
+                .collect(),
+        );
+        route.probe = Some(Arc::new(Probe::new(
+            crate::probe::ProbeMethod::Tcp,
+            std::time::Duration::from_secs(1),
+        )));
+
+        let target = route
+            .select(&Client::default(), &create_player())
+            .await
+            .expect("target selected");
+        assert_eq!(target.identifier, "t1");
+    }
+
+    #[tokio::test]
+    async fn select_no_candidates() {
+        let route = create_route(&[], TieBreaker::First);
+        let result = route.select(&Client::default(), &create_player()).await;
+        assert!(matches!(
+            result,
+            Err(passage_adapters::Error::Rejected { reason: Some(reason), .. })
+                if reason == "disconnect_no_target"
+        ));
+    }
+}
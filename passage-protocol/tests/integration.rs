@@ -1,9 +1,10 @@
 use futures::{SinkExt, StreamExt};
 use passage_adapters::authentication::Profile;
+use passage_adapters::authentication::offline::offline_uuid;
 use passage_adapters::discovery::DiscoveryAdapter;
 use passage_adapters::{
-    Client, FixedAuthenticationAdapter, FixedDiscoveryAdapter, FixedLocalizationAdapter,
-    FixedStatusAdapter, Target,
+    Client, DiscoveryActionAdapter, FixedAuthenticationAdapter, FixedDiscoveryAdapter,
+    FixedLocalizationAdapter, FixedStatusAdapter, OfflineAuthenticationAdapter, Player, Target,
 };
 use passage_packets::codec::PacketCodec;
 use passage_packets::configuration::clientbound as conf_out;
@@ -19,9 +20,11 @@ use passage_protocol::Error;
 use passage_protocol::config::Config;
 use passage_protocol::connection::{Connection, KEEP_ALIVE_INTERVAL};
 use passage_protocol::cookie::{
-    AUTH_COOKIE_KEY, AuthCookie, SESSION_COOKIE_KEY, SessionCookie, sign,
+    AUTH_COOKIE_KEY, AuthCookie, CookieFormat, CookieRule, Keyring, SESSION_COOKIE_KEY,
+    SessionCookie, sign,
 };
-use passage_protocol::routes::Route;
+use passage_protocol::nonce_store::{MemoryNonceStore, NonceStore, ReplayPolicy};
+use passage_protocol::routes::{Route, RouteCookies};
 use proxy_header::ParseConfig;
 use proxy_header::io::ProxiedStream;
 use rand::rngs::SysRng;
@@ -29,6 +32,7 @@ use regex::Regex;
 use rsa::pkcs8::DecodePublicKey;
 use rsa::rand_core::UnwrapErr;
 use rsa::{Pkcs1v15Encrypt, RsaPublicKey};
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -78,6 +82,42 @@ impl DiscoveryAdapter for SlowDiscoveryAdapter {
     }
 }
 
+#[derive(Debug)]
+struct ClaimsDiscoveryAdapter;
+
+impl DiscoveryActionAdapter for ClaimsDiscoveryAdapter {
+    async fn apply(
+        &self,
+        _client: &Client,
+        player: &Player,
+        targets: &mut Vec<Target>,
+    ) -> passage_adapters::Result<()> {
+        // echo the incoming party claim and last game cookie and select a new game mode
+        let party = player.claims.get("party").cloned().unwrap_or_default();
+        let last_game = player
+            .cookies
+            .get("lobby:last_game")
+            .map(ToString::to_string);
+        let last_target = player.session.as_ref().and_then(|s| s.last_target());
+        targets.push(Target {
+            identifier: "lobby".to_string(),
+            address: SocketAddr::from_str("127.0.0.1:25565").expect("invalid address"),
+            priority: 0,
+            meta: Default::default(),
+            claims: HashMap::from([
+                ("mode".to_string(), "bedwars".to_string()),
+                ("seen_party".to_string(), party),
+                ("last_game".to_string(), last_game.unwrap_or_default()),
+                (
+                    "last_target".to_string(),
+                    last_target.unwrap_or_default().to_string(),
+                ),
+            ]),
+        });
+        Ok(())
+    }
+}
+
 #[tokio::test(start_paused = true)]
 async fn simulate_handshake() {
     // create stream
@@ -92,6 +132,9 @@ async fn simulate_handshake() {
         discovery_adapter: FixedDiscoveryAdapter::new(vec![]),
         authentication_adapter: FixedAuthenticationAdapter::default(),
         localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: Default::default(),
+        tie_breaker: Default::default(),
+        probe: None,
     })];
 
     // build connection
@@ -145,6 +188,9 @@ async fn simulate_status() {
         discovery_adapter: FixedDiscoveryAdapter::new(vec![]),
         authentication_adapter: FixedAuthenticationAdapter::default(),
         localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: Default::default(),
+        tie_breaker: Default::default(),
+        probe: None,
     })];
 
     // build connection
@@ -221,6 +267,9 @@ async fn simulate_transfer_no_configuration() {
         discovery_adapter: FixedDiscoveryAdapter::new(vec![]),
         authentication_adapter: FixedAuthenticationAdapter::default(),
         localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: Default::default(),
+        tie_breaker: Default::default(),
+        probe: None,
     })];
 
     // build connection
@@ -275,6 +324,10 @@ async fn simulate_transfer_no_configuration() {
                     id: Default::default(),
                     server_address: "".to_string(),
                     server_port: 0,
+                    first_seen: 0,
+                    hops: 0,
+                    targets: vec![],
+                    instance: None,
                     extra: Default::default(),
                 })
                 .expect("session cookie serialization failed"),
@@ -300,6 +353,7 @@ async fn simulate_transfer_no_configuration() {
         user_id,
         target: None,
         profile_properties: vec![],
+        nonce: None,
         extra: Default::default(),
     })
     .expect("auth cookie serialization failed");
@@ -372,6 +426,358 @@ async fn simulate_transfer_no_configuration() {
     server.await.expect("server run failed");
 }
 
+#[tokio::test(start_paused = true)]
+async fn simulate_transfer_replayed_auth_cookie() {
+    let user_name = "Hydrofin".to_owned();
+    let user_id = uuid!("09879557-e479-45a9-b434-a56377674627");
+    let nonce = uuid!("5f1b6c6e-8d2a-4a43-9a59-0c7d4bb3c0e1");
+
+    // create stream
+    let keyring = Keyring::single("secret");
+    let client_address = SocketAddr::from_str("127.0.0.1:25564").expect("invalid address");
+    let (client_stream, server_stream) = tokio::io::duplex(1024);
+    let mut client_stream = Framed::new(client_stream, PacketCodec::new(1_000));
+
+    // build supplier
+    let routes = vec![Arc::new(Route {
+        hostname: Regex::new(".*").expect("valid regex"),
+        status_adapter: FixedStatusAdapter::default(),
+        discovery_adapter: FixedDiscoveryAdapter::new(vec![]),
+        authentication_adapter: FixedAuthenticationAdapter::default(),
+        localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: Default::default(),
+        tie_breaker: Default::default(),
+        probe: None,
+    })];
+
+    // mark the cookie nonce as already used
+    let now_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time error")
+        .as_secs();
+    let store = Arc::new(MemoryNonceStore::new(Duration::from_secs(60)));
+    store.swap(nonce, now_secs, Duration::from_secs(60)).await;
+
+    // build connection
+    let shutdown = CancellationToken::new();
+    let mut server = Connection::new(
+        server_stream,
+        routes.into(),
+        Config::default()
+            .with_auth_keyring(Some(keyring.clone()))
+            .with_replay_policy(ReplayPolicy::SingleUse, Some(store)),
+        client_address,
+        shutdown,
+    );
+    let server = tokio::spawn(async move { server.listen().await });
+
+    // simulate client
+    client_stream
+        .send(hand_in::HandshakePacket {
+            protocol_version: 0,
+            server_address: "".to_string(),
+            server_port: 0,
+            next_state: State::Transfer,
+        })
+        .await
+        .expect("send handshake failed");
+
+    client_stream
+        .send(login_in::LoginStartPacket {
+            user_name: user_name.clone(),
+            user_id,
+        })
+        .await
+        .expect("send login start failed");
+
+    let cookie_request_packet: login_out::CookieRequestPacket = client_stream
+        .next_packet()
+        .await
+        .expect("session cookie request packet read failed");
+    assert_eq!(&cookie_request_packet.key, SESSION_COOKIE_KEY);
+
+    client_stream
+        .send(login_in::CookieResponsePacket {
+            key: cookie_request_packet.key,
+            payload: None,
+        })
+        .await
+        .expect("send session cookie response failed");
+
+    let cookie_request_packet: login_out::CookieRequestPacket = client_stream
+        .next_packet()
+        .await
+        .expect("cookie request packet read failed");
+    assert_eq!(&cookie_request_packet.key, AUTH_COOKIE_KEY);
+
+    let auth_payload = serde_json::to_vec(&AuthCookie {
+        timestamp: now_secs,
+        client_addr: client_address,
+        user_name: user_name.clone(),
+        user_id,
+        target: None,
+        profile_properties: vec![],
+        nonce: Some(nonce),
+        extra: Default::default(),
+    })
+    .expect("auth cookie serialization failed");
+
+    client_stream
+        .send(login_in::CookieResponsePacket {
+            key: cookie_request_packet.key,
+            payload: Some(keyring.sign(&auth_payload)),
+        })
+        .await
+        .expect("send cookie response failed");
+
+    // the replayed cookie is rejected, so the client has to authenticate
+    let encryption_request_packet: login_out::EncryptionRequestPacket = client_stream
+        .next_packet()
+        .await
+        .expect("encryption request packet read failed");
+    assert!(encryption_request_packet.should_authenticate);
+
+    drop(client_stream);
+    let _ = server.await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn simulate_transfer_with_claims_cookies_and_session() {
+    let shared_secret = b"verysecuresecret";
+    let user_name = "Hydrofin".to_owned();
+    let user_id = uuid!("09879557-e479-45a9-b434-a56377674627");
+
+    // create stream
+    let keyring = Keyring::single("secret");
+    let client_address = SocketAddr::from_str("127.0.0.1:25564").expect("invalid address");
+    let (client_stream, server_stream) = tokio::io::duplex(4096);
+    let mut client_stream = Framed::new(client_stream, PacketCodec::new(4_000));
+
+    // build supplier
+    let routes = vec![Arc::new(Route {
+        hostname: Regex::new(".*").expect("valid regex"),
+        status_adapter: FixedStatusAdapter::default(),
+        discovery_adapter: ClaimsDiscoveryAdapter,
+        authentication_adapter: FixedAuthenticationAdapter::default(),
+        localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: RouteCookies {
+            request: vec![CookieRule::new("lobby:last_game", CookieFormat::Json)],
+            clear: vec!["lobby:last_game".to_string()],
+        },
+        tie_breaker: Default::default(),
+        probe: None,
+    })];
+
+    // build connection
+    let shutdown = CancellationToken::new();
+    let mut server = Connection::new(
+        server_stream,
+        routes.into(),
+        Config::default().with_auth_keyring(Some(keyring.clone())),
+        client_address,
+        shutdown,
+    );
+    let server = tokio::spawn(async move { server.listen().await });
+
+    // simulate client
+    client_stream
+        .send(hand_in::HandshakePacket {
+            protocol_version: 0,
+            server_address: "".to_string(),
+            server_port: 0,
+            next_state: State::Transfer,
+        })
+        .await
+        .expect("send handshake failed");
+
+    client_stream
+        .send(login_in::LoginStartPacket {
+            user_name: user_name.clone(),
+            user_id,
+        })
+        .await
+        .expect("send login start failed");
+
+    let cookie_request_packet: login_out::CookieRequestPacket = client_stream
+        .next_packet()
+        .await
+        .expect("session cookie request packet read failed");
+    assert_eq!(&cookie_request_packet.key, SESSION_COOKIE_KEY);
+
+    let session_id = uuid!("0f4c1d8e-2b7a-4c53-9e61-7d2f8a3b5c90");
+    client_stream
+        .send(login_in::CookieResponsePacket {
+            key: cookie_request_packet.key,
+            payload: Some(
+                serde_json::to_vec(&SessionCookie {
+                    id: session_id,
+                    server_address: "mc.example.net".to_string(),
+                    server_port: 25565,
+                    first_seen: 42,
+                    hops: 5,
+                    targets: (1..=5).map(|i| format!("survival-{i}")).collect(),
+                    instance: Some("passage-0".to_string()),
+                    extra: Default::default(),
+                })
+                .expect("session cookie serialization failed"),
+            ),
+        })
+        .await
+        .expect("send session cookie response failed");
+
+    let cookie_request_packet: login_out::CookieRequestPacket = client_stream
+        .next_packet()
+        .await
+        .expect("cookie request packet read failed");
+    assert_eq!(&cookie_request_packet.key, AUTH_COOKIE_KEY);
+
+    let now_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time error")
+        .as_secs();
+    let auth_payload = serde_json::to_vec(&AuthCookie {
+        timestamp: now_secs,
+        client_addr: client_address,
+        user_name: user_name.clone(),
+        user_id,
+        target: None,
+        profile_properties: vec![],
+        nonce: None,
+        extra: HashMap::from([
+            ("party".to_string(), "3f2a".to_string()),
+            ("mode".to_string(), "skywars".to_string()),
+        ]),
+    })
+    .expect("auth cookie serialization failed");
+
+    client_stream
+        .send(login_in::CookieResponsePacket {
+            key: cookie_request_packet.key,
+            payload: Some(keyring.sign(&auth_payload)),
+        })
+        .await
+        .expect("send cookie response failed");
+
+    let cookie_request_packet: login_out::CookieRequestPacket = client_stream
+        .next_packet()
+        .await
+        .expect("cookie request packet read failed");
+    assert_eq!(&cookie_request_packet.key, "lobby:last_game");
+
+    client_stream
+        .send(login_in::CookieResponsePacket {
+            key: cookie_request_packet.key,
+            payload: Some(br#"{"game":"bedwars-3"}"#.to_vec()),
+        })
+        .await
+        .expect("send cookie response failed");
+
+    let encryption_request_packet: login_out::EncryptionRequestPacket = client_stream
+        .next_packet()
+        .await
+        .expect("encryption request packet read failed");
+    assert!(!encryption_request_packet.should_authenticate);
+
+    let pub_key = RsaPublicKey::from_public_key_der(&encryption_request_packet.public_key)
+        .expect("public key deserialization failed");
+    client_stream
+        .send(login_in::EncryptionResponsePacket {
+            shared_secret: encrypt(&pub_key, shared_secret),
+            verify_token: encrypt(&pub_key, &encryption_request_packet.verify_token),
+        })
+        .await
+        .expect("send encryption response failed");
+
+    client_stream
+        .codec_mut()
+        .encrypt(shared_secret)
+        .expect("create ciphers failed");
+
+    let _: login_out::LoginSuccessPacket = client_stream
+        .next_packet()
+        .await
+        .expect("login success packet read failed");
+
+    client_stream
+        .send(login_in::LoginAcknowledgedPacket)
+        .await
+        .expect("send login acknowledged packet failed");
+
+    client_stream
+        .send(conf_in::ClientInformationPacket {
+            locale: "de_DE".to_string(),
+            view_distance: 10,
+            chat_mode: ChatMode::Enabled,
+            chat_colors: false,
+            displayed_skin_parts: DisplayedSkinParts(0),
+            main_hand: MainHand::Left,
+            enable_text_filtering: false,
+            allow_server_listing: false,
+            particle_status: ParticleStatus::All,
+        })
+        .await
+        .expect("send client information packet failed");
+
+    // the requested cookie is cleared before the transfer
+    let store_cookie_packet: conf_out::StoreCookiePacket = client_stream
+        .next_packet()
+        .await
+        .expect("clear cookie packet read failed");
+    assert_eq!(&store_cookie_packet.key, "lobby:last_game");
+    assert!(store_cookie_packet.payload.is_empty());
+
+    // the incoming claims are kept, while the target claims take precedence
+    let store_cookie_packet: conf_out::StoreCookiePacket = client_stream
+        .next_packet()
+        .await
+        .expect("store auth cookie packet read failed");
+    assert_eq!(&store_cookie_packet.key, AUTH_COOKIE_KEY);
+    let (_, payload) = keyring
+        .verify(&store_cookie_packet.payload)
+        .expect("auth cookie verification failed");
+    let cookie: AuthCookie =
+        serde_json::from_slice(&payload).expect("auth cookie deserialization failed");
+    assert_eq!(
+        cookie.extra,
+        HashMap::from([
+            ("party".to_string(), "3f2a".to_string()),
+            ("mode".to_string(), "bedwars".to_string()),
+            ("seen_party".to_string(), "3f2a".to_string()),
+            (
+                "last_game".to_string(),
+                r#"{"game":"bedwars-3"}"#.to_string()
+            ),
+            ("last_target".to_string(), "survival-5".to_string()),
+        ])
+    );
+
+    // the session keeps its initial fields, but records the pass and the target
+    let store_cookie_packet: conf_out::StoreCookiePacket = client_stream
+        .next_packet()
+        .await
+        .expect("store session cookie packet read failed");
+    assert_eq!(&store_cookie_packet.key, SESSION_COOKIE_KEY);
+    let session: SessionCookie = serde_json::from_slice(&store_cookie_packet.payload)
+        .expect("session cookie deserialization failed");
+    assert_eq!(session.id, session_id);
+    assert_eq!(session.first_seen, 42);
+    assert_eq!(session.hops, 6);
+    assert_eq!(
+        session.targets,
+        vec![
+            "survival-2",
+            "survival-3",
+            "survival-4",
+            "survival-5",
+            "lobby"
+        ]
+    );
+    assert_eq!(session.instance.as_deref(), Some("passage-0"));
+
+    drop(client_stream);
+    let _ = server.await;
+}
+
 #[tokio::test(start_paused = true)]
 async fn simulate_slow_transfer_no_configuration() {
     let shared_secret = b"verysecuresecret";
@@ -391,6 +797,9 @@ async fn simulate_slow_transfer_no_configuration() {
         discovery_adapter: SlowDiscoveryAdapter::new(2 * KEEP_ALIVE_INTERVAL + 1),
         authentication_adapter: FixedAuthenticationAdapter::default(),
         localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: Default::default(),
+        tie_breaker: Default::default(),
+        probe: None,
     })];
 
     // build connection
@@ -445,6 +854,10 @@ async fn simulate_slow_transfer_no_configuration() {
                     id: Default::default(),
                     server_address: "".to_string(),
                     server_port: 0,
+                    first_seen: 0,
+                    hops: 0,
+                    targets: vec![],
+                    instance: None,
                     extra: Default::default(),
                 })
                 .expect("session cookie serialization failed"),
@@ -470,6 +883,7 @@ async fn simulate_slow_transfer_no_configuration() {
         user_id,
         target: None,
         profile_properties: vec![],
+        nonce: None,
         extra: Default::default(),
     })
     .expect("auth cookie serialization failed");
@@ -569,6 +983,7 @@ async fn simulate_login_no_configuration() {
         name: user_name.clone(),
         properties: vec![],
         profile_actions: vec![],
+        claims: Default::default(),
     };
 
     // create stream
@@ -583,6 +998,9 @@ async fn simulate_login_no_configuration() {
         discovery_adapter: FixedDiscoveryAdapter::new(vec![]),
         authentication_adapter: FixedAuthenticationAdapter::new(Some(profile)),
         localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: Default::default(),
+        tie_breaker: Default::default(),
+        probe: None,
     })];
 
     // build connection
@@ -637,6 +1055,10 @@ async fn simulate_login_no_configuration() {
                     id: Default::default(),
                     server_address: "".to_string(),
                     server_port: 0,
+                    first_seen: 0,
+                    hops: 0,
+                    targets: vec![],
+                    instance: None,
                     extra: Default::default(),
                 })
                 .expect("session cookie serialization failed"),
@@ -705,6 +1127,199 @@ async fn simulate_login_no_configuration() {
     server.await.expect("server run failed");
 }
 
+#[tokio::test(start_paused = true)]
+async fn simulate_login_invalid_verify_token() {
+    let shared_secret = b"verysecuresecret";
+    let user_name = "Hydrofin".to_owned();
+    let user_id = uuid!("09879557-e479-45a9-b434-a56377674627");
+
+    // create stream
+    let client_address = SocketAddr::from_str("127.0.0.1:25564").expect("invalid address");
+    let (client_stream, server_stream) = tokio::io::duplex(1024);
+    let mut client_stream = Framed::new(client_stream, PacketCodec::new(1_000));
+
+    // build supplier
+    let routes = vec![Arc::new(Route {
+        hostname: Regex::new(".*").expect("valid regex"),
+        status_adapter: FixedStatusAdapter::default(),
+        discovery_adapter: FixedDiscoveryAdapter::new(vec![]),
+        authentication_adapter: FixedAuthenticationAdapter::new(None),
+        localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: Default::default(),
+        tie_breaker: Default::default(),
+        probe: None,
+    })];
+
+    // build connection
+    let shutdown = CancellationToken::new();
+    let mut server = Connection::new(
+        server_stream,
+        routes.into(),
+        Config::default(),
+        client_address,
+        shutdown,
+    );
+
+    // start the server in its own thread
+    let server = tokio::spawn(async move {
+        let result = server.listen().await;
+        match result {
+            Err(Error::ConnectionClosed) => {}
+            other => panic!("expected connection closed, got {:?}", other),
+        }
+    });
+
+    // simulate client
+    client_stream
+        .send(hand_in::HandshakePacket {
+            protocol_version: 0,
+            server_address: "".to_string(),
+            server_port: 0,
+            next_state: State::Login,
+        })
+        .await
+        .expect("send handshake failed");
+
+    client_stream
+        .send(login_in::LoginStartPacket { user_name, user_id })
+        .await
+        .expect("send login start failed");
+
+    let cookie_request_packet: login_out::CookieRequestPacket = client_stream
+        .next_packet()
+        .await
+        .expect("session cookie request packet read failed");
+    client_stream
+        .send(login_in::CookieResponsePacket {
+            key: cookie_request_packet.key,
+            payload: None,
+        })
+        .await
+        .expect("send session cookie response failed");
+
+    let encryption_request_packet: login_out::EncryptionRequestPacket = client_stream
+        .next_packet()
+        .await
+        .expect("encryption request packet read failed");
+
+    // answer with a verify token that does not match
+    let pub_key = RsaPublicKey::from_public_key_der(&encryption_request_packet.public_key)
+        .expect("public key deserialization failed");
+    client_stream
+        .send(login_in::EncryptionResponsePacket {
+            shared_secret: encrypt(&pub_key, shared_secret),
+            verify_token: encrypt(&pub_key, b"invalid"),
+        })
+        .await
+        .expect("send encryption response failed");
+
+    client_stream
+        .codec_mut()
+        .encrypt(shared_secret)
+        .expect("create ciphers failed");
+
+    // the disconnect is readable through the encrypted connection
+    let disconnect_packet: login_out::DisconnectPacket = client_stream
+        .next_packet()
+        .await
+        .expect("disconnect packet read failed");
+    assert_eq!(
+        disconnect_packet.reason,
+        "disconnect_encryption_verify_token_mismatch"
+    );
+
+    // wait for the server to finish
+    server.await.expect("server run failed");
+}
+
+#[tokio::test(start_paused = true)]
+async fn simulate_offline_login_without_encryption() {
+    let user_name = "Hydrofin".to_owned();
+
+    // create stream
+    let client_address = SocketAddr::from_str("127.0.0.1:25564").expect("invalid address");
+    let (client_stream, server_stream) = tokio::io::duplex(1024);
+    let mut client_stream = Framed::new(client_stream, PacketCodec::new(1_000));
+
+    // build supplier
+    let routes = vec![Arc::new(Route {
+        hostname: Regex::new(".*").expect("valid regex"),
+        status_adapter: FixedStatusAdapter::default(),
+        discovery_adapter: FixedDiscoveryAdapter::new(vec![]),
+        authentication_adapter: OfflineAuthenticationAdapter::new(false),
+        localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: Default::default(),
+        tie_breaker: Default::default(),
+        probe: None,
+    })];
+
+    // build connection
+    let shutdown = CancellationToken::new();
+    let mut server = Connection::new(
+        server_stream,
+        routes.into(),
+        Config::default(),
+        client_address,
+        shutdown,
+    );
+
+    // start the server in its own thread
+    let server = tokio::spawn(async move {
+        let result = server.listen().await;
+        match result {
+            Err(Error::ConnectionClosed) => {}
+            other => panic!("expected connection closed, got {:?}", other),
+        }
+    });
+
+    // simulate client
+    client_stream
+        .send(hand_in::HandshakePacket {
+            protocol_version: 0,
+            server_address: "".to_string(),
+            server_port: 0,
+            next_state: State::Login,
+        })
+        .await
+        .expect("send handshake failed");
+
+    client_stream
+        .send(login_in::LoginStartPacket {
+            user_name: user_name.clone(),
+            user_id: Default::default(),
+        })
+        .await
+        .expect("send login start failed");
+
+    let cookie_request_packet: login_out::CookieRequestPacket = client_stream
+        .next_packet()
+        .await
+        .expect("session cookie request packet read failed");
+    assert_eq!(&cookie_request_packet.key, SESSION_COOKIE_KEY);
+
+    client_stream
+        .send(login_in::CookieResponsePacket {
+            key: cookie_request_packet.key,
+            payload: None,
+        })
+        .await
+        .expect("send session cookie response failed");
+
+    // no encryption request is sent, the login succeeds with the offline uuid
+    let login_success_packet: login_out::LoginSuccessPacket = client_stream
+        .next_packet()
+        .await
+        .expect("login success packet read failed");
+    assert_eq!(login_success_packet.user_name, user_name);
+    assert_eq!(login_success_packet.user_id, offline_uuid(&user_name));
+
+    // simulate connection closed after the login
+    drop(client_stream);
+
+    // wait for the server to finish
+    server.await.expect("server run failed");
+}
+
 #[tokio::test(start_paused = true)]
 async fn sends_keep_alive() {
     let shared_secret = b"verysecuresecret";
@@ -724,6 +1339,9 @@ async fn sends_keep_alive() {
         discovery_adapter: FixedDiscoveryAdapter::new(vec![]),
         authentication_adapter: FixedAuthenticationAdapter::default(),
         localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: Default::default(),
+        tie_breaker: Default::default(),
+        probe: None,
     })];
 
     // build connection
@@ -778,6 +1396,10 @@ async fn sends_keep_alive() {
                     id: Default::default(),
                     server_address: "".to_string(),
                     server_port: 0,
+                    first_seen: 0,
+                    hops: 0,
+                    targets: vec![],
+                    instance: None,
                     extra: Default::default(),
                 })
                 .expect("session cookie serialization failed"),
@@ -803,6 +1425,7 @@ async fn sends_keep_alive() {
         user_id,
         target: None,
         profile_properties: vec![],
+        nonce: None,
         extra: Default::default(),
     })
     .expect("auth cookie serialization failed");
@@ -900,6 +1523,9 @@ async fn no_respond_keep_alive() {
         discovery_adapter: FixedDiscoveryAdapter::new(vec![]),
         authentication_adapter: FixedAuthenticationAdapter::default(),
         localization_adapter: FixedLocalizationAdapter::default(),
+        cookies: Default::default(),
+        tie_breaker: Default::default(),
+        probe: None,
     })];
 
     // build connection
@@ -954,6 +1580,10 @@ async fn no_respond_keep_alive() {
                     id: Default::default(),
                     server_address: "".to_string(),
                     server_port: 0,
+                    first_seen: 0,
+                    hops: 0,
+                    targets: vec![],
+                    instance: None,
                     extra: Default::default(),
                 })
                 .expect("session cookie serialization failed"),
@@ -979,6 +1609,7 @@ async fn no_respond_keep_alive() {
         user_id,
         target: None,
         profile_properties: vec![],
+        nonce: None,
         extra: Default::default(),
     })
     .expect("auth cookie serialization failed");
@@ -1,10 +1,17 @@
+use crate::adapter::opt_vec_to_uuid;
 use crate::config;
+use passage_adapters::authentication::chain::ChainLink;
 use passage_adapters::authentication::fixed::FixedAuthenticationAdapter;
-use passage_adapters::authentication::{AuthenticationAdapter, Profile};
-use passage_adapters::{Client, DisabledAuthenticationAdapter, Player};
+use passage_adapters::authentication::{AuthenticationAdapter, AuthenticationMode, Profile};
+use passage_adapters::{
+    BanAuthenticationAdapter, ChainAuthenticationAdapter, Client, DisabledAuthenticationAdapter,
+    MaintenanceAuthenticationAdapter, OfflineAuthenticationAdapter, Player,
+};
 use passage_adapters_grpc::authentication_adapter::GrpcAuthenticationAdapter;
-use passage_adapters_http::MojangAdapter;
+use passage_adapters_http::{MojangAdapter, ProfileCache};
 use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Runtime-selected authentication adapter.
 ///
@@ -12,10 +19,18 @@ use std::fmt::{Display, Formatter};
 /// enum so they can be stored uniformly in a [`Route`](passage_protocol::routes::Route).
 #[derive(Debug)]
 pub enum DynAuthenticationAdapter {
+    /// Rejects banned players before delegating to another authentication adapter.
+    Ban(BanAuthenticationAdapter<Box<DynAuthenticationAdapter>>),
+    /// Tries multiple authentication adapters in order.
+    Chain(ChainAuthenticationAdapter<Box<DynAuthenticationAdapter>>),
+    /// Rejects all players except an allow-list while the route is in maintenance.
+    Maintenance(MaintenanceAuthenticationAdapter<Box<DynAuthenticationAdapter>>),
     /// Accepts all players without verification.
     Disabled(DisabledAuthenticationAdapter),
     /// Returns a fixed, pre-configured profile.
     Fixed(FixedAuthenticationAdapter),
+    /// Uses offline-mode semantics with deterministic offline UUIDs.
+    Offline(OfflineAuthenticationAdapter),
     /// Delegates to an external gRPC authentication service.
     #[cfg(feature = "adapters-grpc")]
     Grpc(GrpcAuthenticationAdapter),
@@ -27,8 +42,12 @@ pub enum DynAuthenticationAdapter {
 impl Display for DynAuthenticationAdapter {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::Ban(_) => write!(f, "ban"),
+            Self::Chain(_) => write!(f, "chain"),
+            Self::Maintenance(_) => write!(f, "maintenance"),
             Self::Disabled(_) => write!(f, "disabled"),
             Self::Fixed(_) => write!(f, "fixed"),
+            Self::Offline(_) => write!(f, "offline"),
             #[cfg(feature = "adapters-grpc")]
             Self::Grpc(_) => write!(f, "grpc"),
             #[cfg(feature = "adapters-http")]
@@ -46,6 +65,16 @@ impl AuthenticationAdapter for DynAuthenticationAdapter {
         encoded_public: &[u8],
     ) -> passage_adapters::Result<Profile> {
         match self {
+            // decorators are boxed as they may recursively contain this adapter
+            DynAuthenticationAdapter::Ban(adapter) => {
+                Box::pin(adapter.authenticate(client, player, shared_secret, encoded_public)).await
+            }
+            DynAuthenticationAdapter::Chain(adapter) => {
+                Box::pin(adapter.authenticate(client, player, shared_secret, encoded_public)).await
+            }
+            DynAuthenticationAdapter::Maintenance(adapter) => {
+                Box::pin(adapter.authenticate(client, player, shared_secret, encoded_public)).await
+            }
             DynAuthenticationAdapter::Disabled(adapter) => {
                 adapter
                     .authenticate(client, player, shared_secret, encoded_public)
@@ -56,6 +85,11 @@ impl AuthenticationAdapter for DynAuthenticationAdapter {
                     .authenticate(client, player, shared_secret, encoded_public)
                     .await
             }
+            DynAuthenticationAdapter::Offline(adapter) => {
+                adapter
+                    .authenticate(client, player, shared_secret, encoded_public)
+                    .await
+            }
             #[cfg(feature = "adapters-grpc")]
             DynAuthenticationAdapter::Grpc(adapter) => {
                 adapter
@@ -70,6 +104,42 @@ impl AuthenticationAdapter for DynAuthenticationAdapter {
             }
         }
     }
+
+    fn mode(&self) -> AuthenticationMode {
+        match self {
+            DynAuthenticationAdapter::Ban(adapter) => adapter.mode(),
+            DynAuthenticationAdapter::Chain(adapter) => adapter.mode(),
+            DynAuthenticationAdapter::Maintenance(adapter) => adapter.mode(),
+            DynAuthenticationAdapter::Disabled(adapter) => adapter.mode(),
+            DynAuthenticationAdapter::Fixed(adapter) => adapter.mode(),
+            DynAuthenticationAdapter::Offline(adapter) => adapter.mode(),
+            #[cfg(feature = "adapters-grpc")]
+            DynAuthenticationAdapter::Grpc(adapter) => adapter.mode(),
+            #[cfg(feature = "adapters-http")]
+            DynAuthenticationAdapter::Mojang(adapter) => adapter.mode(),
+        }
+    }
+
+    async fn authorize(&self, client: &Client, player: &Player) -> passage_adapters::Result<()> {
+        match self {
+            DynAuthenticationAdapter::Ban(adapter) => {
+                Box::pin(adapter.authorize(client, player)).await
+            }
+            DynAuthenticationAdapter::Chain(adapter) => {
+                Box::pin(adapter.authorize(client, player)).await
+            }
+            DynAuthenticationAdapter::Maintenance(adapter) => {
+                Box::pin(adapter.authorize(client, player)).await
+            }
+            DynAuthenticationAdapter::Disabled(adapter) => adapter.authorize(client, player).await,
+            DynAuthenticationAdapter::Fixed(adapter) => adapter.authorize(client, player).await,
+            DynAuthenticationAdapter::Offline(adapter) => adapter.authorize(client, player).await,
+            #[cfg(feature = "adapters-grpc")]
+            DynAuthenticationAdapter::Grpc(adapter) => adapter.authorize(client, player).await,
+            #[cfg(feature = "adapters-http")]
+            DynAuthenticationAdapter::Mojang(adapter) => adapter.authorize(client, player).await,
+        }
+    }
 }
 
 impl DynAuthenticationAdapter {
@@ -79,6 +149,30 @@ impl DynAuthenticationAdapter {
     ) -> Result<Self, Box<dyn std::error::Error>> {
         #[allow(unreachable_patterns)]
         match config {
+            config::AuthenticationAdapter::Ban(config) => {
+                let inner = Box::pin(Self::from_config(*config.adapter)).await?;
+                let adapter = BanAuthenticationAdapter::new(
+                    Box::new(inner),
+                    config.bans,
+                    config.file.map(PathBuf::from),
+                );
+                Ok(DynAuthenticationAdapter::Ban(adapter))
+            }
+            config::AuthenticationAdapter::Chain(config) => {
+                let mut links = Vec::with_capacity(config.links.len());
+                for (index, link) in config.links.into_iter().enumerate() {
+                    let adapter = Box::pin(Self::from_config(link.adapter)).await?;
+                    let name = link.name.unwrap_or_else(|| index.to_string());
+                    links.push(
+                        ChainLink::new(name, Box::new(adapter))
+                            .with_fallback_on_rejected(link.fallback_on_rejected)
+                            .with_fallback_on_failure(link.fallback_on_failure)
+                            .with_timeout(link.timeout.map(Duration::from_secs)),
+                    );
+                }
+                let adapter = ChainAuthenticationAdapter::new(links);
+                Ok(DynAuthenticationAdapter::Chain(adapter))
+            }
             config::AuthenticationAdapter::Disabled => {
                 let adapter = DisabledAuthenticationAdapter::new();
                 Ok(DynAuthenticationAdapter::Disabled(adapter))
@@ -87,6 +181,10 @@ impl DynAuthenticationAdapter {
                 let adapter = FixedAuthenticationAdapter::new(config.profile);
                 Ok(DynAuthenticationAdapter::Fixed(adapter))
             }
+            config::AuthenticationAdapter::Offline(config) => {
+                let adapter = OfflineAuthenticationAdapter::new(config.encryption);
+                Ok(DynAuthenticationAdapter::Offline(adapter))
+            }
             #[cfg(feature = "adapters-grpc")]
             config::AuthenticationAdapter::Grpc(config) => {
                 let adapter = GrpcAuthenticationAdapter::new(config.address).await?;
@@ -94,10 +192,34 @@ impl DynAuthenticationAdapter {
             }
             #[cfg(feature = "adapters-http")]
             config::AuthenticationAdapter::Mojang(config) => {
-                let adapter = MojangAdapter::default().with_server_id(config.server_id);
+                let adapter = MojangAdapter::default()
+                    .with_server_id(config.server_id)
+                    .with_base_url(config.base_url)
+                    .with_prevent_proxy_connections(config.prevent_proxy_connections)
+                    .with_timeout(config.timeout.map(Duration::from_secs))
+                    .with_backoff(config.backoff)
+                    .with_cache(config.cache.map(|cache| {
+                        ProfileCache::new(Duration::from_secs(cache.ttl))
+                            .with_max_staleness(cache.max_staleness.map(Duration::from_secs))
+                            .with_failure_limit(
+                                cache.failure_limit,
+                                Duration::from_secs(cache.failure_window),
+                            )
+                    }));
                 Ok(DynAuthenticationAdapter::Mojang(adapter))
             }
             _ => Err("unknown authentication adapter configured".into()),
         }
     }
+
+    /// Wraps the adapter such that only the allowed players of the maintenance configuration are
+    /// accepted.
+    pub fn with_maintenance(
+        self,
+        config: config::Maintenance,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let ids = opt_vec_to_uuid(Some(config.ids))?.unwrap_or_default();
+        let adapter = MaintenanceAuthenticationAdapter::new(Box::new(self), config.usernames, ids);
+        Ok(DynAuthenticationAdapter::Maintenance(adapter))
+    }
 }
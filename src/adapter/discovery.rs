@@ -1,10 +1,16 @@
 use crate::adapter::{opt_to_regex, opt_vec_to_uuid};
 use crate::config;
 use crate::config::DnsDiscoveryRecordType;
-use passage_adapters::discovery_action::meta_filter::{FilterOperation, FilterRule};
+use passage_adapters::discovery_action::meta_filter::{
+    FieldRef, Filter, FilterOperation, FilterRule, FilterValue,
+};
+use passage_adapters::geoip::GeoIpDatabase;
 use passage_adapters::{
-    Client, DiscoveryActionAdapter, FixedDiscoveryAdapter, MetaFilterAdapter, Player,
-    PlayerAllowFilterAdapter, PlayerBlockFilterAdapter, PlayerFillStrategyAdapter, Target,
+    Client, ConsistentHashStrategyAdapter, DiscoveryActionAdapter, FixedDiscoveryAdapter,
+    HealthCheckAdapter, LeastPlayersStrategyAdapter, MetaFilterAdapter, Player,
+    PlayerAllowFilterAdapter, PlayerBlockFilterAdapter, PlayerFillStrategyAdapter,
+    PowerOfTwoStrategyAdapter, RegionStrategyAdapter, RoundRobinStrategyAdapter, Target,
+    WeightedRandomStrategyAdapter,
 };
 #[cfg(feature = "adapters-agones")]
 use passage_adapters_agones::AgonesDiscoveryAdapter;
@@ -15,7 +21,11 @@ use passage_adapters_dns::{DnsDiscoveryAdapter, RecordType};
 use passage_adapters_grpc::GrpcDiscoveryActionAdapter;
 #[cfg(feature = "adapters-grpc")]
 use passage_adapters_grpc::GrpcDiscoveryAdapter;
+use regex::Regex;
+use semver::VersionReq;
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Runtime-selected discovery action adapter.
 ///
@@ -45,6 +55,20 @@ pub enum DynDiscoveryActionAdapter {
     PlayerBlockFilter(PlayerBlockFilterAdapter),
     /// Re-orders targets to maximise server utilisation.
     PlayerFillStrategy(PlayerFillStrategyAdapter),
+    /// Re-orders targets randomly, proportional to their weight.
+    WeightedRandomStrategy(WeightedRandomStrategyAdapter),
+    /// Rotates through the targets on each request.
+    RoundRobinStrategy(RoundRobinStrategyAdapter),
+    /// Re-orders targets to spread players evenly.
+    LeastPlayersStrategy(LeastPlayersStrategyAdapter),
+    /// Re-orders targets to keep players on the same target.
+    ConsistentHashStrategy(ConsistentHashStrategyAdapter),
+    /// Prefers the less loaded of two random targets.
+    PowerOfTwoStrategy(PowerOfTwoStrategyAdapter),
+    /// Removes targets that fail periodic status checks.
+    HealthCheck(HealthCheckAdapter),
+    /// Re-orders targets to prefer the regions nearest to the client.
+    RegionStrategy(RegionStrategyAdapter),
 }
 
 impl Display for DynDiscoveryActionAdapter {
@@ -64,6 +88,13 @@ impl Display for DynDiscoveryActionAdapter {
             PlayerAllowFilter(_) => write!(f, "player-allow-filter"),
             PlayerBlockFilter(_) => write!(f, "player-block-filter"),
             PlayerFillStrategy(_) => write!(f, "player-fill-strategy"),
+            WeightedRandomStrategy(_) => write!(f, "weighted-random-strategy"),
+            RoundRobinStrategy(_) => write!(f, "round-robin-strategy"),
+            LeastPlayersStrategy(_) => write!(f, "least-players-strategy"),
+            ConsistentHashStrategy(_) => write!(f, "consistent-hash-strategy"),
+            PowerOfTwoStrategy(_) => write!(f, "power-of-two-strategy"),
+            HealthCheck(_) => write!(f, "health-check"),
+            RegionStrategy(_) => write!(f, "region-strategy"),
         }
     }
 }
@@ -90,6 +121,13 @@ impl DiscoveryActionAdapter for DynDiscoveryActionAdapter {
             PlayerAllowFilter(adapter) => adapter.apply(client, player, targets).await,
             PlayerBlockFilter(adapter) => adapter.apply(client, player, targets).await,
             PlayerFillStrategy(adapter) => adapter.apply(client, player, targets).await,
+            WeightedRandomStrategy(adapter) => adapter.apply(client, player, targets).await,
+            RoundRobinStrategy(adapter) => adapter.apply(client, player, targets).await,
+            LeastPlayersStrategy(adapter) => adapter.apply(client, player, targets).await,
+            ConsistentHashStrategy(adapter) => adapter.apply(client, player, targets).await,
+            PowerOfTwoStrategy(adapter) => adapter.apply(client, player, targets).await,
+            HealthCheck(adapter) => adapter.apply(client, player, targets).await,
+            RegionStrategy(adapter) => adapter.apply(client, player, targets).await,
         }
     }
 }
@@ -99,20 +137,23 @@ impl DynDiscoveryActionAdapter {
     ///
     /// The first element is the discovery source; subsequent elements are action adapters applied
     /// in order. Returns all adapters as a `Vec` suitable for use with the blanket
-    /// `DiscoveryActionAdapter for Vec<T>` implementation.
+    /// `DiscoveryActionAdapter for Vec<T>` implementation. Actions that locate the client use the
+    /// shared `geoip` database.
     pub async fn from_config(
         config: config::DiscoveryAdapter,
+        geoip: Option<&Arc<GeoIpDatabase>>,
     ) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
         let mut adapters = Vec::with_capacity(config.actions.len() + 1);
-        adapters.push(Self::action_from_config(config.adapter).await?);
+        adapters.push(Self::action_from_config(config.adapter, geoip).await?);
         for action in config.actions {
-            adapters.push(Self::action_from_config(action).await?);
+            adapters.push(Self::action_from_config(action, geoip).await?);
         }
         Ok(adapters)
     }
 
     async fn action_from_config(
         config: config::DiscoveryActionAdapter,
+        geoip: Option<&Arc<GeoIpDatabase>>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         use DynDiscoveryActionAdapter::*;
         use config::DiscoveryActionAdapter as conf;
@@ -157,8 +198,14 @@ impl DynDiscoveryActionAdapter {
                 Ok(Grpc(adapter))
             }
             conf::MetaFilter(config) => {
-                let adapter =
-                    MetaFilterAdapter::new(config.rules.into_iter().map(Into::into).collect());
+                let adapter = MetaFilterAdapter::new(
+                    config
+                        .rules
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                )
+                .with_geoip(geoip.cloned());
                 Ok(MetaFilter(adapter))
             }
             conf::PlayerAllowFilter(config) => {
@@ -181,29 +228,135 @@ impl DynDiscoveryActionAdapter {
                 let adapter = PlayerFillStrategyAdapter::new(config.field, config.max_players);
                 Ok(PlayerFillStrategy(adapter))
             }
+            conf::WeightedRandomStrategy(config) => {
+                let adapter =
+                    WeightedRandomStrategyAdapter::new(config.field, config.default_weight);
+                Ok(WeightedRandomStrategy(adapter))
+            }
+            conf::RoundRobinStrategy(_) => Ok(RoundRobinStrategy(RoundRobinStrategyAdapter::new())),
+            conf::LeastPlayersStrategy(config) => {
+                let adapter = LeastPlayersStrategyAdapter::new(config.field);
+                Ok(LeastPlayersStrategy(adapter))
+            }
+            conf::ConsistentHashStrategy(_) => {
+                Ok(ConsistentHashStrategy(ConsistentHashStrategyAdapter::new()))
+            }
+            conf::PowerOfTwoStrategy(config) => {
+                let adapter = PowerOfTwoStrategyAdapter::new(config.field);
+                Ok(PowerOfTwoStrategy(adapter))
+            }
+            conf::HealthCheck(config) => {
+                if config.interval == 0 {
+                    return Err("health check interval must be greater than zero".into());
+                }
+                let adapter = HealthCheckAdapter::new(
+                    Duration::from_secs(config.interval),
+                    Duration::try_from_secs_f64(config.timeout)?,
+                    config.unhealthy_threshold,
+                    config.healthy_threshold,
+                );
+                Ok(HealthCheck(adapter))
+            }
+            conf::RegionStrategy(config) => {
+                let Some(geoip) = geoip else {
+                    return Err("region strategy requires a GeoIP database".into());
+                };
+                let adapter = RegionStrategyAdapter::new(
+                    Arc::clone(geoip),
+                    config.field,
+                    config.countries,
+                    config.continents,
+                    config.distances,
+                )
+                .with_default_region(config.default_region);
+                Ok(RegionStrategy(adapter))
+            }
             _ => Err("unknown discovery adapter configured".into()),
         }
     }
 }
 
-impl From<config::FilterRule> for FilterRule {
-    fn from(value: config::FilterRule) -> Self {
-        Self {
+impl TryFrom<config::Filter> for Filter {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(value: config::Filter) -> Result<Self, Self::Error> {
+        let filter = match value {
+            config::Filter::All { all } => Filter::All(
+                all.into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+            config::Filter::Any { any } => Filter::Any(
+                any.into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+            config::Filter::Not { not } => Filter::Not(Box::new((*not).try_into()?)),
+            config::Filter::Rule(rule) => Filter::Rule(rule.try_into()?),
+        };
+        Ok(filter)
+    }
+}
+
+impl TryFrom<config::FilterRule> for FilterRule {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(value: config::FilterRule) -> Result<Self, Self::Error> {
+        Ok(Self {
             key: value.key,
-            operation: value.operation.into(),
-        }
+            operation: value.operation.try_into()?,
+        })
     }
 }
 
-impl From<config::FilterOperation> for FilterOperation {
-    fn from(value: config::FilterOperation) -> Self {
-        match value {
-            config::FilterOperation::Equals(value) => FilterOperation::Equals(value),
-            config::FilterOperation::NotEquals(value) => FilterOperation::NotEquals(value),
-            config::FilterOperation::Exists => FilterOperation::Exists,
-            config::FilterOperation::NotExists => FilterOperation::NotExists,
-            config::FilterOperation::In(values) => FilterOperation::In(values),
-            config::FilterOperation::NotIn(values) => FilterOperation::NotIn(values),
-        }
+impl TryFrom<config::FilterOperation> for FilterOperation {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(value: config::FilterOperation) -> Result<Self, Self::Error> {
+        use config::FilterOperation as conf;
+        let operation = match value {
+            conf::Equals(value) => FilterOperation::Equals(value.try_into()?),
+            conf::NotEquals(value) => FilterOperation::NotEquals(value.try_into()?),
+            conf::Exists => FilterOperation::Exists,
+            conf::NotExists => FilterOperation::NotExists,
+            conf::In(values) => FilterOperation::In(
+                values
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+            conf::NotIn(values) => FilterOperation::NotIn(
+                values
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+            conf::LessThan(value) => FilterOperation::LessThan(value.try_into()?),
+            conf::LessThanOrEquals(value) => FilterOperation::LessThanOrEquals(value.try_into()?),
+            conf::GreaterThan(value) => FilterOperation::GreaterThan(value.try_into()?),
+            conf::GreaterThanOrEquals(value) => {
+                FilterOperation::GreaterThanOrEquals(value.try_into()?)
+            }
+            conf::Version(requirement) => {
+                FilterOperation::Version(VersionReq::parse(&requirement)?)
+            }
+            conf::Matches(regex) => FilterOperation::Matches(Regex::new(&regex)?),
+            conf::NotMatches(regex) => FilterOperation::NotMatches(Regex::new(&regex)?),
+        };
+        Ok(operation)
+    }
+}
+
+impl TryFrom<config::FilterValue> for FilterValue {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(value: config::FilterValue) -> Result<Self, Self::Error> {
+        let value = match value {
+            config::FilterValue::Text(value) => FilterValue::Literal(value),
+            config::FilterValue::Number(value) => FilterValue::Literal(value.to_string()),
+            config::FilterValue::Bool(value) => FilterValue::Literal(value.to_string()),
+            config::FilterValue::Field { field } => FilterValue::Field(field.parse::<FieldRef>()?),
+        };
+        Ok(value)
     }
 }
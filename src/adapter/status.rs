@@ -1,18 +1,44 @@
+use crate::adapter::discovery::DynDiscoveryActionAdapter;
+use crate::adapter::localization::DynLocalizationAdapter;
 use crate::config;
+use passage_adapters::geoip::GeoIpDatabase;
 use passage_adapters::status::StatusAdapter;
-use passage_adapters::{Client, FixedStatusAdapter, ServerStatus, ServerVersion};
+use passage_adapters::status::aggregate::{AggregateMotd, AggregateSource, MotdTemplate};
+use passage_adapters::status::favicon::{
+    FaviconSource, FileFaviconSource, FixedFaviconSource, encode_data_uri,
+};
+use passage_adapters::status::post_process::StatusPostProcessor;
+use passage_adapters::{
+    AggregateStatusAdapter, Client, FaviconStatusAdapter, FixedStatusAdapter,
+    MaintenanceStatusAdapter, MotdStatusAdapter, PostProcessStatusAdapter, ServerStatus,
+    ServerVersion,
+};
 #[cfg(feature = "adapters-grpc")]
 use passage_adapters_grpc::GrpcStatusAdapter;
 #[cfg(feature = "adapters-http")]
-use passage_adapters_http::HttpStatusAdapter;
+use passage_adapters_http::{HttpFaviconSource, HttpStatusAdapter};
 use serde_json::value::RawValue;
 use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
 
 /// Runtime-selected status adapter.
 ///
 /// Wraps every built-in and feature-gated [`StatusAdapter`] implementation behind a single enum.
 #[derive(Debug)]
 pub enum DynStatusAdapter {
+    /// Replaces the description of another status adapter with a localized message.
+    Motd(MotdStatusAdapter<Box<DynStatusAdapter>, Arc<DynLocalizationAdapter>>),
+    /// Replaces the description of another status adapter while the route is in maintenance.
+    Maintenance(MaintenanceStatusAdapter<Box<DynStatusAdapter>>),
+    /// Replaces the favicon of another status adapter with a (rotated) favicon source.
+    Favicon(FaviconStatusAdapter<Box<DynStatusAdapter>, DynFaviconSource>),
+    /// Applies a stack of post processors to the status of another status adapter.
+    PostProcess(PostProcessStatusAdapter<Box<DynStatusAdapter>, Arc<DynLocalizationAdapter>>),
+    /// Derives the player counts of another status adapter from the targets of a discovery.
+    Aggregate(AggregateStatusAdapter<Box<DynStatusAdapter>, Vec<DynDiscoveryActionAdapter>>),
     /// Returns a fixed, pre-configured server status.
     Fixed(FixedStatusAdapter),
     /// Retrieves status from an external gRPC service.
@@ -26,6 +52,11 @@ pub enum DynStatusAdapter {
 impl Display for DynStatusAdapter {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::Motd(_) => write!(f, "motd"),
+            Self::Maintenance(_) => write!(f, "maintenance"),
+            Self::Favicon(_) => write!(f, "favicon"),
+            Self::PostProcess(_) => write!(f, "post-process"),
+            Self::Aggregate(_) => write!(f, "aggregate"),
             Self::Fixed(_) => write!(f, "fixed"),
             #[cfg(feature = "adapters-grpc")]
             Self::Grpc(_) => write!(f, "grpc"),
@@ -38,6 +69,12 @@ impl Display for DynStatusAdapter {
 impl StatusAdapter for DynStatusAdapter {
     async fn status(&self, client: &Client) -> passage_adapters::Result<Option<ServerStatus>> {
         match self {
+            // decorators are boxed as they recursively contain this adapter
+            DynStatusAdapter::Motd(adapter) => Box::pin(adapter.status(client)).await,
+            DynStatusAdapter::Maintenance(adapter) => Box::pin(adapter.status(client)).await,
+            DynStatusAdapter::Favicon(adapter) => Box::pin(adapter.status(client)).await,
+            DynStatusAdapter::PostProcess(adapter) => Box::pin(adapter.status(client)).await,
+            DynStatusAdapter::Aggregate(adapter) => Box::pin(adapter.status(client)).await,
             DynStatusAdapter::Fixed(adapter) => adapter.status(client).await,
             #[cfg(feature = "adapters-grpc")]
             DynStatusAdapter::Grpc(adapter) => adapter.status(client).await,
@@ -48,9 +85,11 @@ impl StatusAdapter for DynStatusAdapter {
 }
 
 impl DynStatusAdapter {
-    /// Constructs the adapter described by `config`, establishing any required connections.
+    /// Constructs the adapter described by `config`, establishing any required connections. The
+    /// `geoip` database is passed to the discovery of the aggregate adapter.
     pub async fn from_config(
         config: config::StatusAdapter,
+        geoip: Option<&Arc<GeoIpDatabase>>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         #[allow(unreachable_patterns)]
         match config {
@@ -66,7 +105,11 @@ impl DynStatusAdapter {
                         },
                         players: None,
                         description,
-                        favicon: config.favicon,
+                        favicon: config.favicon.and_then(|favicon| {
+                            encode_data_uri(&favicon)
+                                .inspect_err(|err| warn!(err = %err, "ignoring invalid favicon"))
+                                .ok()
+                        }),
                         enforces_secure_chat: config.enforces_secure_chat,
                     }),
                     config.preferred_version,
@@ -85,7 +128,181 @@ impl DynStatusAdapter {
                 let adapter = HttpStatusAdapter::new(config.address, config.cache_duration)?;
                 Ok(DynStatusAdapter::Http(adapter))
             }
+            config::StatusAdapter::Aggregate(config) => {
+                let inner = Box::pin(Self::from_config(*config.status, geoip)).await?;
+                let Some(discovery) = config.discovery else {
+                    return Err("aggregate status adapter requires a discovery".into());
+                };
+                let player_specific = discovery.actions.iter().any(|action| {
+                    matches!(
+                        action,
+                        config::DiscoveryActionAdapter::PlayerAllowFilter(_)
+                            | config::DiscoveryActionAdapter::PlayerBlockFilter(_)
+                    )
+                });
+                if player_specific {
+                    warn!(
+                        "aggregate status discovery filters players, but only sees an anonymous one"
+                    );
+                }
+                let discovery =
+                    Box::pin(DynDiscoveryActionAdapter::from_config(*discovery, geoip)).await?;
+                let source = match config.source {
+                    config::AggregateSource::Meta => AggregateSource::Meta {
+                        online_field: config.online_field,
+                        max_field: config.max_field,
+                    },
+                    config::AggregateSource::Ping => AggregateSource::Ping {
+                        timeout: Duration::try_from_secs_f64(config.timeout)?,
+                    },
+                };
+                let motd = match config.motd {
+                    config::AggregateMotd::Inner => AggregateMotd::Inner,
+                    config::AggregateMotd::Top => {
+                        if matches!(source, AggregateSource::Meta { .. }) {
+                            return Err("aggregate status top motd requires the ping source".into());
+                        }
+                        AggregateMotd::Top
+                    }
+                    config::AggregateMotd::Template => {
+                        let Some(template) = config.template else {
+                            return Err("aggregate status template motd requires a template".into());
+                        };
+                        AggregateMotd::Template(MotdTemplate::new(template)?)
+                    }
+                };
+                let adapter = AggregateStatusAdapter::new(Box::new(inner), discovery, source)
+                    .with_motd(motd)
+                    .with_max_samples(config.max_samples)
+                    .with_cache_duration(Duration::from_secs(config.cache_duration));
+                Ok(DynStatusAdapter::Aggregate(adapter))
+            }
             _ => Err("unknown status adapter configured".into()),
         }
     }
+
+    /// Wraps the adapter such that the description is localized with the `localization` adapter of
+    /// the route. The locale is guessed with the `geoip` database (if available).
+    pub fn with_motd(
+        self,
+        config: config::Motd,
+        localization: &Arc<DynLocalizationAdapter>,
+        geoip: Option<&Arc<GeoIpDatabase>>,
+    ) -> Self {
+        let mut adapter =
+            MotdStatusAdapter::new(Box::new(self), Arc::clone(localization), config.key)
+                .with_default_locale(config.default_locale);
+        match geoip {
+            Some(geoip) => adapter = adapter.with_geoip(Arc::clone(geoip), config.locales),
+            None if !config.locales.is_empty() => {
+                warn!("ignoring motd locales as no GeoIP database is configured");
+            }
+            None => {}
+        }
+        DynStatusAdapter::Motd(adapter)
+    }
+
+    /// Wraps the adapter such that the description of the maintenance configuration is shown.
+    pub fn with_maintenance(self, config: config::Maintenance) -> Self {
+        let description = config
+            .description
+            .and_then(|str| RawValue::from_string(str).ok());
+        let adapter = MaintenanceStatusAdapter::new(Box::new(self), description);
+        DynStatusAdapter::Maintenance(adapter)
+    }
+
+    /// Wraps the adapter such that the favicon is taken from the sources of the favicon configuration.
+    pub fn with_favicon(self, config: config::Favicon) -> Result<Self, Box<dyn std::error::Error>> {
+        let sources = config
+            .sources
+            .iter()
+            .map(|source| DynFaviconSource::from_config(source, &config))
+            .collect::<Result<Vec<_>, _>>()?;
+        let adapter = FaviconStatusAdapter::new(
+            Box::new(self),
+            sources,
+            Duration::from_secs(config.rotation_interval),
+        );
+        Ok(DynStatusAdapter::Favicon(adapter))
+    }
+
+    /// Wraps the adapter such that the post processors are applied (in order) to its status. The
+    /// sample lines are localized with the `localization` adapter of the route.
+    pub fn with_processors(
+        self,
+        config: Vec<config::StatusProcessor>,
+        localization: &Arc<DynLocalizationAdapter>,
+    ) -> Self {
+        if config.is_empty() {
+            return self;
+        }
+        let processors = config
+            .into_iter()
+            .map(|config| StatusPostProcessor {
+                min_version: config.min_version,
+                max_version: config.max_version,
+                online_offset: config.online_offset,
+                min_online: config.min_online,
+                max_online: config.max_online,
+                max_from_online: config.max_from_online,
+                sample: config.sample,
+                hide_players: config.hide_players,
+            })
+            .collect();
+        let adapter =
+            PostProcessStatusAdapter::new(Box::new(self), Arc::clone(localization), processors);
+        DynStatusAdapter::PostProcess(adapter)
+    }
+}
+
+/// Runtime-selected favicon source.
+///
+/// Wraps every built-in and feature-gated [`FaviconSource`] implementation behind a single enum.
+#[derive(Debug)]
+pub enum DynFaviconSource {
+    /// Returns a fixed favicon data URI.
+    Fixed(FixedFaviconSource),
+    /// Reads the favicon from an image file and reloads it whenever it changes.
+    File(FileFaviconSource),
+    /// Periodically fetches the favicon from a URL.
+    #[cfg(feature = "adapters-http")]
+    Http(HttpFaviconSource),
+}
+
+impl FaviconSource for DynFaviconSource {
+    async fn favicon(&self) -> Option<String> {
+        match self {
+            DynFaviconSource::Fixed(source) => source.favicon().await,
+            DynFaviconSource::File(source) => source.favicon().await,
+            #[cfg(feature = "adapters-http")]
+            DynFaviconSource::Http(source) => source.favicon().await,
+        }
+    }
+}
+
+impl DynFaviconSource {
+    /// Constructs the source described by `source`. Data URIs are fixed favicons, HTTP(S) URLs are
+    /// fetched every `cache_duration` seconds and everything else is a file path that is checked for
+    /// modifications every `reload_interval` seconds.
+    pub fn from_config(
+        source: &str,
+        config: &config::Favicon,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if source.starts_with("data:") {
+            return Ok(DynFaviconSource::Fixed(FixedFaviconSource::new(source)?));
+        }
+        if source.starts_with("http://") || source.starts_with("https://") {
+            #[cfg(feature = "adapters-http")]
+            return Ok(DynFaviconSource::Http(HttpFaviconSource::new(
+                source.to_string(),
+                config.cache_duration,
+            )?));
+            #[cfg(not(feature = "adapters-http"))]
+            return Err("favicon urls require the adapters-http feature".into());
+        }
+        Ok(DynFaviconSource::File(
+            FileFaviconSource::new(PathBuf::from(source))
+                .with_reload_interval(Duration::from_secs(config.reload_interval)),
+        ))
+    }
 }
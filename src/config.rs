@@ -15,8 +15,11 @@
 //!
 //! ## Layer 2 (Auth Secret File) \[optional\]
 //!
-//! The next layer is just for setting the auth cookie secret. It reads a single file as the key. Set
-//! the location using the `AUTH_SECRET_FILE` environment variable, defaulting to `config/auth_secret`.
+//! The next layer is just for setting the auth cookie secrets. Set the location using the
+//! `AUTH_SECRET_FILE` environment variable, defaulting to `config/auth_secret`. The location can be
+//! a plain file containing a single secret, a JSON file containing a keyring (see [`AuthKeys`]) or
+//! a directory with one file per key (file name as key id). In a directory, the optional file
+//! `active` contains the id of the active key, otherwise the greatest key id is used.
 //!
 //! ## Layer 3 (Custom configuration) \[optional\]
 //!
@@ -42,15 +45,20 @@
 #![allow(clippy::derivable_impls)]
 
 use crate::metrics::system::DEFAULT_OBSERVE_INTERVAL;
-use config::{ConfigError, Environment, File, FileStoredFormat, Format, Map, Value, ValueKind};
+use config::{ConfigError, Environment, File, Map, Source, Value, ValueKind};
 use passage_adapters::authentication::Profile;
+use passage_adapters::authentication::ban::Ban;
 use passage_adapters::backoff::ExponentialBackoff;
 use passage_adapters::{Protocol, Target};
-use passage_protocol::config::DEFAULT_CONNECTION_TIMEOUT;
+use passage_protocol::config::{DEFAULT_CONNECTION_TIMEOUT, DEFAULT_SESSION_HISTORY};
 use passage_protocol::connection::{DEFAULT_AUTH_COOKIE_EXPIRY, DEFAULT_MAX_PACKET_LENGTH};
+use passage_protocol::crypto::DEFAULT_KEY_SIZE;
+use passage_protocol::ip_binding::{DEFAULT_IPV4_PREFIX, DEFAULT_IPV6_PREFIX};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::env;
+use std::fs;
+use std::path::PathBuf;
 
 macro_rules! hashmap {
     ($($key:expr => $value:expr),* $(,)?) => {{
@@ -83,6 +91,26 @@ pub struct Config {
     #[serde(alias = "authcookieexpiry")]
     pub auth_cookie_expiry: u64,
 
+    /// Whether to encrypt auth cookies, so clients cannot read their contents. Requires an HMAC
+    /// secret as active key.
+    #[serde(alias = "authcookieencryption")]
+    pub auth_cookie_encryption: bool,
+
+    /// The replay policy of auth cookies.
+    #[serde(alias = "authcookiereplaypolicy")]
+    pub auth_cookie_replay_policy: ReplayPolicy,
+
+    /// The binding policy of the auth cookie client address.
+    #[serde(alias = "authcookieipbinding")]
+    pub auth_cookie_ip_binding: IpBinding,
+
+    /// The number of previous targets kept in the session cookie.
+    #[serde(alias = "sessionhistory")]
+    pub session_history: usize,
+
+    /// The name of this instance, recorded in new session cookies (defaults to the hostname).
+    pub instance: Option<String>,
+
     /// The interval in seconds at which the system observer should be run.
     #[serde(alias = "systemobserverinterval")]
     pub system_observer_interval: Option<u64>,
@@ -106,6 +134,34 @@ pub struct Config {
     #[serde(alias = "authsecret")]
     pub auth_secret: Option<String>,
 
+    /// The auth cookie keyring, overrides the auth cookie secret if set.
+    #[serde(alias = "authkeys")]
+    pub auth_keys: Option<AuthKeys>,
+
+    /// The file to write the public keys of asymmetric auth cookie keys to (as JSON, by key id).
+    #[serde(alias = "authpublickeysfile")]
+    pub auth_public_keys_file: Option<String>,
+
+    /// The RSA server key (PKCS#8 or PKCS#1 PEM) used for the encryption handshake. A key is
+    /// generated on startup if empty.
+    #[serde(alias = "serverkey")]
+    pub server_key: Option<String>,
+
+    /// The file to read the RSA server key from, used if the server key is empty.
+    #[serde(alias = "serverkeyfile")]
+    pub server_key_file: Option<String>,
+
+    /// The size in bits of the RSA server key that is generated on startup.
+    #[serde(alias = "serverkeysize")]
+    pub server_key_size: usize,
+
+    /// The named secrets that requested cookies of the routes are signed with.
+    #[serde(alias = "cookiesecrets")]
+    pub cookie_secrets: HashMap<String, String>,
+
+    /// The GeoIP database that resolves the location of client addresses (disabled if empty).
+    pub geoip: Option<GeoIp>,
+
     /// The routes' configuration.
     pub routes: Vec<Routes>,
 }
@@ -121,9 +177,21 @@ impl Default for Config {
             rate_limiter: None,
             proxy_protocol: None,
             auth_secret: None,
+            auth_keys: None,
+            auth_public_keys_file: None,
+            server_key: None,
+            server_key_file: None,
+            server_key_size: DEFAULT_KEY_SIZE,
+            cookie_secrets: HashMap::new(),
+            geoip: None,
             routes: Default::default(),
             max_packet_length: DEFAULT_MAX_PACKET_LENGTH as usize,
             auth_cookie_expiry: DEFAULT_AUTH_COOKIE_EXPIRY,
+            session_history: DEFAULT_SESSION_HISTORY,
+            instance: None,
+            auth_cookie_encryption: false,
+            auth_cookie_replay_policy: ReplayPolicy::default(),
+            auth_cookie_ip_binding: IpBinding::default(),
         }
     }
 }
@@ -194,6 +262,122 @@ impl Default for RateLimiter {
     }
 }
 
+/// [`AuthKeys`] hold the auth cookie keyring configuration.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct AuthKeys {
+    /// The id of the key used to sign new auth cookies.
+    pub active: String,
+
+    /// The keys by key id. All keys are accepted to verify auth cookies. A key is either a secret
+    /// (HMAC-SHA256), an Ed25519 private key (PKCS#8 PEM) or an Ed25519 public key (SPKI PEM, verify
+    /// only).
+    pub keys: HashMap<String, String>,
+}
+
+/// [`ReplayPolicy`] defines how often an auth cookie may be used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayPolicy {
+    /// Auth cookies can be reused any number of times until they expire.
+    #[default]
+    Reusable,
+
+    /// Auth cookies can only be used once. Each transfer issues a new cookie.
+    SingleUse,
+
+    /// Auth cookies keep their nonce across transfers, but each cookie is only accepted once and
+    /// only if it is newer than the last used one.
+    Sliding,
+}
+
+/// [`IpBinding`] defines how the auth cookie client address has to match the connecting client.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpBinding {
+    /// The addresses have to be equal.
+    #[default]
+    Exact,
+
+    /// The addresses have to share the same network prefix.
+    Prefix(PrefixIpBinding),
+
+    /// The addresses have to belong to the same autonomous system.
+    Asn(AsnIpBinding),
+
+    /// The addresses are not checked.
+    None,
+}
+
+/// [`PrefixIpBinding`] hold the prefix ip binding configuration.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct PrefixIpBinding {
+    /// The prefix length of IPv4 addresses.
+    pub ipv4: u8,
+
+    /// The prefix length of IPv6 addresses.
+    pub ipv6: u8,
+}
+
+impl Default for PrefixIpBinding {
+    fn default() -> Self {
+        Self {
+            ipv4: DEFAULT_IPV4_PREFIX,
+            ipv6: DEFAULT_IPV6_PREFIX,
+        }
+    }
+}
+
+/// [`AsnIpBinding`] hold the autonomous system ip binding configuration. If the database is also
+/// the GeoIP database, it is shared.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct AsnIpBinding {
+    /// The path of the MaxMind ASN database (e.g. GeoLite2-ASN).
+    pub database: String,
+
+    /// How often the database file is checked for modifications in seconds (disabled if zero).
+    #[serde(alias = "reloadinterval")]
+    pub reload_interval: u64,
+}
+
+impl Default for AsnIpBinding {
+    fn default() -> Self {
+        Self {
+            database: String::new(),
+            reload_interval: 60,
+        }
+    }
+}
+
+/// [`GeoIp`] hold the GeoIP database configuration. The database is shared by all routes.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct GeoIp {
+    /// The path of the MaxMind country or city database (e.g. GeoLite2-Country).
+    pub database: String,
+
+    /// How often the database file is checked for modifications in seconds (disabled if zero).
+    #[serde(alias = "reloadinterval")]
+    pub reload_interval: u64,
+}
+
+impl Default for GeoIp {
+    fn default() -> Self {
+        Self {
+            database: String::new(),
+            reload_interval: 60,
+        }
+    }
+}
+
 /// [`ProxyProtocol`] hold the PROXY protocol configuration.
 #[derive(Debug, Clone, Deserialize)]
 #[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
@@ -237,6 +421,257 @@ pub struct Routes {
 
     /// The discovery adapter configuration.
     pub discovery: DiscoveryAdapter,
+
+    /// The localized description configuration (disabled if empty).
+    pub motd: Option<Motd>,
+
+    /// The maintenance mode configuration (disabled if empty).
+    pub maintenance: Option<Maintenance>,
+
+    /// The favicon configuration that replaces the favicon of the status (disabled if empty).
+    pub favicon: Option<Favicon>,
+
+    /// The post processors that are applied (in order) to the status.
+    #[serde(alias = "statusprocessors")]
+    pub status_processors: Vec<StatusProcessor>,
+
+    /// The additional cookies configuration.
+    pub cookies: RouteCookies,
+
+    /// The tie-breaker that chooses among the targets with the lowest priority.
+    #[serde(alias = "tiebreaker")]
+    pub tie_breaker: TieBreaker,
+
+    /// The reachability probe of the selected target (disabled if empty).
+    pub probe: Option<Probe>,
+}
+
+/// [`Probe`] hold the target reachability probe configuration. If the selected target is not
+/// reachable, the next target is tried instead.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Probe {
+    /// The method that is used to check the reachability.
+    pub method: ProbeMethod,
+
+    /// The timeout of a single probe in (fractional) seconds.
+    pub timeout: f64,
+
+    /// The time a probe result is cached in seconds (disabled if zero).
+    #[serde(alias = "cacheduration")]
+    pub cache_duration: u64,
+
+    /// The number of targets that are probed before the player is disconnected.
+    pub attempts: usize,
+}
+
+impl Default for Probe {
+    fn default() -> Self {
+        Self {
+            method: ProbeMethod::default(),
+            timeout: 0.5,
+            cache_duration: 5,
+            attempts: 3,
+        }
+    }
+}
+
+/// [`ProbeMethod`] defines how the reachability of a target is checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeMethod {
+    /// A TCP connection is established.
+    #[default]
+    Tcp,
+
+    /// A status request (server list ping) is answered.
+    Status,
+}
+
+/// [`TieBreaker`] defines how the target is chosen among the targets with the lowest priority.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreaker {
+    /// The first target (in pipeline order) is chosen.
+    #[default]
+    First,
+
+    /// A random target is chosen.
+    Random,
+
+    /// A target is chosen by hashing the player id, so the same player gets the same target.
+    Hash,
+}
+
+/// [`RouteCookies`] hold the additional (non-Passage) cookie configuration of a route.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct RouteCookies {
+    /// The cookies that are requested from transferred clients and exposed to the adapters.
+    pub request: Vec<CookieRequest>,
+
+    /// The keys of the cookies that are cleared before the client is transferred.
+    pub clear: Vec<String>,
+}
+
+/// [`CookieRequest`] hold the configuration of a single requested cookie.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+pub struct CookieRequest {
+    /// The key of the cookie.
+    pub key: String,
+
+    /// The format of the cookie payload.
+    #[serde(default)]
+    pub format: CookieFormat,
+
+    /// The name of the cookie secret the cookie is signed with (unsigned if empty).
+    pub secret: Option<String>,
+}
+
+/// [`CookieFormat`] defines how the payload of a requested cookie is decoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CookieFormat {
+    /// The payload is exposed as a string.
+    #[default]
+    Raw,
+
+    /// The payload is decoded as JSON.
+    Json,
+}
+
+/// [`Maintenance`] hold the route maintenance mode configuration. While in maintenance, all players
+/// except the allowed ones are rejected and the status description is replaced.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Maintenance {
+    /// List of player usernames that are allowed to join during maintenance.
+    pub usernames: Vec<String>,
+
+    /// List of player IDs that are allowed to join during maintenance.
+    pub ids: Vec<String>,
+
+    /// The description of the server during maintenance.
+    pub description: Option<String>,
+}
+
+impl Default for Maintenance {
+    fn default() -> Self {
+        Self {
+            usernames: vec![],
+            ids: vec![],
+            description: Some(
+                "{\"text\":\"Server is under maintenance\",\"color\":\"red\"}".to_string(),
+            ),
+        }
+    }
+}
+
+/// [`Motd`] hold the localized description configuration. The locale of the client is guessed from
+/// the country of its address.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Motd {
+    /// The localization key of the description.
+    pub key: String,
+
+    /// The locale that is used if no locale could be guessed (the localization default if empty).
+    #[serde(alias = "defaultlocale")]
+    pub default_locale: Option<String>,
+
+    /// The locales by ISO country code (e.g. `DE: de_de`). Requires the GeoIP database.
+    pub locales: HashMap<String, String>,
+}
+
+impl Default for Motd {
+    fn default() -> Self {
+        Self {
+            key: "motd".to_string(),
+            default_locale: None,
+            locales: HashMap::new(),
+        }
+    }
+}
+
+/// [`Favicon`] hold the route favicon configuration. The favicons are validated and resized to
+/// 64x64 PNG images if needed.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Favicon {
+    /// The favicon sources, either a data URI, an HTTP(S) URL or an image file path. Multiple
+    /// sources are rotated.
+    pub sources: Vec<String>,
+
+    /// How often the source is rotated in seconds (on every status request if zero).
+    #[serde(alias = "rotationinterval")]
+    pub rotation_interval: u64,
+
+    /// The cache duration in seconds to store the fetched favicon of an HTTP(S) URL. Must be greater
+    /// than zero.
+    #[serde(alias = "cacheduration")]
+    pub cache_duration: u64,
+
+    /// How often favicon files are checked for modifications in seconds (disabled if zero).
+    #[serde(alias = "reloadinterval")]
+    pub reload_interval: u64,
+}
+
+impl Default for Favicon {
+    fn default() -> Self {
+        Self {
+            sources: vec![],
+            rotation_interval: 0,
+            cache_duration: 300,
+            reload_interval: 60,
+        }
+    }
+}
+
+/// [`StatusProcessor`] hold the status post processor configuration. The processor only applies to
+/// clients within the protocol version range.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct StatusProcessor {
+    /// The minimum protocol version of the clients (inclusive, unbounded if empty).
+    #[serde(alias = "minversion")]
+    pub min_version: Option<Protocol>,
+
+    /// The maximum protocol version of the clients (inclusive, unbounded if empty).
+    #[serde(alias = "maxversion")]
+    pub max_version: Option<Protocol>,
+
+    /// The offset that is added to the online count.
+    #[serde(alias = "onlineoffset")]
+    pub online_offset: i64,
+
+    /// The lower bound of the online count.
+    #[serde(alias = "minonline")]
+    pub min_online: Option<u32>,
+
+    /// The upper bound of the online count.
+    #[serde(alias = "maxonline")]
+    pub max_online: Option<u32>,
+
+    /// Whether the maximum count is set to one more than the online count.
+    #[serde(alias = "maxfromonline")]
+    pub max_from_online: bool,
+
+    /// The localization keys (or literal texts) of the lines that replace the player sample.
+    pub sample: Option<Vec<String>>,
+
+    /// Whether the player counts are hidden entirely.
+    #[serde(alias = "hideplayers")]
+    pub hide_players: bool,
 }
 
 /// [`StatusAdapter`] hold the status adapter configuration.
@@ -247,6 +682,7 @@ pub enum StatusAdapter {
     Fixed(FixedStatus),
     Grpc(GrpcStatus),
     Http(HttpStatus),
+    Aggregate(AggregateStatus),
 }
 
 impl Default for StatusAdapter {
@@ -332,6 +768,95 @@ impl Default for HttpStatus {
     }
 }
 
+/// [`AggregateStatus`] hold the aggregate status (ping) configuration. The player counts of the
+/// inner status are derived from the targets of its own discovery. The route discovery is not used,
+/// as its actions (e.g., allocations or round-robin counters) must only run for real logins.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct AggregateStatus {
+    /// The status adapter whose player counts (and description) are replaced.
+    pub status: Box<StatusAdapter>,
+
+    /// The discovery of the aggregated targets. The targets are discovered for an anonymous player.
+    pub discovery: Option<Box<DiscoveryAdapter>>,
+
+    /// Where the player counts of the targets are taken from.
+    pub source: AggregateSource,
+
+    /// The name of the target metadata field that stores the online players (for the meta source).
+    #[serde(alias = "onlinefield")]
+    pub online_field: String,
+
+    /// The name of the target metadata field that stores the maximum players (for the meta source).
+    #[serde(alias = "maxfield")]
+    pub max_field: String,
+
+    /// The timeout of a single status request in (fractional) seconds (for the ping source).
+    pub timeout: f64,
+
+    /// Where the description (MOTD) is taken from.
+    pub motd: AggregateMotd,
+
+    /// The description template (for the template motd). The placeholders `{online}`, `{max}` and
+    /// `{targets}` are replaced with the aggregated values.
+    pub template: Option<String>,
+
+    /// The maximum number of merged sample players.
+    #[serde(alias = "maxsamples")]
+    pub max_samples: usize,
+
+    /// The time the aggregated values are cached in seconds (disabled if zero).
+    #[serde(alias = "cacheduration")]
+    pub cache_duration: u64,
+}
+
+impl Default for AggregateStatus {
+    fn default() -> Self {
+        Self {
+            status: Box::default(),
+            discovery: None,
+            source: AggregateSource::default(),
+            online_field: "online".to_string(),
+            max_field: "max".to_string(),
+            timeout: 1.0,
+            motd: AggregateMotd::default(),
+            template: None,
+            max_samples: 12,
+            cache_duration: 5,
+        }
+    }
+}
+
+/// [`AggregateSource`] defines where the player counts of the targets are taken from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateSource {
+    /// The target metadata (e.g., as annotated by a health check).
+    #[default]
+    Meta,
+
+    /// A status request (server list ping) to each target.
+    Ping,
+}
+
+/// [`AggregateMotd`] defines where the description (MOTD) of the aggregated status is taken from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateMotd {
+    /// The description of the inner status.
+    #[default]
+    Inner,
+
+    /// The description of the target with the highest priority (requires the ping source).
+    Top,
+
+    /// The description rendered from the template.
+    Template,
+}
+
 /// [`DiscoveryAdapter`] hold the discovery (adapter) configuration.
 #[derive(Default, Debug, Clone, Deserialize)]
 #[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
@@ -367,6 +892,20 @@ pub enum DiscoveryActionAdapter {
     PlayerBlockFilter(PlayerBlockFilter),
     #[serde(alias = "playerfillfilter")]
     PlayerFillStrategy(PlayerFillStrategy),
+    #[serde(alias = "weightedrandomstrategy")]
+    WeightedRandomStrategy(WeightedRandomStrategy),
+    #[serde(alias = "roundrobinstrategy")]
+    RoundRobinStrategy(RoundRobinStrategy),
+    #[serde(alias = "leastplayersstrategy")]
+    LeastPlayersStrategy(LeastPlayersStrategy),
+    #[serde(alias = "consistenthashstrategy")]
+    ConsistentHashStrategy(ConsistentHashStrategy),
+    #[serde(alias = "poweroftwostrategy")]
+    PowerOfTwoStrategy(PowerOfTwoStrategy),
+    #[serde(alias = "healthcheck")]
+    HealthCheck(HealthCheck),
+    #[serde(alias = "regionstrategy")]
+    RegionStrategy(RegionStrategy),
 }
 
 impl Default for DiscoveryActionAdapter {
@@ -485,12 +1024,27 @@ impl Default for DnsDiscovery {
 #[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct MetaFilter {
-    /// List of filter rules. All rules must match (AND logic).
-    pub rules: Vec<FilterRule>,
+    /// List of filter rules or groups. All rules must match (AND logic).
+    pub rules: Vec<Filter>,
+}
+
+/// A filter that is either a single rule or a logical group of filters.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum Filter {
+    /// All filters must match (AND logic).
+    All { all: Vec<Filter> },
+    /// Any filter must match (OR logic).
+    Any { any: Vec<Filter> },
+    /// The filter must not match.
+    Not { not: Box<Filter> },
+    /// A single filter rule.
+    Rule(FilterRule),
 }
 
 /// A single filter rule.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 #[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
 pub struct FilterRule {
     /// The metadata key to filter on.
@@ -502,25 +1056,64 @@ pub struct FilterRule {
 }
 
 /// Filter operation to apply to a target field.
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 #[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
 #[serde(tag = "op", content = "value", rename_all = "snake_case")]
 pub enum FilterOperation {
     /// Field must equal the specified value.
-    Equals(String),
+    Equals(FilterValue),
     /// Field must not equal the specified value.
     #[serde(alias = "notequals")]
-    NotEquals(String),
+    NotEquals(FilterValue),
     /// Field must exist (have any value).
     Exists,
     /// Field must not exist.
     #[serde(alias = "notexists")]
     NotExists,
     /// Field must be one of the specified values.
-    In(Vec<String>),
+    In(Vec<FilterValue>),
     /// Field must not be any of the specified values.
     #[serde(alias = "notin")]
-    NotIn(Vec<String>),
+    NotIn(Vec<FilterValue>),
+    /// Field must be a number less than the specified value.
+    #[serde(alias = "lessthan", alias = "lt")]
+    LessThan(FilterValue),
+    /// Field must be a number less than or equal to the specified value.
+    #[serde(alias = "lessthanorequals", alias = "lte")]
+    LessThanOrEquals(FilterValue),
+    /// Field must be a number greater than the specified value.
+    #[serde(alias = "greaterthan", alias = "gt")]
+    GreaterThan(FilterValue),
+    /// Field must be a number greater than or equal to the specified value.
+    #[serde(alias = "greaterthanorequals", alias = "gte")]
+    GreaterThanOrEquals(FilterValue),
+    /// Field must be a version that satisfies the specified requirement (e.g. `>=1.21`).
+    #[serde(alias = "semver")]
+    Version(String),
+    /// Field must match the specified regex.
+    #[serde(alias = "regex")]
+    Matches(String),
+    /// Field must not match the specified regex.
+    #[serde(alias = "notmatches")]
+    NotMatches(String),
+}
+
+/// The value of a filter operation, either a literal or a reference to a field of the request
+/// (e.g. `{ field: client.ip }`).
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum FilterValue {
+    /// A literal text value.
+    Text(String),
+    /// A literal numeric value.
+    Number(f64),
+    /// A literal boolean value.
+    Bool(bool),
+    /// A reference to a field of the target (`meta.<key>`, `target.identifier`, ...), client
+    /// (`client.ip`, ...), player (`player.name`, `player.claims.<key>`, ...) or session
+    /// (`session.hops`, ...).
+    Field { field: String },
 }
 
 /// [`PlayerAllowFilter`] hold the player filter configuration (blocks all if empty).
@@ -566,6 +1159,124 @@ pub struct PlayerFillStrategy {
     pub max_players: u32,
 }
 
+/// [`WeightedRandomStrategy`] hold the weighted random strategy configuration.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct WeightedRandomStrategy {
+    /// The name of the field that stores the weight.
+    pub field: String,
+
+    /// The weight of targets without a weight field.
+    #[serde(alias = "defaultweight")]
+    pub default_weight: f64,
+}
+
+impl Default for WeightedRandomStrategy {
+    fn default() -> Self {
+        Self {
+            field: "weight".to_string(),
+            default_weight: 1.0,
+        }
+    }
+}
+
+/// [`RoundRobinStrategy`] hold the round-robin strategy configuration.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct RoundRobinStrategy {}
+
+/// [`LeastPlayersStrategy`] hold the least-players strategy configuration.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct LeastPlayersStrategy {
+    /// The name of the field that stores the player amount.
+    pub field: String,
+}
+
+/// [`ConsistentHashStrategy`] hold the consistent hash strategy configuration.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct ConsistentHashStrategy {}
+
+/// [`PowerOfTwoStrategy`] hold the power-of-two-choices strategy configuration.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct PowerOfTwoStrategy {
+    /// The name of the field that stores the player amount.
+    pub field: String,
+}
+
+/// [`HealthCheck`] hold the health check configuration.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct HealthCheck {
+    /// How often each known target is checked in seconds. Must be greater than zero.
+    pub interval: u64,
+
+    /// The timeout of a single check in (fractional) seconds.
+    pub timeout: f64,
+
+    /// The number of consecutive failed checks after which a target is removed.
+    #[serde(alias = "unhealthythreshold")]
+    pub unhealthy_threshold: u32,
+
+    /// The number of consecutive successful checks after which a removed target is restored.
+    #[serde(alias = "healthythreshold")]
+    pub healthy_threshold: u32,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        Self {
+            interval: 10,
+            timeout: 1.0,
+            unhealthy_threshold: 3,
+            healthy_threshold: 2,
+        }
+    }
+}
+
+/// [`RegionStrategy`] hold the region strategy configuration. The client location is resolved with
+/// the GeoIP database.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct RegionStrategy {
+    /// The name of the field that stores the target region.
+    pub field: String,
+
+    /// The client regions by ISO country code.
+    pub countries: HashMap<String, String>,
+
+    /// The client regions by continent code, used if the country has no region.
+    pub continents: HashMap<String, String>,
+
+    /// The region that is used if the client region is unknown (targets are kept if empty).
+    #[serde(alias = "defaultregion")]
+    pub default_region: Option<String>,
+
+    /// The distances between regions (looked up in both directions).
+    pub distances: HashMap<String, HashMap<String, u32>>,
+}
+
+impl Default for RegionStrategy {
+    fn default() -> Self {
+        Self {
+            field: "region".to_string(),
+            countries: HashMap::new(),
+            continents: HashMap::new(),
+            default_region: None,
+            distances: HashMap::new(),
+        }
+    }
+}
+
 /// [`GrpcDiscoveryAction`] hold the gRPC discovery action configuration.
 #[derive(Default, Debug, Clone, Deserialize)]
 #[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
@@ -580,9 +1291,12 @@ pub struct GrpcDiscoveryAction {
 #[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum AuthenticationAdapter {
+    Ban(BanAuthentication),
+    Chain(ChainAuthentication),
     Disabled,
     Fixed(FixedAuthentication),
     Grpc(GrpcAuthentication),
+    Offline(OfflineAuthentication),
     Mojang(MojangAuthentication),
 }
 
@@ -592,6 +1306,65 @@ impl Default for AuthenticationAdapter {
     }
 }
 
+/// [`BanAuthentication`] hold the ban authentication configuration.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct BanAuthentication {
+    /// The bans that should be applied.
+    pub bans: Vec<Ban>,
+
+    /// The path of a JSON file with additional bans (disabled if empty). It is re-read on change.
+    pub file: Option<String>,
+
+    /// The authentication adapter configuration used to authenticate players that are not banned.
+    pub adapter: Box<AuthenticationAdapter>,
+}
+
+/// [`ChainAuthentication`] hold the chain authentication configuration.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct ChainAuthentication {
+    /// The links of the chain. They are tried in order.
+    pub links: Vec<ChainAuthenticationLink>,
+}
+
+/// [`ChainAuthenticationLink`] hold the configuration of a single authentication chain link.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct ChainAuthenticationLink {
+    /// The name of the link used for logging and metrics (defaults to the index of the link).
+    pub name: Option<String>,
+
+    /// Whether the next link should be tried if this link rejects the player.
+    #[serde(alias = "fallbackonrejected")]
+    pub fallback_on_rejected: bool,
+
+    /// Whether the next link should be tried if this link fails (e.g. timeout or unavailable).
+    #[serde(alias = "fallbackonfailure")]
+    pub fallback_on_failure: bool,
+
+    /// The timeout in seconds for this link to authenticate a player (disabled if empty).
+    pub timeout: Option<u64>,
+
+    /// The authentication adapter configuration of this link.
+    pub adapter: AuthenticationAdapter,
+}
+
+impl Default for ChainAuthenticationLink {
+    fn default() -> Self {
+        Self {
+            name: None,
+            fallback_on_rejected: false,
+            fallback_on_failure: true,
+            timeout: None,
+            adapter: AuthenticationAdapter::default(),
+        }
+    }
+}
+
 /// [`FixedAuthentication`] hold the fixed authentication configuration.
 #[derive(Default, Debug, Clone, Deserialize)]
 #[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
@@ -601,6 +1374,16 @@ pub struct FixedAuthentication {
     pub profile: Option<Profile>,
 }
 
+/// [`OfflineAuthentication`] hold the offline authentication configuration.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct OfflineAuthentication {
+    /// Whether the connection should still be encrypted. If disabled, the encryption handshake is
+    /// skipped entirely.
+    pub encryption: bool,
+}
+
 /// [`GrpcAuthentication`] hold the gRPC authentication configuration.
 #[derive(Default, Debug, Clone, Deserialize)]
 #[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
@@ -611,13 +1394,79 @@ pub struct GrpcAuthentication {
 }
 
 /// [`MojangAuthentication`] hold the mojang authentication configuration.
-#[derive(Default, Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct MojangAuthentication {
     /// The server id passed to the Mojang authentication server.
     #[serde(alias = "serverid")]
     pub server_id: String,
+
+    /// The base URL of the (Yggdrasil-compatible) session server.
+    #[serde(alias = "baseurl")]
+    pub base_url: String,
+
+    /// Whether the client ip should be sent to the session server to prevent proxy connections.
+    #[serde(alias = "preventproxyconnections")]
+    pub prevent_proxy_connections: bool,
+
+    /// The timeout in seconds of a single session server request (disabled if empty).
+    pub timeout: Option<u64>,
+
+    /// The exponential backoff configuration to retry failed session server requests.
+    pub backoff: ExponentialBackoff,
+
+    /// The profile cache configuration (disabled if empty).
+    pub cache: Option<MojangCache>,
+}
+
+/// [`MojangCache`] hold the mojang profile cache configuration.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct MojangCache {
+    /// The duration in seconds for which a verified profile is used instead of asking the session
+    /// server (disabled if zero). Cached profiles are not proven by the client, so any client behind
+    /// the same ip (e.g. NAT) can impersonate the cached player within this duration.
+    pub ttl: u64,
+
+    /// The maximum age in seconds of a cached profile that is used while the session server is
+    /// unavailable (disabled if empty).
+    #[serde(alias = "maxstaleness")]
+    pub max_staleness: Option<u64>,
+
+    /// The maximum number of failed authentications per username and client ip within the failure
+    /// window (disabled if empty).
+    #[serde(alias = "failurelimit")]
+    pub failure_limit: Option<u32>,
+
+    /// The duration in seconds of the window in which failures are counted.
+    #[serde(alias = "failurewindow")]
+    pub failure_window: u64,
+}
+
+impl Default for MojangCache {
+    fn default() -> Self {
+        Self {
+            ttl: 0,
+            max_staleness: None,
+            failure_limit: None,
+            failure_window: 60,
+        }
+    }
+}
+
+impl Default for MojangAuthentication {
+    fn default() -> Self {
+        Self {
+            server_id: String::new(),
+            base_url: "https://sessionserver.mojang.com".to_string(),
+            prevent_proxy_connections: false,
+            timeout: None,
+            backoff: ExponentialBackoff::once(),
+            cache: None,
+        }
+    }
 }
 
 /// [`LocalizationAdapter`] hold the localization adapter configuration.
@@ -663,36 +1512,90 @@ impl Default for FixedLocalization {
                     "disconnect_timeout" => "{\"text\":\"Disconnected: No response from client (keep-alive timeout)\"}",
                     "disconnect_no_target" => "{\"text\":\"Disconnected: No available server to handle your connection\"}",
                     "disconnect_unauthenticated" => "{\"text\":\"Disconnected: Could not authenticate client\"}",
+                    "disconnect_banned" => "{\"text\":\"Disconnected: You are banned from this server (reason: {reason}, remaining: {remaining})\"}",
+                    "disconnect_banned_permanent" => "{\"text\":\"Disconnected: You are permanently banned from this server (reason: {reason})\"}",
+                    "disconnect_maintenance" => "{\"text\":\"Disconnected: The server is currently under maintenance\"}",
+                    "disconnect_invalid_username" => "{\"text\":\"Disconnected: Invalid username\"}",
+                    "disconnect_encryption_unexpected_packet" => "{\"text\":\"Disconnected: Unexpected packet during encryption\"}",
+                    "disconnect_encryption_shared_secret_decryption" => "{\"text\":\"Disconnected: Could not decrypt the shared secret\"}",
+                    "disconnect_encryption_shared_secret_length" => "{\"text\":\"Disconnected: Invalid shared secret\"}",
+                    "disconnect_encryption_verify_token_decryption" => "{\"text\":\"Disconnected: Could not decrypt the verify token\"}",
+                    "disconnect_encryption_verify_token_mismatch" => "{\"text\":\"Disconnected: Invalid verify token\"}",
                 },
                 "es" => hashmap! {
                     "locale" => "Español",
                     "disconnect_timeout" => "{\"text\":\"Desconectado: No hubo respuesta del cliente (tiempo de espera agotado)\"}",
                     "disconnect_no_target" => "{\"text\":\"Desconectado: No hay un servidor disponible para manejar tu conexión\"}",
                     "disconnect_unauthenticated" => "{\"text\":\"Desconectado: No se pudo autenticar el cliente\"}",
+                    "disconnect_banned" => "{\"text\":\"Desconectado: Estás baneado de este servidor (motivo: {reason}, restante: {remaining})\"}",
+                    "disconnect_banned_permanent" => "{\"text\":\"Desconectado: Estás baneado permanentemente de este servidor (motivo: {reason})\"}",
+                    "disconnect_maintenance" => "{\"text\":\"Desconectado: El servidor está en mantenimiento\"}",
+                    "disconnect_invalid_username" => "{\"text\":\"Desconectado: Nombre de usuario no válido\"}",
+                    "disconnect_encryption_unexpected_packet" => "{\"text\":\"Desconectado: Paquete inesperado durante el cifrado\"}",
+                    "disconnect_encryption_shared_secret_decryption" => "{\"text\":\"Desconectado: No se pudo descifrar el secreto compartido\"}",
+                    "disconnect_encryption_shared_secret_length" => "{\"text\":\"Desconectado: Secreto compartido no válido\"}",
+                    "disconnect_encryption_verify_token_decryption" => "{\"text\":\"Desconectado: No se pudo descifrar el token de verificación\"}",
+                    "disconnect_encryption_verify_token_mismatch" => "{\"text\":\"Desconectado: Token de verificación no válido\"}",
                 },
                 "fr" => hashmap! {
                     "locale" => "Français",
                     "disconnect_timeout" => "{\"text\":\"Déconnecté : aucune réponse du client (délai de keep-alive dépassé)\"}",
                     "disconnect_no_target" => "{\"text\":\"Déconnecté : aucun serveur disponible pour traiter votre connexion\"}",
                     "disconnect_unauthenticated" => "{\"text\":\"DDéconnecté : Impossible d’authentifier le client\"}",
+                    "disconnect_banned" => "{\"text\":\"Déconnecté : vous êtes banni de ce serveur (raison : {reason}, temps restant : {remaining})\"}",
+                    "disconnect_banned_permanent" => "{\"text\":\"Déconnecté : vous êtes banni définitivement de ce serveur (raison : {reason})\"}",
+                    "disconnect_maintenance" => "{\"text\":\"Déconnecté : le serveur est actuellement en maintenance\"}",
+                    "disconnect_invalid_username" => "{\"text\":\"Déconnecté : nom d’utilisateur invalide\"}",
+                    "disconnect_encryption_unexpected_packet" => "{\"text\":\"Déconnecté : paquet inattendu pendant le chiffrement\"}",
+                    "disconnect_encryption_shared_secret_decryption" => "{\"text\":\"Déconnecté : impossible de déchiffrer le secret partagé\"}",
+                    "disconnect_encryption_shared_secret_length" => "{\"text\":\"Déconnecté : secret partagé invalide\"}",
+                    "disconnect_encryption_verify_token_decryption" => "{\"text\":\"Déconnecté : impossible de déchiffrer le jeton de vérification\"}",
+                    "disconnect_encryption_verify_token_mismatch" => "{\"text\":\"Déconnecté : jeton de vérification invalide\"}",
                 },
                 "de" => hashmap! {
                     "locale" => "Deutsch",
                     "disconnect_timeout" => "{\"text\":\"Verbindung getrennt: Keine Antwort vom Client (Keep-Alive-Timeout)\"}",
                     "disconnect_no_target" => "{\"text\":\"Verbindung getrennt: Kein verfügbarer Server für diese Verbindung\"}",
                     "disconnect_unauthenticated" => "{\"text\":\"Verbindung getrennt: Client konnte nicht authentifiziert werden\"}",
+                    "disconnect_banned" => "{\"text\":\"Verbindung getrennt: Du bist von diesem Server gesperrt (Grund: {reason}, verbleibend: {remaining})\"}",
+                    "disconnect_banned_permanent" => "{\"text\":\"Verbindung getrennt: Du bist dauerhaft von diesem Server gesperrt (Grund: {reason})\"}",
+                    "disconnect_maintenance" => "{\"text\":\"Verbindung getrennt: Der Server wird gerade gewartet\"}",
+                    "disconnect_invalid_username" => "{\"text\":\"Verbindung getrennt: Ungültiger Benutzername\"}",
+                    "disconnect_encryption_unexpected_packet" => "{\"text\":\"Verbindung getrennt: Unerwartetes Paket während der Verschlüsselung\"}",
+                    "disconnect_encryption_shared_secret_decryption" => "{\"text\":\"Verbindung getrennt: Das gemeinsame Geheimnis konnte nicht entschlüsselt werden\"}",
+                    "disconnect_encryption_shared_secret_length" => "{\"text\":\"Verbindung getrennt: Ungültiges gemeinsames Geheimnis\"}",
+                    "disconnect_encryption_verify_token_decryption" => "{\"text\":\"Verbindung getrennt: Das Verifizierungstoken konnte nicht entschlüsselt werden\"}",
+                    "disconnect_encryption_verify_token_mismatch" => "{\"text\":\"Verbindung getrennt: Ungültiges Verifizierungstoken\"}",
                 },
                 "zh-CN" => hashmap! {
                     "locale" => "简体中文",
                     "disconnect_timeout" => "{\"text\":\"已断开连接：客户端无响应（保持连接超时）\"}",
                     "disconnect_no_target" => "{\"text\":\"已断开连接：没有可用的服务器来处理你的连接\"}",
                     "disconnect_no_target" => "{\"text\":\"已断开连接：无法验证客户端\"}",
+                    "disconnect_banned" => "{\"text\":\"已断开连接：你已被此服务器封禁（原因：{reason}，剩余时间：{remaining}）\"}",
+                    "disconnect_banned_permanent" => "{\"text\":\"已断开连接：你已被此服务器永久封禁（原因：{reason}）\"}",
+                    "disconnect_maintenance" => "{\"text\":\"已断开连接：服务器正在维护中\"}",
+                    "disconnect_invalid_username" => "{\"text\":\"已断开连接：无效的用户名\"}",
+                    "disconnect_encryption_unexpected_packet" => "{\"text\":\"已断开连接：加密过程中收到意外的数据包\"}",
+                    "disconnect_encryption_shared_secret_decryption" => "{\"text\":\"已断开连接：无法解密共享密钥\"}",
+                    "disconnect_encryption_shared_secret_length" => "{\"text\":\"已断开连接：无效的共享密钥\"}",
+                    "disconnect_encryption_verify_token_decryption" => "{\"text\":\"已断开连接：无法解密验证令牌\"}",
+                    "disconnect_encryption_verify_token_mismatch" => "{\"text\":\"已断开连接：无效的验证令牌\"}",
                 },
                 "ru" => hashmap! {
                     "locale" => "English",
                     "disconnect_timeout" => "{\"text\":\"Отключено: нет ответа от клиента (тайм-аут keep-alive)\"}",
                     "disconnect_no_target" => "{\"text\":\"Отключено: нет доступного сервера для обработки подключения\"}",
                     "disconnect_unauthenticated" => "{\"text\":\"Отключено: не удалось аутентифицировать клиента\"}",
+                    "disconnect_banned" => "{\"text\":\"Отключено: вы заблокированы на этом сервере (причина: {reason}, осталось: {remaining})\"}",
+                    "disconnect_banned_permanent" => "{\"text\":\"Отключено: вы навсегда заблокированы на этом сервере (причина: {reason})\"}",
+                    "disconnect_maintenance" => "{\"text\":\"Отключено: сервер находится на техническом обслуживании\"}",
+                    "disconnect_invalid_username" => "{\"text\":\"Отключено: недопустимое имя пользователя\"}",
+                    "disconnect_encryption_unexpected_packet" => "{\"text\":\"Отключено: неожиданный пакет во время шифрования\"}",
+                    "disconnect_encryption_shared_secret_decryption" => "{\"text\":\"Отключено: не удалось расшифровать общий секрет\"}",
+                    "disconnect_encryption_shared_secret_length" => "{\"text\":\"Отключено: недопустимый общий секрет\"}",
+                    "disconnect_encryption_verify_token_decryption" => "{\"text\":\"Отключено: не удалось расшифровать токен проверки\"}",
+                    "disconnect_encryption_verify_token_mismatch" => "{\"text\":\"Отключено: недопустимый токен проверки\"}",
                 },
             },
         }
@@ -720,7 +1623,7 @@ impl Config {
         let s = config::Config::builder()
             // load custom configuration from file (at runtime)
             .add_source(File::with_name(&config_file).required(false))
-            .add_source(File::new(&auth_secret_file, AuthSecretFile).required(false))
+            .add_source(AuthSecretFile::new(auth_secret_file))
             // add in config from the environment (with a prefix of APP)
             // e.g. `PASSAGE_DEBUG=1` would set the `debug` key, on the other hand,
             // `PASSAGE_CACHE_REDIS_ENABLED=1` would enable the redis cache.
@@ -737,29 +1640,97 @@ impl Config {
     }
 }
 
+/// [`AuthSecretFile`] is the config source of the auth cookie secrets. It reads a single secret,
+/// a JSON keyring or a directory of keys, as described in the [module documentation](crate::config).
 #[derive(Debug, Clone)]
-pub struct AuthSecretFile;
-
-impl Format for AuthSecretFile {
-    fn parse(
-        &self,
-        uri: Option<&String>,
-        text: &str,
-    ) -> Result<Map<String, Value>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut result = Map::new();
+pub struct AuthSecretFile {
+    path: PathBuf,
+}
 
-        result.insert(
-            // key has to match config param
-            "auth_secret".to_owned(),
-            Value::new(uri, ValueKind::String(text.into())),
-        );
+impl AuthSecretFile {
+    /// Creates a new source for the given file or directory. It is optional, missing paths are ignored.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
 
-        Ok(result)
+    /// Reads the keys of a directory with one file per key.
+    fn read_dir(&self) -> Result<AuthKeys, ConfigError> {
+        let mut keys = HashMap::new();
+        let mut active = None;
+        for entry in fs::read_dir(&self.path).map_err(|err| ConfigError::Foreign(err.into()))? {
+            let path = entry
+                .map_err(|err| ConfigError::Foreign(err.into()))?
+                .path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            // skip hidden files (e.g. the `..data` links of kubernetes secrets) and directories
+            if name.starts_with('.') || !path.is_file() {
+                continue;
+            }
+            let content =
+                fs::read_to_string(&path).map_err(|err| ConfigError::Foreign(err.into()))?;
+            if name == "active" {
+                active = Some(content.trim().to_string());
+            } else {
+                keys.insert(name.to_string(), content);
+            }
+        }
+        let active = active
+            .or_else(|| keys.keys().max().cloned())
+            .unwrap_or_default();
+        Ok(AuthKeys { active, keys })
     }
 }
 
-impl FileStoredFormat for AuthSecretFile {
-    fn file_extensions(&self) -> &'static [&'static str] {
-        &[]
+impl Source for AuthSecretFile {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        let uri = Some(self.path.display().to_string());
+        let uri = uri.as_ref();
+        let mut result = Map::new();
+
+        let keys = if self.path.is_dir() {
+            self.read_dir()?
+        } else if self.path.is_file() {
+            let text =
+                fs::read_to_string(&self.path).map_err(|err| ConfigError::Foreign(err.into()))?;
+            if !text.trim_start().starts_with('{') {
+                // key has to match config param
+                result.insert(
+                    "auth_secret".to_owned(),
+                    Value::new(uri, ValueKind::String(text)),
+                );
+                return Ok(result);
+            }
+            serde_json::from_str(&text).map_err(|err| ConfigError::Foreign(err.into()))?
+        } else {
+            return Ok(result);
+        };
+
+        let secrets = keys
+            .keys
+            .into_iter()
+            .map(|(id, secret)| (id, Value::new(uri, ValueKind::String(secret))))
+            .collect();
+        let table = Map::from([
+            (
+                "active".to_owned(),
+                Value::new(uri, ValueKind::String(keys.active)),
+            ),
+            (
+                "keys".to_owned(),
+                Value::new(uri, ValueKind::Table(secrets)),
+            ),
+        ]);
+        // key has to match config param
+        result.insert(
+            "auth_keys".to_owned(),
+            Value::new(uri, ValueKind::Table(table)),
+        );
+        Ok(result)
     }
 }
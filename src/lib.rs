@@ -10,16 +10,23 @@ use crate::adapter::discovery::DynDiscoveryActionAdapter;
 use crate::adapter::localization::DynLocalizationAdapter;
 use crate::adapter::status::DynStatusAdapter;
 use crate::config::Config;
+use passage_adapters::geoip::GeoIpDatabase;
 use passage_protocol::config::{Config as ListenerConfig, ProxyProtocol};
+use passage_protocol::cookie::{CookieFormat, CookieRule, Key, Keyring};
+use passage_protocol::crypto::{self, KeyPair};
+use passage_protocol::ip_binding::IpBinding;
 use passage_protocol::listener::Listener;
+use passage_protocol::nonce_store::{MemoryNonceStore, NonceStore, ReplayPolicy};
+use passage_protocol::probe::{Probe, ProbeMethod};
 use passage_protocol::rate_limiter::RateLimiter;
-use passage_protocol::routes::Route;
+use passage_protocol::routes::{Route, RouteCookies, TieBreaker};
 use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
 use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio_util::sync::CancellationToken;
-use tracing::debug;
+use tracing::{debug, info, warn};
 
 /// Initializes the Minecraft tcp server and creates all necessary resources for the operation.
 ///
@@ -34,15 +41,81 @@ use tracing::debug;
 pub async fn start(config: Config) -> Result<(), Box<dyn std::error::Error>> {
     // initialize the adapters
     debug!("building routes");
+    let geoip_database = config.geoip.as_ref().map(|geoip| geoip.database.clone());
+    let geoip = match config.geoip {
+        Some(geoip) => Some(Arc::new(
+            GeoIpDatabase::open(geoip.database)?
+                .with_reload_interval(Duration::from_secs(geoip.reload_interval)),
+        )),
+        None => None,
+    };
     let mut routes = vec![];
     for route in config.routes {
+        let discovery_adapter = Arc::new(
+            DynDiscoveryActionAdapter::from_config(route.discovery, geoip.as_ref()).await?,
+        );
+        let mut status_adapter =
+            DynStatusAdapter::from_config(route.status, geoip.as_ref()).await?;
+        let mut authentication_adapter =
+            DynAuthenticationAdapter::from_config(route.authentication).await?;
+        let localization_adapter =
+            Arc::new(DynLocalizationAdapter::from_config(route.localization).await?);
+        if let Some(motd) = route.motd {
+            status_adapter = status_adapter.with_motd(motd, &localization_adapter, geoip.as_ref());
+        }
+        if let Some(maintenance) = route.maintenance {
+            status_adapter = status_adapter.with_maintenance(maintenance.clone());
+            authentication_adapter = authentication_adapter.with_maintenance(maintenance)?;
+        }
+        if let Some(favicon) = route.favicon {
+            status_adapter = status_adapter.with_favicon(favicon)?;
+        }
+        status_adapter =
+            status_adapter.with_processors(route.status_processors, &localization_adapter);
+        let mut cookies = RouteCookies {
+            request: vec![],
+            clear: route.cookies.clear,
+        };
+        for request in route.cookies.request {
+            let format = match request.format {
+                config::CookieFormat::Raw => CookieFormat::Raw,
+                config::CookieFormat::Json => CookieFormat::Json,
+            };
+            let mut rule = CookieRule::new(request.key, format);
+            if let Some(name) = request.secret {
+                let Some(secret) = config.cookie_secrets.get(&name) else {
+                    return Err(format!("unknown cookie secret: {name}").into());
+                };
+                rule = rule.with_secret(secret.as_bytes());
+            }
+            cookies.request.push(rule);
+        }
         routes.push(Arc::new(Route {
             hostname: Regex::new(&route.hostname)?,
-            status_adapter: DynStatusAdapter::from_config(route.status).await?,
-            discovery_adapter: DynDiscoveryActionAdapter::from_config(route.discovery).await?,
-            authentication_adapter: DynAuthenticationAdapter::from_config(route.authentication)
-                .await?,
-            localization_adapter: DynLocalizationAdapter::from_config(route.localization).await?,
+            status_adapter,
+            discovery_adapter,
+            authentication_adapter,
+            localization_adapter,
+            cookies,
+            tie_breaker: match route.tie_breaker {
+                config::TieBreaker::First => TieBreaker::First,
+                config::TieBreaker::Random => TieBreaker::Random,
+                config::TieBreaker::Hash => TieBreaker::Hash,
+            },
+            probe: match route.probe {
+                Some(probe) => {
+                    let method = match probe.method {
+                        config::ProbeMethod::Tcp => ProbeMethod::Tcp,
+                        config::ProbeMethod::Status => ProbeMethod::Status,
+                    };
+                    Some(Arc::new(
+                        Probe::new(method, Duration::try_from_secs_f64(probe.timeout)?)
+                            .with_cache_duration(Duration::from_secs(probe.cache_duration))
+                            .with_attempts(probe.attempts),
+                    ))
+                }
+                None => None,
+            },
         }));
     }
     debug!(routes = ?routes, "build routes");
@@ -68,17 +141,97 @@ pub async fn start(config: Config) -> Result<(), Box<dyn std::error::Error>> {
         .system_observer_interval
         .map(|seconds| metrics::system::Observer::new(Duration::from_secs(seconds)));
 
+    // initialize the server key pair, so the first login does not have to generate it
+    let key_pair = match (config.server_key, config.server_key_file) {
+        (Some(pem), _) => KeyPair::from_pem(&pem)?,
+        (None, Some(path)) => KeyPair::from_pem(&std::fs::read_to_string(path)?)?,
+        (None, None) => {
+            let bits = config.server_key_size;
+            tokio::task::spawn_blocking(move || KeyPair::generate(bits)).await??
+        }
+    };
+    info!(bits = key_pair.bits(), "using server key pair");
+    if !crypto::init_key_pair(key_pair) {
+        warn!("server key pair was already initialized");
+    }
+
     // build and start the protocol
     debug!("building protocol");
+    let auth_keyring = match config.auth_keys {
+        Some(auth_keys) => {
+            let mut keys = HashMap::new();
+            for (id, value) in auth_keys.keys {
+                let key = Key::parse(&id, &value)?;
+                keys.insert(id, key);
+            }
+            Some(Keyring::new(auth_keys.active, keys)?)
+        }
+        None => config.auth_secret.map(Keyring::single),
+    }
+    .map(|keyring| keyring.with_encryption(config.auth_cookie_encryption))
+    .transpose()?;
+
+    // publish the public keys of asymmetric auth cookie keys, so backend servers can verify cookies
+    if let Some(public_keys) = auth_keyring.as_ref().map(Keyring::public_keys) {
+        let public_keys: BTreeMap<_, _> = public_keys.into_iter().collect();
+        for (id, pem) in &public_keys {
+            info!(
+                key = id,
+                public_key = pem,
+                "using asymmetric auth cookie key"
+            );
+        }
+        if let Some(path) = &config.auth_public_keys_file {
+            debug!(path = path, "writing auth cookie public keys");
+            std::fs::write(path, serde_json::to_string_pretty(&public_keys)?)?;
+        }
+    }
+    // build the auth cookie replay protection
+    let replay_policy = match config.auth_cookie_replay_policy {
+        config::ReplayPolicy::Reusable => ReplayPolicy::Reusable,
+        config::ReplayPolicy::SingleUse => ReplayPolicy::SingleUse,
+        config::ReplayPolicy::Sliding => ReplayPolicy::Sliding,
+    };
+    // expired nonces are removed once per cookie expiry, so they are kept at most twice as long
+    let nonce_store: Option<Arc<dyn NonceStore>> =
+        (replay_policy != ReplayPolicy::Reusable).then(|| {
+            let cleanup_interval = Duration::from_secs(config.auth_cookie_expiry);
+            Arc::new(MemoryNonceStore::new(cleanup_interval)) as _
+        });
+
+    // build the auth cookie ip binding
+    let ip_binding = match config.auth_cookie_ip_binding {
+        config::IpBinding::Exact => IpBinding::Exact,
+        config::IpBinding::Prefix(prefix) => IpBinding::Prefix {
+            ipv4: prefix.ipv4,
+            ipv6: prefix.ipv6,
+        },
+        config::IpBinding::Asn(asn) => match &geoip {
+            Some(geoip) if geoip_database.as_ref() == Some(&asn.database) => {
+                IpBinding::Asn(Arc::clone(geoip))
+            }
+            _ => IpBinding::Asn(Arc::new(
+                GeoIpDatabase::open(asn.database)?
+                    .with_reload_interval(Duration::from_secs(asn.reload_interval)),
+            )),
+        },
+        config::IpBinding::None => IpBinding::None,
+    };
+
     let listener_config = ListenerConfig {
-        auth_secret: config.auth_secret.clone(),
+        auth_keyring,
         max_packet_length: config.max_packet_length,
         auth_cookie_expiry: config.auth_cookie_expiry,
+        auth_cookie_replay_policy: replay_policy,
+        nonce_store,
+        auth_cookie_ip_binding: ip_binding,
         proxy_protocol: config.proxy_protocol.map(|c| ProxyProtocol {
             allow_v1: c.allow_v1,
             allow_v2: c.allow_v2,
         }),
         connection_timeout: config.timeout,
+        session_history: config.session_history,
+        instance: config.instance.or_else(|| std::env::var("HOSTNAME").ok()),
     };
     let mut listener = Listener::new(routes.into(), rate_limiter, listener_config);
 
@@ -15,6 +15,7 @@ use opentelemetry_semantic_conventions::{
     attribute::{DEPLOYMENT_ENVIRONMENT_NAME, SERVICE_VERSION},
 };
 use passage::config::Config;
+use passage_protocol::crypto::{DEFAULT_KEY_SIZE, KeyPair};
 use std::borrow::Cow::Owned;
 use std::collections::HashMap;
 use std::env;
@@ -44,6 +45,22 @@ fn resource(environment: &str) -> Resource {
 /// This initializes the logging, aggregates configuration and starts the multithreaded tokio runtime. This is only a
 /// thin-wrapper around the passage crate that supplies the necessary settings.
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // handle the subcommands
+    let mut args = env::args().skip(1);
+    if let Some(command) = args.next() {
+        return match command.as_str() {
+            "generate-key" => {
+                let bits = match args.next() {
+                    Some(bits) => bits.parse()?,
+                    None => DEFAULT_KEY_SIZE,
+                };
+                print!("{}", KeyPair::generate(bits)?.to_pem()?);
+                Ok(())
+            }
+            _ => Err(format!("unknown command: {command}").into()),
+        };
+    }
+
     // parse the arguments and configuration
     let config = Config::read()?;
 
@@ -172,7 +189,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             info!("sentry is disabled");
         }
 
-        if config.auth_secret.is_some() {
+        if config.auth_secret.is_some() || config.auth_keys.is_some() {
             info!("auth cookie is enabled");
         } else {
             info!("auth cookie is disabled");